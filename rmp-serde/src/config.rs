@@ -0,0 +1,257 @@
+//! A single policy for both serialization and deserialization.
+
+use rmp::TypedArray;
+use rmp::decode::DecodeLimits;
+
+use encode::{EnumRepr, StructRepr, VariantId};
+
+/// Settings shared by `Serializer` and `Deserializer`.
+///
+/// A `Config` collects every setting of both sides into a single value, which can be defined once
+/// and passed to `Serializer::with_config`, `Deserializer::with_config` and
+/// `Deserializer::from_read_ref_with_config` wherever data is encoded or decoded. The defaults
+/// match `Serializer::new` and `Deserializer::new`.
+///
+/// The deserializer accepts structs and enums in every representation the serializer can be
+/// configured to write, so the struct and enum settings affect the serializer only, while the
+/// limits affect the deserializer only, except for the depth limit, which both enforce.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rmp_serde as rmps;
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+///
+/// use serde::{Deserialize, Serialize};
+/// use rmps::{Config, Deserializer, Serializer};
+/// use rmps::encode::{StructRepr, VariantId};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// # fn main() {
+/// let config = Config::default()
+///     .with_struct_repr(StructRepr::Map)
+///     .with_variant_id(VariantId::Name)
+///     .with_max_depth(32);
+///
+/// let mut se = Serializer::with_config(Vec::new(), config);
+/// Point { x: 1, y: 2 }.serialize(&mut se).unwrap();
+/// let buf = se.into_inner();
+///
+/// // {"x": 1, "y": 2}
+/// assert_eq!(vec![0x82, 0xa1, 0x78, 0x01, 0xa1, 0x79, 0x02], buf);
+///
+/// let mut de = Deserializer::with_config(&buf[..], config);
+/// assert_eq!(Point { x: 1, y: 2 }, Deserialize::deserialize(&mut de).unwrap());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    struct_repr: StructRepr,
+    variant_id: VariantId,
+    enum_repr: EnumRepr,
+    bare_unit_variants: bool,
+    legacy_raw: bool,
+    human_readable: bool,
+    compact_ints: bool,
+    compact_floats: bool,
+    i128_ext_type: Option<i8>,
+    u128_ext_type: Option<i8>,
+    typed_array_ext_type: i8,
+    limits: DecodeLimits,
+}
+
+impl Default for Config {
+    /// Returns the configuration of `Serializer::new` and `Deserializer::new`: structs as tuples,
+    /// enums as a map from the variant index, compact integers, floats of their own precision,
+    /// no extension for 128-bit integers, `TypedArray::DEFAULT_EXT_TYPE` for typed arrays and the
+    /// default `DecodeLimits`.
+    fn default() -> Config {
+        Config {
+            struct_repr: StructRepr::Tuple,
+            variant_id: VariantId::Index,
+            enum_repr: EnumRepr::Map,
+            bare_unit_variants: false,
+            legacy_raw: false,
+            human_readable: true,
+            compact_ints: true,
+            compact_floats: false,
+            i128_ext_type: None,
+            u128_ext_type: None,
+            typed_array_ext_type: TypedArray::DEFAULT_EXT_TYPE,
+            limits: DecodeLimits::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Sets how structs are written, which is as a tuple without field names by default.
+    pub fn with_struct_repr(mut self, repr: StructRepr) -> Config {
+        self.struct_repr = repr;
+        self
+    }
+
+    /// Sets how enum variants are identified, which is by their index by default.
+    pub fn with_variant_id(mut self, id: VariantId) -> Config {
+        self.variant_id = id;
+        self
+    }
+
+    /// Sets how enum variants wrap their identifier and payload, which is a single-entry map by
+    /// default.
+    pub fn with_enum_repr(mut self, repr: EnumRepr) -> Config {
+        self.enum_repr = repr;
+        self
+    }
+
+    /// Sets whether unit variants are written as a bare identifier, which is disabled by default.
+    pub fn with_bare_unit_variants(mut self, enabled: bool) -> Config {
+        self.bare_unit_variants = enabled;
+        self
+    }
+
+    /// Sets whether bytes are handled in the compatibility mode with the old MessagePack
+    /// specification, which is disabled by default.
+    ///
+    /// See `Serializer::set_legacy_raw` and `Deserializer::set_legacy_raw` for details.
+    pub fn with_legacy_raw(mut self, enabled: bool) -> Config {
+        self.legacy_raw = enabled;
+        self
+    }
+
+    /// Sets what `is_human_readable` of both sides returns, which is `true` by default.
+    ///
+    /// Types like `IpAddr` pick a more compact form when it's `false`, so both sides must agree
+    /// on it.
+    pub fn with_human_readable(mut self, enabled: bool) -> Config {
+        self.human_readable = enabled;
+        self
+    }
+
+    /// Sets whether integers are written in the smallest form that fits the value, which is
+    /// enabled by default. Otherwise they are written in the form matching their Rust type, like
+    /// `0xd2` for `i32`, regardless of the value.
+    pub fn with_compact_ints(mut self, enabled: bool) -> Config {
+        self.compact_ints = enabled;
+        self
+    }
+
+    /// Sets whether `f64` values, which are exactly representable as `f32`, are written as the
+    /// latter, which is disabled by default.
+    pub fn with_compact_floats(mut self, enabled: bool) -> Config {
+        self.compact_floats = enabled;
+        self
+    }
+
+    /// Sets the extension type `i128` values are written as, like `rmp::encode::write_i128` does,
+    /// which is none by default.
+    ///
+    /// Without an extension type only the values fitting in 64 bits can be written, as native
+    /// integers. Native integers are accepted for `i128` regardless of this setting, while the
+    /// extension is accepted only if the type is set. `rmp::DEFAULT_I128_EXT_TYPE` is a suggested
+    /// type id, but extension type ids belong to the application, so nothing is used implicitly.
+    pub fn with_i128_ext_type(mut self, ty: Option<i8>) -> Config {
+        self.i128_ext_type = ty;
+        self
+    }
+
+    /// Sets the extension type `u128` values are written as, like `rmp::encode::write_u128` does,
+    /// which is none by default.
+    ///
+    /// See `with_i128_ext_type`.
+    pub fn with_u128_ext_type(mut self, ty: Option<i8>) -> Config {
+        self.u128_ext_type = ty;
+        self
+    }
+
+    /// Sets the extension type of the typed arrays handled by the `typed_array` module, which is
+    /// `TypedArray::DEFAULT_EXT_TYPE` by default.
+    pub fn with_typed_array_ext_type(mut self, ty: i8) -> Config {
+        self.typed_array_ext_type = ty;
+        self
+    }
+
+    /// Sets the maximum nesting depth of arrays and maps, which both sides enforce.
+    pub fn with_max_depth(mut self, depth: usize) -> Config {
+        self.limits.max_depth = depth;
+        self
+    }
+
+    /// Sets the limits the input of a deserializer is checked against, including the depth
+    /// limit.
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Config {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns how structs are written.
+    pub fn struct_repr(&self) -> StructRepr {
+        self.struct_repr
+    }
+
+    /// Returns how enum variants are identified.
+    pub fn variant_id(&self) -> VariantId {
+        self.variant_id
+    }
+
+    /// Returns how enum variants wrap their identifier and payload.
+    pub fn enum_repr(&self) -> EnumRepr {
+        self.enum_repr
+    }
+
+    /// Returns whether unit variants are written as a bare identifier.
+    pub fn bare_unit_variants(&self) -> bool {
+        self.bare_unit_variants
+    }
+
+    /// Returns whether the compatibility mode with the old MessagePack specification is enabled.
+    pub fn legacy_raw(&self) -> bool {
+        self.legacy_raw
+    }
+
+    /// Returns what `is_human_readable` of both sides returns.
+    pub fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    /// Returns whether integers are written in the smallest form that fits the value.
+    pub fn compact_ints(&self) -> bool {
+        self.compact_ints
+    }
+
+    /// Returns whether `f64` values are written as `f32` when it's lossless.
+    pub fn compact_floats(&self) -> bool {
+        self.compact_floats
+    }
+
+    /// Returns the extension type of `i128` values, if any.
+    pub fn i128_ext_type(&self) -> Option<i8> {
+        self.i128_ext_type
+    }
+
+    /// Returns the extension type of `u128` values, if any.
+    pub fn u128_ext_type(&self) -> Option<i8> {
+        self.u128_ext_type
+    }
+
+    /// Returns the extension type of typed arrays.
+    pub fn typed_array_ext_type(&self) -> i8 {
+        self.typed_array_ext_type
+    }
+
+    /// Returns the maximum nesting depth of arrays and maps.
+    pub fn max_depth(&self) -> usize {
+        self.limits.max_depth
+    }
+
+    /// Returns the limits the input of a deserializer is checked against.
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+}
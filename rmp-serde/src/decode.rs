@@ -3,16 +3,29 @@
 use std::error;
 use std::fmt::{self, Display, Formatter};
 use std::io::{self, Cursor, ErrorKind, Read};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
 use std::str::{self, Utf8Error};
+#[cfg(feature = "async")]
+use std::future::Future;
 
 use byteorder::{self, ReadBytesExt};
+#[cfg(feature = "async")]
+use futures_io::AsyncRead;
+#[cfg(feature = "async")]
+use futures_util::FutureExt;
 
 use serde;
-use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, Visitor};
+use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, IntoDeserializer, Unexpected,
+                Visitor};
+use serde::de::value::{BorrowedBytesDeserializer, BytesDeserializer};
 
 use rmp;
-use rmp::Marker;
-use rmp::decode::{self, MarkerReadError, DecodeStringError, ValueReadError, NumValueReadError};
+use rmp::{Family, Marker};
+use rmp::decode::{MarkerReadError, DecodeStringError, ValueReadError, NumValueReadError,
+                  DecodeLimits, LimitError, Limiter, Unpacker, ValidateError};
+
+use {Config, MSGPACK_EXT_STRUCT_NAME, TYPED_ARRAY_STRUCT_NAME};
 
 /// Enum representing errors that can occur while decoding MessagePack data.
 #[derive(Debug)]
@@ -37,8 +50,13 @@ pub enum Error {
     Syntax(String),
     /// An encoded string could not be parsed as UTF-8.
     Utf8Error(Utf8Error),
-    /// The depth limit was exceeded; not currently used.
+    /// Arrays and maps are nested deeper than the limit set by `Deserializer::set_max_depth`.
     DepthLimitExceeded,
+    /// A string, binary, extension, array or map with the enclosed marker is longer than the
+    /// limits allow.
+    LengthLimitExceeded(Marker),
+    /// The message is larger than the limits allow.
+    TotalLimitExceeded,
 }
 
 impl error::Error for Error {
@@ -57,6 +75,8 @@ impl error::Error for Error {
             Error::Syntax(..) => None,
             Error::Utf8Error(ref err) => Some(err),
             Error::DepthLimitExceeded => None,
+            Error::LengthLimitExceeded(..) => None,
+            Error::TotalLimitExceeded => None,
         }
     }
 }
@@ -90,7 +110,8 @@ impl From<Utf8Error> for Error {
 impl From<ValueReadError> for Error {
     fn from(err: ValueReadError) -> Error {
         match err {
-            ValueReadError::TypeMismatch(marker) => Error::TypeMismatch(marker),
+            ValueReadError::TypeMismatch(marker) |
+            ValueReadError::NonCanonical(marker) => Error::TypeMismatch(marker),
             ValueReadError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
             ValueReadError::InvalidDataRead(err) => Error::InvalidDataRead(err),
         }
@@ -100,7 +121,8 @@ impl From<ValueReadError> for Error {
 impl From<NumValueReadError> for Error {
     fn from(err: NumValueReadError) -> Error {
         match err {
-            NumValueReadError::TypeMismatch(marker) => Error::TypeMismatch(marker),
+            NumValueReadError::TypeMismatch(marker) |
+            NumValueReadError::NonCanonical(marker) => Error::TypeMismatch(marker),
             NumValueReadError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
             NumValueReadError::InvalidDataRead(err) => Error::InvalidDataRead(err),
             NumValueReadError::OutOfRange => Error::OutOfRange,
@@ -113,13 +135,34 @@ impl<'a> From<DecodeStringError<'a>> for Error {
         match err {
             DecodeStringError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
             DecodeStringError::InvalidDataRead(err) => Error::InvalidDataRead(err),
-            DecodeStringError::TypeMismatch(marker) => Error::TypeMismatch(marker),
+            DecodeStringError::TypeMismatch(marker) |
+            DecodeStringError::NonCanonical(marker) => Error::TypeMismatch(marker),
             DecodeStringError::BufferSizeTooSmall(..) => Error::Uncategorized("BufferSizeTooSmall".to_string()),
             DecodeStringError::InvalidUtf8(..) => Error::Uncategorized("InvalidUtf8".to_string()),
         }
     }
 }
 
+impl From<LimitError> for Error {
+    fn from(err: LimitError) -> Error {
+        match err {
+            LimitError::DepthLimitExceeded => Error::DepthLimitExceeded,
+            LimitError::LengthLimitExceeded(marker) => Error::LengthLimitExceeded(marker),
+            LimitError::TotalLimitExceeded => Error::TotalLimitExceeded,
+        }
+    }
+}
+
+impl From<ValidateError> for Error {
+    fn from(err: ValidateError) -> Error {
+        match err {
+            ValidateError::ReservedMarker(..) => Error::TypeMismatch(Marker::Reserved),
+            ValidateError::DepthLimitExceeded(..) => Error::DepthLimitExceeded,
+            err => Error::InvalidDataRead(io::Error::new(ErrorKind::InvalidData, err)),
+        }
+    }
+}
+
 /// A Deserializer that reads bytes from a buffer.
 ///
 /// # Note
@@ -130,7 +173,12 @@ impl<'a> From<DecodeStringError<'a>> for Error {
 pub struct Deserializer<R> {
     rd: R,
     marker: Option<Marker>,
-    depth: usize,
+    limiter: Limiter,
+    legacy_raw: bool,
+    human_readable: bool,
+    i128_ext_type: Option<i8>,
+    u128_ext_type: Option<i8>,
+    typed_array_ext_type: i8,
 }
 
 impl<R: Read> Deserializer<ReadReader<R>> {
@@ -142,12 +190,13 @@ impl<R: Read> Deserializer<ReadReader<R>> {
 
     /// Constructs a new `Deserializer` by consuming the given reader.
     pub fn new(rd: R) -> Self {
-        Self {
-            rd: ReadReader::new(rd),
-            // Cached marker in case of deserializing optional values.
-            marker: None,
-            depth: 1024,
-        }
+        Self::with_config(rd, Config::default())
+    }
+
+    /// Constructs a new `Deserializer` by consuming the given reader, following the given
+    /// configuration.
+    pub fn with_config(rd: R, config: Config) -> Self {
+        Deserializer::from_reader(ReadReader::new(rd), config)
     }
 
     /// Gets a reference to the underlying reader in this decoder.
@@ -179,11 +228,13 @@ impl<'de, R> Deserializer<ReadRefReader<'de, R>>
 {
     /// Constructs a new `Deserializer` from the given byte slice.
     pub fn from_read_ref(rd: &'de R) -> Self {
-        Deserializer {
-            rd: ReadRefReader::new(rd),
-            marker: None,
-            depth: 1024,
-        }
+        Self::from_read_ref_with_config(rd, Config::default())
+    }
+
+    /// Constructs a new `Deserializer` from the given byte slice, following the given
+    /// configuration.
+    pub fn from_read_ref_with_config(rd: &'de R, config: Config) -> Self {
+        Deserializer::from_reader(ReadRefReader::new(rd), config)
     }
 
     /// Gets a reference to the underlying reader in this decoder.
@@ -193,9 +244,125 @@ impl<'de, R> Deserializer<ReadRefReader<'de, R>>
 }
 
 impl<'de, R: ReadSlice<'de>> Deserializer<R> {
-    /// Changes the maximum nesting depth that is allowed
+    fn from_reader(rd: R, config: Config) -> Self {
+        Deserializer {
+            rd: rd,
+            // Cached marker in case of deserializing optional values.
+            marker: None,
+            limiter: Limiter::new(*config.limits()),
+            legacy_raw: config.legacy_raw(),
+            human_readable: config.is_human_readable(),
+            i128_ext_type: config.i128_ext_type(),
+            u128_ext_type: config.u128_ext_type(),
+            typed_array_ext_type: config.typed_array_ext_type(),
+        }
+    }
+
+    /// Changes the maximum nesting depth of arrays and maps that is allowed, which is 1024 by
+    /// default.
+    ///
+    /// Input nested deeper fails with `Error::DepthLimitExceeded` instead of overflowing the
+    /// stack. Enum variants count as a level, since they are encoded as single-entry maps.
     pub fn set_max_depth(&mut self, depth: usize) {
-        self.depth = depth;
+        let limits = DecodeLimits { max_depth: depth, ..*self.limiter.limits() };
+        self.set_limits(limits);
+    }
+
+    /// Changes the limits the input is checked against, which are `DecodeLimits::default()` unless
+    /// specified otherwise. The data already read is no longer accounted, so the limits should be
+    /// set before decoding.
+    ///
+    /// Declared lengths are checked before anything is allocated for them, so limiting them is
+    /// enough to decode untrusted input with bounded memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rmp;
+    /// extern crate rmp_serde as rmps;
+    /// extern crate serde;
+    ///
+    /// use rmp::Marker;
+    /// use rmp::decode::DecodeLimits;
+    /// use serde::Deserialize;
+    ///
+    /// # fn main() {
+    /// // A 5-byte message claiming a 4 GiB string.
+    /// let buf = [0xdb, 0xff, 0xff, 0xff, 0xff];
+    ///
+    /// let mut de = rmps::Deserializer::new(&buf[..]);
+    /// de.set_limits(DecodeLimits { max_data_len: 1024, ..DecodeLimits::default() });
+    ///
+    /// match String::deserialize(&mut de) {
+    ///     Err(rmps::decode::Error::LengthLimitExceeded(Marker::Str32)) => {}
+    ///     other => panic!("unexpected result: {:?}", other),
+    /// }
+    /// # }
+    /// ```
+    pub fn set_limits(&mut self, limits: DecodeLimits) {
+        self.limiter = Limiter::new(limits);
+    }
+
+    /// Returns the limits the input is checked against.
+    pub fn limits(&self) -> &DecodeLimits {
+        self.limiter.limits()
+    }
+
+    /// Applies the given configuration, replacing the limits and other settings made before. As
+    /// with `set_limits`, this should be done before decoding.
+    ///
+    /// Structs and enums are accepted in any representation, so only the settings of `Config`
+    /// regarding the input apply here.
+    pub fn set_config(&mut self, config: Config) {
+        self.set_limits(*config.limits());
+        self.legacy_raw = config.legacy_raw();
+        self.human_readable = config.is_human_readable();
+        self.i128_ext_type = config.i128_ext_type();
+        self.u128_ext_type = config.u128_ext_type();
+        self.typed_array_ext_type = config.typed_array_ext_type();
+    }
+
+    /// Returns the stashed marker if any, or else reads a new one, accounting for it.
+    fn take_marker(&mut self) -> Result<Marker, Error> {
+        match self.marker.take() {
+            Some(marker) => Ok(marker),
+            None => {
+                let marker = rmp::decode::read_marker(&mut self.rd)?;
+                self.limiter.check_marker(marker)?;
+                Ok(marker)
+            }
+        }
+    }
+
+    /// Enables or disables the compatibility mode with the old MessagePack specification (before
+    /// 2013), which is disabled by default.
+    ///
+    /// Old implementations write both strings and byte arrays as raw, using the string markers. In
+    /// this mode such values are accepted wherever bytes are expected, regardless of whether they
+    /// are valid UTF-8. Strings are accepted from raw in any mode.
+    pub fn set_legacy_raw(&mut self, enabled: bool) {
+        self.legacy_raw = enabled;
+    }
+
+    /// Sets the extension type `i128` values are read from, which is none by default.
+    ///
+    /// Native integers are accepted for `i128` regardless of this setting, while the extension is
+    /// accepted only if the type is set. See `Config::with_i128_ext_type`.
+    pub fn set_i128_ext_type(&mut self, ty: Option<i8>) {
+        self.i128_ext_type = ty;
+    }
+
+    /// Sets the extension type `u128` values are read from, which is none by default.
+    ///
+    /// See `set_i128_ext_type`.
+    pub fn set_u128_ext_type(&mut self, ty: Option<i8>) {
+        self.u128_ext_type = ty;
+    }
+
+    /// Sets the extension type of the typed arrays handled by the `typed_array` module, which is
+    /// `TypedArray::DEFAULT_EXT_TYPE` by default.
+    pub fn set_typed_array_ext_type(&mut self, ty: i8) {
+        self.typed_array_ext_type = ty;
     }
 
     fn read_str_data<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
@@ -233,51 +400,28 @@ impl<'de, R: ReadSlice<'de>> Deserializer<R> {
         self.rd.read_slice(len as usize).map_err(Error::InvalidDataRead)
     }
 
-    fn read_array<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
-        where V: Visitor<'de>
-    {
-        visitor.visit_seq(SeqAccess::new(self, len as usize))
-    }
+    fn read_ext_len(&mut self, marker: Marker) -> Result<u32, Error> {
+        let len = match marker {
+            Marker::FixExt1 => 1,
+            Marker::FixExt2 => 2,
+            Marker::FixExt4 => 4,
+            Marker::FixExt8 => 8,
+            Marker::FixExt16 => 16,
+            Marker::Ext8 => read_u8(&mut self.rd)? as u32,
+            Marker::Ext16 => read_u16(&mut self.rd)? as u32,
+            Marker::Ext32 => read_u32(&mut self.rd)?,
+            marker => return Err(Error::TypeMismatch(marker)),
+        };
+        self.limiter.check_len(marker, len)?;
 
-    fn read_map<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
-        where V: Visitor<'de>
-    {
-        visitor.visit_map(MapAccess::new(self, len as usize))
+        Ok(len)
     }
 
-    fn read_bytes<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
-        where V: Visitor<'de>
-    {
-        match self.read_bin_data(len)? {
-            Reference::Borrowed(buf) => visitor.visit_borrowed_bytes(buf),
-            Reference::Copied(buf) => visitor.visit_bytes(buf),
-        }
-    }
-}
-
-fn read_u8<R: Read>(rd: &mut R) -> Result<u8, Error> {
-    rd.read_u8().map_err(Error::InvalidDataRead)
-}
-
-fn read_u16<R: Read>(rd: &mut R) -> Result<u16, Error> {
-    rd.read_u16::<byteorder::BigEndian>().map_err(Error::InvalidDataRead)
-}
-
-fn read_u32<R: Read>(rd: &mut R) -> Result<u32, Error> {
-    rd.read_u32::<byteorder::BigEndian>().map_err(Error::InvalidDataRead)
-}
-
-impl<'de, 'a, R: ReadSlice<'de>> serde::Deserializer<'de> for &'a mut Deserializer<R> {
-    type Error = Error;
-
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    // Reads values other than arrays and maps, which keeps the frames of the recursion through
+    // `deserialize_any` small enough for the default depth limit.
+    fn read_flat_value<V>(&mut self, marker: Marker, visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>
     {
-        let marker = match self.marker.take() {
-            Some(marker) => marker,
-            None => rmp::decode::read_marker(&mut self.rd)?,
-        };
-
         match marker {
             Marker::Null => visitor.visit_unit(),
             Marker::True => visitor.visit_bool(true),
@@ -299,59 +443,145 @@ impl<'de, 'a, R: ReadSlice<'de>> serde::Deserializer<'de> for &'a mut Deserializ
             }
             Marker::Str8 => {
                 let len = read_u8(&mut self.rd)?;
+                self.limiter.check_len(marker, len as u32)?;
                 self.read_str_data(len as u32, visitor)
             }
             Marker::Str16 => {
                 let len = read_u16(&mut self.rd)?;
+                self.limiter.check_len(marker, len as u32)?;
                 self.read_str_data(len as u32, visitor)
             }
             Marker::Str32 => {
                 let len = read_u32(&mut self.rd)?;
+                self.limiter.check_len(marker, len as u32)?;
                 self.read_str_data(len as u32, visitor)
             }
-            Marker::FixArray(len) => {
-                self.read_array(len as u32, visitor)
-            }
-            Marker::Array16 => {
-                let len = read_u16(&mut self.rd)?;
-                self.read_array(len as u32, visitor)
-            }
-            Marker::Array32 => {
-                let len = read_u32(&mut self.rd)?;
-                self.read_array(len, visitor)
-            }
-            Marker::FixMap(len) => {
-                self.read_map(len as u32, visitor)
-            }
-            Marker::Map16 => {
-                let len = read_u16(&mut self.rd)?;
-                self.read_map(len as u32, visitor)
-            }
-            Marker::Map32 => {
-                let len = read_u32(&mut self.rd)?;
-                self.read_map(len, visitor)
-            }
             Marker::Bin8 => {
                 let len = read_u8(&mut self.rd)?;
+                self.limiter.check_len(marker, len as u32)?;
                 self.read_bytes(len as u32, visitor)
             }
             Marker::Bin16 => {
                 let len = read_u16(&mut self.rd)?;
+                self.limiter.check_len(marker, len as u32)?;
                 self.read_bytes(len as u32, visitor)
             }
             Marker::Bin32 => {
                 let len = read_u32(&mut self.rd)?;
+                self.limiter.check_len(marker, len as u32)?;
                 self.read_bytes(len, visitor)
             }
+            Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 |
+            Marker::FixExt16 | Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => {
+                let len = self.read_ext_len(marker)?;
+                let ty = rmp::decode::read_data_i8(&mut self.rd)?;
+                let data = self.read_bin_data(len)?;
+
+                visitor.visit_newtype_struct(ExtDeserializer::new(ty, data))
+            }
+            Marker::FixArray(..) | Marker::Array16 | Marker::Array32 |
+            Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => unreachable!(),
             Marker::Reserved => Err(Error::TypeMismatch(Marker::Reserved)),
-            marker => Err(Error::TypeMismatch(marker)),
+        }
+    }
+
+    // Reads a 128-bit integer, which is either a native integer or a `fixext16` of one of the
+    // types set by `Config::with_i128_ext_type` and `with_u128_ext_type`. Only an explicit request
+    // for such an integer reads the extension this way, `deserialize_any` passes every extension
+    // on as is, since their type ids belong to the application.
+    fn read_int128<V>(&mut self, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        let marker = self.take_marker()?;
+        if marker != Marker::FixExt16 || (self.i128_ext_type.is_none() && self.u128_ext_type.is_none()) {
+            self.marker = Some(marker);
+            return serde::Deserializer::deserialize_any(self, visitor);
+        }
+
+        let ty = rmp::decode::read_data_i8(&mut self.rd)?;
+        let mut buf = [0; 16];
+        self.rd.read_exact(&mut buf).map_err(Error::InvalidDataRead)?;
+
+        if Some(ty) == self.i128_ext_type {
+            visitor.visit_i128(i128::from_be_bytes(buf))
+        } else if Some(ty) == self.u128_ext_type {
+            visitor.visit_u128(u128::from_be_bytes(buf))
+        } else {
+            Err(Error::Syntax(format!("unexpected extension type {}", ty)))
+        }
+    }
+
+    fn read_array<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        self.limiter.enter()?;
+        let res = visitor.visit_seq(SeqAccess::new(self, len as usize));
+        self.limiter.leave();
+        res
+    }
+
+    fn read_map<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        self.limiter.enter()?;
+        let res = visitor.visit_map(MapAccess::new(self, len as usize));
+        self.limiter.leave();
+        res
+    }
+
+    fn read_bytes<V>(&mut self, len: u32, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        match self.read_bin_data(len)? {
+            Reference::Borrowed(buf) => visitor.visit_borrowed_bytes(buf),
+            Reference::Copied(buf) => visitor.visit_bytes(buf),
+        }
+    }
+}
+
+fn read_u8<R: Read>(rd: &mut R) -> Result<u8, Error> {
+    rd.read_u8().map_err(Error::InvalidDataRead)
+}
+
+fn read_u16<R: Read>(rd: &mut R) -> Result<u16, Error> {
+    rd.read_u16::<byteorder::BigEndian>().map_err(Error::InvalidDataRead)
+}
+
+fn read_u32<R: Read>(rd: &mut R) -> Result<u32, Error> {
+    rd.read_u32::<byteorder::BigEndian>().map_err(Error::InvalidDataRead)
+}
+
+impl<'de, 'a, R: ReadSlice<'de>> serde::Deserializer<'de> for &'a mut Deserializer<R> {
+    type Error = Error;
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        let marker = self.take_marker()?;
+
+        let len = match marker {
+            Marker::FixArray(len) | Marker::FixMap(len) => len as u32,
+            Marker::Array16 | Marker::Map16 => read_u16(&mut self.rd)? as u32,
+            Marker::Array32 | Marker::Map32 => read_u32(&mut self.rd)?,
+            marker => return self.read_flat_value(marker, visitor),
+        };
+        self.limiter.check_len(marker, len)?;
+
+        if marker.family() == Family::Array {
+            self.read_array(len, visitor)
+        } else {
+            self.read_map(len, visitor)
         }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
         where V: Visitor<'de>
     {
-        let marker = rmp::decode::read_marker(&mut self.rd)?;
+        let marker = self.take_marker()?;
 
         if marker == Marker::Null {
             visitor.visit_none()
@@ -364,26 +594,168 @@ impl<'de, 'a, R: ReadSlice<'de>> serde::Deserializer<'de> for &'a mut Deserializ
     fn deserialize_enum<V>(self, _name: &str, _variants: &[&str], visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>
     {
-        match decode::read_map_len(&mut self.rd)? {
-            1 => visitor.visit_enum(VariantAccess::new(self)),
-            n => Err(Error::LengthMismatch(n as u32)),
+        // Any representation the serializer can be configured to write is accepted: a map like
+        // `{id => payload}`, an array like `[id, payload]` or a bare identifier of a unit variant.
+        let marker = self.take_marker()?;
+        let (len, expected) = match marker {
+            Marker::FixMap(len) => (len as u32, 1),
+            Marker::Map16 => (read_u16(&mut self.rd)? as u32, 1),
+            Marker::Map32 => (read_u32(&mut self.rd)?, 1),
+            Marker::FixArray(len) => (len as u32, 2),
+            Marker::Array16 => (read_u16(&mut self.rd)? as u32, 2),
+            Marker::Array32 => (read_u32(&mut self.rd)?, 2),
+            marker => {
+                self.marker = Some(marker);
+                return visitor.visit_enum(VariantAccess::unit(self));
+            }
+        };
+        self.limiter.check_len(marker, len)?;
+
+        if len != expected {
+            return Err(Error::LengthMismatch(len));
         }
+
+        self.limiter.enter()?;
+        let res = visitor.visit_enum(VariantAccess::new(self));
+        self.limiter.leave();
+        res
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>
     {
+        if name == MSGPACK_EXT_STRUCT_NAME {
+            let marker = self.take_marker()?;
+            let len = self.read_ext_len(marker)?;
+            let ty = rmp::decode::read_data_i8(&mut self.rd)?;
+            let data = self.read_bin_data(len)?;
+
+            return visitor.visit_newtype_struct(ExtDeserializer::new(ty, data));
+        }
+
+        if name == TYPED_ARRAY_STRUCT_NAME {
+            let marker = self.take_marker()?;
+            let len = self.read_ext_len(marker)?;
+            let ty = rmp::decode::read_data_i8(&mut self.rd)?;
+            if ty != self.typed_array_ext_type {
+                return Err(Error::Syntax(format!("unexpected extension type {}", ty)));
+            }
+
+            // The data is passed on as is, see `typed_array`.
+            return self.read_bytes(len, visitor);
+        }
+
         visitor.visit_newtype_struct(self)
     }
 
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if !self.legacy_raw {
+            return self.deserialize_any(visitor);
+        }
+
+        let marker = self.take_marker()?;
+
+        // Raw shares its markers with strings, but may contain arbitrary bytes.
+        let len = match marker {
+            Marker::FixStr(len) => len as u32,
+            Marker::Str8 => read_u8(&mut self.rd)? as u32,
+            Marker::Str16 => read_u16(&mut self.rd)? as u32,
+            Marker::Str32 => read_u32(&mut self.rd)?,
+            marker => {
+                self.marker = Some(marker);
+                return self.deserialize_any(visitor);
+            }
+        };
+        self.limiter.check_len(marker, len)?;
+
+        self.read_bytes(len, visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.read_int128(visitor)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.read_int128(visitor)
+    }
+
     forward_to_deserialize_any! {
         bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char
-        str string bytes byte_buf unit unit_struct seq map
+        str string unit unit_struct seq map
         tuple_struct struct identifier tuple
         ignored_any
     }
 }
 
+/// Presents an extension as a tuple of its type id and data, which is how it is passed through
+/// the newtype struct named `MSGPACK_EXT_STRUCT_NAME`.
+struct ExtDeserializer<'de, 'a> {
+    ty: Option<i8>,
+    data: Option<Reference<'de, 'a, [u8]>>,
+}
+
+impl<'de, 'a> ExtDeserializer<'de, 'a> {
+    fn new(ty: i8, data: Reference<'de, 'a, [u8]>) -> Self {
+        ExtDeserializer {
+            ty: Some(ty),
+            data: Some(data),
+        }
+    }
+}
+
+impl<'de, 'a> serde::Deserializer<'de> for ExtDeserializer<'de, 'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_seq(&mut self)
+    }
+
+    forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 f32 f64 char
+        str string bytes byte_buf option unit unit_struct newtype_struct seq map
+        tuple_struct struct identifier tuple enum
+        ignored_any
+    }
+}
+
+impl<'de, 'a, 'b> de::SeqAccess<'de> for &'b mut ExtDeserializer<'de, 'a> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+        where T: DeserializeSeed<'de>
+    {
+        if let Some(ty) = self.ty.take() {
+            return seed.deserialize(ty.into_deserializer()).map(Some);
+        }
+
+        match self.data.take() {
+            Some(Reference::Borrowed(buf)) => {
+                seed.deserialize(BorrowedBytesDeserializer::new(buf)).map(Some)
+            }
+            Some(Reference::Copied(buf)) => seed.deserialize(BytesDeserializer::new(buf)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.ty.iter().count() + self.data.iter().count())
+    }
+}
+
 struct SeqAccess<'a, R: 'a> {
     de: &'a mut Deserializer<R>,
     left: usize,
@@ -458,12 +830,30 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
 
 struct VariantAccess<'a, R: 'a> {
     de: &'a mut Deserializer<R>,
+    // Set for a bare identifier, which can only be a unit variant.
+    bare: bool,
 }
 
 impl<'a, R: 'a> VariantAccess<'a, R> {
     pub fn new(de: &'a mut Deserializer<R>) -> Self {
         VariantAccess {
             de: de,
+            bare: false,
+        }
+    }
+
+    fn unit(de: &'a mut Deserializer<R>) -> Self {
+        VariantAccess {
+            de: de,
+            bare: true,
+        }
+    }
+
+    fn fail_bare(&self) -> Result<(), Error> {
+        if self.bare {
+            Err(de::Error::invalid_type(Unexpected::UnitVariant, &"a variant with a payload"))
+        } else {
+            Ok(())
         }
     }
 }
@@ -475,11 +865,10 @@ impl<'de, 'a, R: ReadSlice<'de>> de::EnumAccess<'de> for VariantAccess<'a, R> {
     fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
         where V: de::DeserializeSeed<'de>,
     {
-        use serde::de::IntoDeserializer;
-
-        let idx: u32 = serde::Deserialize::deserialize(&mut *self.de)?;
-        let val: Result<_, Error> = seed.deserialize(idx.into_deserializer());
-        Ok((val?, self))
+        // Variants are identified either by their index or by their name, both of which the
+        // derived identifiers accept.
+        let val = seed.deserialize(&mut *self.de)?;
+        Ok((val, self))
     }
 }
 
@@ -487,25 +876,34 @@ impl<'de, 'a, R: ReadSlice<'de>> de::VariantAccess<'de> for VariantAccess<'a, R>
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Error> {
-        decode::read_nil(&mut self.de.rd)?;
-        Ok(())
+        if self.bare {
+            return Ok(());
+        }
+
+        match self.de.take_marker()? {
+            Marker::Null => Ok(()),
+            marker => Err(Error::TypeMismatch(marker)),
+        }
     }
 
     fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
         where T: DeserializeSeed<'de>
     {
+        self.fail_bare()?;
         seed.deserialize(self.de)
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>
     {
+        self.fail_bare()?;
         de::Deserializer::deserialize_tuple(self.de, len, visitor)
     }
 
     fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>
     {
+        self.fail_bare()?;
         de::Deserializer::deserialize_tuple(self.de, fields.len(), visitor)
     }
 }
@@ -546,8 +944,12 @@ impl<R: Read> ReadReader<R> {
 impl<'de, R: Read> ReadSlice<'de> for ReadReader<R> {
     #[inline]
     fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, io::Error> {
-        self.buf.resize(len, 0u8);
-        self.rd.read_exact(&mut self.buf[..])?;
+        // The length comes from the wire, so the buffer grows as the data actually arrives.
+        self.buf.clear();
+        self.rd.by_ref().take(len as u64).read_to_end(&mut self.buf)?;
+        if self.buf.len() < len {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
 
         Ok(Reference::Copied(&self.buf[..]))
     }
@@ -630,6 +1032,147 @@ where R: Read,
     Deserialize::deserialize(&mut Deserializer::new(rd))
 }
 
+/// Deserialize an instance of type `T` from an asynchronous stream of MessagePack.
+///
+/// The next encoded value is first read entirely into a buffer, without blocking the executor
+/// thread, and then deserialized as `from_read` does. The reader is left positioned right after the
+/// value, so this function can be called repeatedly to receive a sequence of messages.
+///
+/// # Errors
+///
+/// This conversion can fail if the reader fails or the stream ends in the middle of the value, in
+/// addition to the cases described in `from_read`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate futures_executor;
+/// extern crate rmp_serde as rmps;
+///
+/// use futures_executor::block_on;
+///
+/// # fn main() {
+/// // Encoded `["Bobby", 8]`.
+/// let buf = [0x92, 0xa5, 0x42, 0x6f, 0x62, 0x62, 0x79, 0x8];
+/// let mut rd = &buf[..];
+///
+/// let dog: (String, u8) = block_on(rmps::decode::from_read_async(&mut rd)).unwrap();
+/// assert_eq!(("Bobby".into(), 8), dog);
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub fn from_read_async<'a, R, T>(rd: &'a mut R) -> impl Future<Output = Result<T, Error>> + 'a
+where
+    R: AsyncRead + Unpin + ?Sized,
+    T: DeserializeOwned + 'a
+{
+    from_read_async_with_config(rd, Config::default())
+}
+
+/// Deserialize an instance of type `T` from an asynchronous stream of MessagePack, following the
+/// given configuration.
+///
+/// The limits of the configuration are checked while the value is being received, so no more
+/// than they allow is ever buffered.
+///
+/// # Errors
+///
+/// This conversion can fail for the reasons described in `from_read_async`, or if the value
+/// exceeds the limits.
+///
+/// # Examples
+///
+/// ```
+/// extern crate futures_executor;
+/// extern crate rmp_serde as rmps;
+///
+/// use futures_executor::block_on;
+/// use rmps::Config;
+/// use rmps::decode::Error;
+///
+/// # fn main() {
+/// // Encoded `[[[8]]]`.
+/// let buf = [0x91, 0x91, 0x91, 0x8];
+/// let config = Config::default().with_max_depth(2);
+///
+/// let res: Result<Vec<Vec<Vec<u8>>>, Error> =
+///     block_on(rmps::decode::from_read_async_with_config(&mut &buf[..], config));
+///
+/// match res {
+///     Err(Error::DepthLimitExceeded) => {}
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub fn from_read_async_with_config<'a, R, T>(rd: &'a mut R, config: Config) ->
+    impl Future<Output = Result<T, Error>> + 'a
+where
+    R: AsyncRead + Unpin + ?Sized,
+    T: DeserializeOwned + 'a
+{
+    rmp::decode::asynchronous::read_value_bytes_with_limits(rd, config.limits()).map(move |res| {
+        let buf = res.map_err(from_async_read_error)?;
+        Deserialize::deserialize(&mut Deserializer::with_config(&buf[..], config))
+    })
+}
+
+// The asynchronous reader reports exceeded limits as `InvalidData` errors wrapping `LimitError`,
+// which are unwrapped here to match the errors the `Deserializer` returns itself.
+#[cfg(feature = "async")]
+fn from_async_read_error(err: ValueReadError) -> Error {
+    if let ValueReadError::InvalidDataRead(ref io_err) = err {
+        if let Some(lim) = io_err.get_ref().and_then(|e| e.downcast_ref::<LimitError>()) {
+            return (*lim).into();
+        }
+    }
+    err.into()
+}
+
+/// Deserialize an instance of type `T` from the next complete message buffered in the unpacker.
+///
+/// Returns `Ok(None)` if the unpacker doesn't have a complete message buffered yet, in which case
+/// more data should be fed into it. Deserialization is performed in zero-copy manner whenever it
+/// is possible, borrowing the data from the unpacker buffer.
+///
+/// # Errors
+///
+/// This conversion can fail if the message is malformed or exceeds the unpacker limits, in addition
+/// to the cases described in `from_read`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rmp;
+/// extern crate rmp_serde as rmps;
+///
+/// use rmp::decode::Unpacker;
+///
+/// # fn main() {
+/// // Encoded `["Bobby", 8]`, split in two chunks.
+/// let mut unpacker = Unpacker::new();
+///
+/// unpacker.feed(&[0x92, 0xa5, 0x42, 0x6f]);
+/// assert_eq!(None, rmps::decode::from_unpacker::<(&str, u8)>(&mut unpacker).unwrap());
+///
+/// unpacker.feed(&[0x62, 0x62, 0x79, 0x8]);
+/// assert_eq!(Some(("Bobby", 8)), rmps::decode::from_unpacker(&mut unpacker).unwrap());
+/// # }
+/// ```
+pub fn from_unpacker<'a, T>(unpacker: &'a mut Unpacker) -> Result<Option<T>, Error>
+where
+    T: Deserialize<'a>
+{
+    let config = Config::default().with_limits(*unpacker.limits());
+    match unpacker.next_message()? {
+        Some(msg) => {
+            let mut de = Deserializer::from_read_ref_with_config(msg, config);
+            Deserialize::deserialize(&mut de).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
 /// Deserializes a byte slice into the desired type.
 ///
 /// Currently deprecated, use more generic `from_read_ref` instead.
@@ -680,3 +1223,162 @@ where
     let mut de = Deserializer::from_read_ref(rd);
     Deserialize::deserialize(&mut de)
 }
+
+/// An iterator over MessagePack messages written back to back to a reader without any framing,
+/// deserializing each into an instance of type `T`.
+///
+/// The iterator ends when the reader reaches EOF right at a message boundary. A reader ending in
+/// the middle of a message is truncated, which is reported as an `InvalidMarkerRead` or an
+/// `InvalidDataRead` error of `ErrorKind::UnexpectedEof` kind instead.
+///
+/// Any error leaves the reader in the middle of a message, where the next one can't be found, so
+/// the iterator ends after yielding it. The limits set by the configuration apply to each message
+/// on its own.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rmp_serde as rmps;
+///
+/// use std::io::ErrorKind;
+///
+/// use rmps::decode::{Error, StreamDeserializer};
+///
+/// # fn main() {
+/// // Encoded `["Bobby", 8]` and `["Rex", 3]`, followed by a truncated message.
+/// let buf = [0x92, 0xa5, 0x42, 0x6f, 0x62, 0x62, 0x79, 0x08, 0x92, 0xa3, 0x52, 0x65, 0x78, 0x03,
+///            0x92, 0xa4];
+///
+/// let mut stream = StreamDeserializer::<_, (String, u8)>::new(&buf[..]);
+///
+/// assert_eq!(("Bobby".into(), 8), stream.next().unwrap().unwrap());
+/// assert_eq!(8, stream.byte_offset());
+/// assert_eq!(("Rex".into(), 3), stream.next().unwrap().unwrap());
+/// assert_eq!(14, stream.byte_offset());
+///
+/// match stream.next() {
+///     Some(Err(Error::InvalidDataRead(ref err))) if err.kind() == ErrorKind::UnexpectedEof => {}
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// assert!(stream.next().is_none());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StreamDeserializer<R: Read, T> {
+    de: Deserializer<ReadReader<CountingReader<R>>>,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T> StreamDeserializer<R, T> {
+    /// Constructs a new `StreamDeserializer` by consuming the given reader.
+    pub fn new(rd: R) -> Self {
+        Self::with_config(rd, Config::default())
+    }
+
+    /// Constructs a new `StreamDeserializer` by consuming the given reader, following the given
+    /// configuration.
+    pub fn with_config(rd: R, config: Config) -> Self {
+        let rd = CountingReader {
+            rd: rd,
+            count: 0,
+        };
+
+        StreamDeserializer {
+            de: Deserializer::with_config(rd, config),
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes read from the reader so far.
+    ///
+    /// Between messages this is the offset of the end of the last message, which is the start of
+    /// the next one. After an error it's the offset, at which the error occurred.
+    pub fn byte_offset(&self) -> u64 {
+        self.de.rd.rd.count
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.de.rd.rd.rd
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Reading from it directly makes the byte offsets inaccurate.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.de.rd.rd.rd
+    }
+
+    /// Consumes this iterator returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.de.rd.rd.rd
+    }
+
+    /// Reads the first byte of the next message, returning `None` on EOF.
+    fn read_first(&mut self) -> Result<Option<u8>, io::Error> {
+        let mut buf = [0];
+        loop {
+            match self.de.rd.read(&mut buf) {
+                Ok(0) => return Ok(None),
+                Ok(..) => return Ok(Some(buf[0])),
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn read_next(&mut self) -> Option<Result<T, Error>>
+        where T: DeserializeOwned
+    {
+        let marker = match self.read_first() {
+            Ok(Some(byte)) => Marker::from_u8(byte),
+            Ok(None) => return None,
+            Err(err) => return Some(Err(Error::InvalidMarkerRead(err))),
+        };
+
+        self.de.limiter.reset();
+        if let Err(err) = self.de.limiter.check_marker(marker) {
+            return Some(Err(err.into()));
+        }
+        self.de.marker = Some(marker);
+
+        Some(Deserialize::deserialize(&mut self.de))
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for StreamDeserializer<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let res = self.read_next();
+        match res {
+            Some(Ok(..)) => {}
+            Some(Err(..)) | None => self.done = true,
+        }
+        res
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> FusedIterator for StreamDeserializer<R, T> {}
+
+// Counts the bytes read through it, which gives the byte offsets of a stream.
+#[derive(Debug)]
+struct CountingReader<R> {
+    rd: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.rd.read(buf)?;
+        self.count += len as u64;
+        Ok(len)
+    }
+}
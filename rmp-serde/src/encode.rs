@@ -3,6 +3,17 @@
 use std::error;
 use std::fmt::{self, Display};
 use std::io::Write;
+#[cfg(feature = "async")]
+use std::future::Future;
+
+#[cfg(feature = "bytes")]
+use bytes::{BufMut, BytesMut};
+#[cfg(feature = "async")]
+use futures_io::AsyncWrite;
+#[cfg(feature = "async")]
+use futures_util::FutureExt;
+#[cfg(feature = "async")]
+use futures_util::future::{ready, Either};
 
 use serde;
 use serde::Serialize;
@@ -10,9 +21,10 @@ use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVar
                  SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
 
 use rmp::encode;
-use rmp::encode::ValueWriteError;
+use rmp::encode::{LenPlaceholder, ValueWriteError};
 
 use ext::{StructMapSerializer, StructTupleSerializer};
+use {Config, MSGPACK_EXT_STRUCT_NAME, TYPED_ARRAY_STRUCT_NAME};
 
 /// This type represents all possible errors that can occur when serializing or
 /// deserializing MessagePack data.
@@ -21,8 +33,11 @@ pub enum Error {
     /// Failed to write a MessagePack value.
     InvalidValueWrite(ValueWriteError),
     /// Failed to serialize struct, sequence or map, because its length is unknown.
+    ///
+    /// Sequences and maps of unknown length are buffered in memory until their end now, so this
+    /// error is no longer returned by the serializer itself.
     UnknownLength,
-    /// Depth limit exceeded
+    /// Arrays and maps are nested deeper than the limit set by `Serializer::set_max_depth`.
     DepthLimitExceeded,
     /// Catchall for syntax error messages.
     Syntax(String),
@@ -84,6 +99,75 @@ pub trait UnderlyingWrite {
 
     /// Unwraps this `Serializer`, returning the underlying writer.
     fn into_inner(self) -> Self::Write;
+
+    /// Enters an array or a map, which is about to be written.
+    ///
+    /// Wrappers writing containers on their own call this to respect the depth limit of the
+    /// underlying serializer. Does nothing by default.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::DepthLimitExceeded` if the nesting becomes deeper than allowed.
+    fn enter(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Leaves an array or a map previously entered.
+    fn leave(&mut self) {}
+
+    /// Writes everything of an enum variant with the given index and name that precedes its
+    /// payload, entering the container the variant is wrapped into.
+    ///
+    /// Wrappers writing struct variants on their own call this to respect the enum representation
+    /// of the underlying serializer, and leave the container after the payload. By default writes
+    /// the header of an array of the variant index and the payload, i.e. `[idx, payload]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error` if the nesting becomes deeper than allowed or the writer fails.
+    fn write_variant_header(&mut self, idx: u32, _name: &'static str) -> Result<(), Error> {
+        self.enter()?;
+        encode::write_array_len(self.get_mut(), 2)?;
+        encode::write_uint(self.get_mut(), idx as u64)?;
+        Ok(())
+    }
+
+    /// Returns the configuration of the underlying serializer, which wrappers follow for
+    /// everything they don't override. Returns the default configuration by default.
+    fn config(&self) -> Config {
+        Config::default()
+    }
+}
+
+/// Defines how a struct is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructRepr {
+    /// An array of the field values without names, which is the most compact form.
+    Tuple,
+    /// A map from the field names to their values, which most implementations in other languages
+    /// expect.
+    Map,
+}
+
+/// Identifies an enum variant on the wire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariantId {
+    /// The index of the variant in the enum declaration, which is the most compact form.
+    Index,
+    /// The name of the variant as a string, which most implementations in other languages expect.
+    Name,
+}
+
+/// Defines how an enum variant wraps its identifier and payload.
+///
+/// Unit variants have `nil` as the payload, unless written as a bare identifier using
+/// `Serializer::set_bare_unit_variants`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnumRepr {
+    /// A single-entry map from the identifier to the payload, like `{id => payload}`.
+    Map,
+    /// An array of the identifier and the payload, like `[id, payload]`.
+    Array,
 }
 
 /// Represents MessagePack serialization implementation.
@@ -93,9 +177,11 @@ pub trait UnderlyingWrite {
 /// MessagePack has no specification about how to encode enum types. Thus we are free to do
 /// whatever we want, so the given chose may be not ideal for you.
 ///
-/// An enum value is represented as a single-entry map whose key is the variant
-/// id and whose value is a sequence containing all associated data. If the enum
-/// does not have associated data, the sequence is empty.
+/// By default an enum value is represented as a single-entry map whose key is the variant
+/// index and whose value is a sequence containing all associated data. If the enum
+/// does not have associated data, the value is `nil`. Use `set_variant_id`, `set_enum_repr` and
+/// `set_bare_unit_variants`, or a whole `Config`, to match the representation expected by the
+/// other side.
 ///
 /// All instances of `ErrorKind::Interrupted` are handled by this function and the underlying
 /// operation is retried.
@@ -104,6 +190,7 @@ pub trait UnderlyingWrite {
 pub struct Serializer<W> {
     wr: W,
     depth: usize,
+    config: Config,
 }
 
 impl<W: Write> Serializer<W> {
@@ -124,12 +211,156 @@ impl<W: Write> Serializer<W> {
         self.wr
     }
 
-    /// Changes the maximum nesting depth that is allowed.
+    /// Changes the maximum nesting depth of arrays and maps that is allowed, which is 1024 by
+    /// default.
     ///
-    /// Currently unused.
-    #[doc(hidden)]
+    /// Values nested deeper fail with `Error::DepthLimitExceeded` instead of overflowing the
+    /// stack. Enum variants count as a level, since they are wrapped into a map or an array,
+    /// except for unit variants written as a bare identifier.
     pub fn set_max_depth(&mut self, depth: usize) {
-        self.depth = depth;
+        self.config = self.config.with_max_depth(depth);
+    }
+
+    /// Enables or disables the compatibility mode with the old MessagePack specification (before
+    /// 2013), which is disabled by default.
+    ///
+    /// In this mode both strings and byte arrays are written as raw, i.e. using `FixStr`, `Str16`
+    /// or `Str32` markers, because old implementations can parse neither `Str8` nor the binary
+    /// family. Note that the reading side can't tell strings and bytes apart in this case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rmp_serde as rmps;
+    /// extern crate serde;
+    ///
+    /// use serde::Serialize;
+    ///
+    /// # fn main() {
+    /// let mut se = rmps::Serializer::new(Vec::new());
+    /// se.set_legacy_raw(true);
+    ///
+    /// "le message".serialize(&mut se).unwrap();
+    /// assert_eq!([0xaa], se.get_ref()[..1]);
+    /// # }
+    /// ```
+    pub fn set_legacy_raw(&mut self, enabled: bool) {
+        self.config = self.config.with_legacy_raw(enabled);
+    }
+
+    /// Sets the extension type `i128` values are written as, like `rmp::encode::write_i128` does,
+    /// which is none by default.
+    ///
+    /// Without an extension type only the values fitting in 64 bits can be written, as native
+    /// integers. `rmp::DEFAULT_I128_EXT_TYPE` is a suggested type id, but extension type ids belong
+    /// to the application, so nothing is used implicitly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rmp;
+    /// extern crate rmp_serde as rmps;
+    /// extern crate serde;
+    ///
+    /// use serde::Serialize;
+    ///
+    /// # fn main() {
+    /// let mut se = rmps::Serializer::new(Vec::new());
+    /// se.set_i128_ext_type(Some(rmp::DEFAULT_I128_EXT_TYPE));
+    ///
+    /// 42i128.serialize(&mut se).unwrap();
+    /// assert_eq!([0xd8, 0x7d], se.get_ref()[..2]);
+    /// # }
+    /// ```
+    pub fn set_i128_ext_type(&mut self, ty: Option<i8>) {
+        self.config = self.config.with_i128_ext_type(ty);
+    }
+
+    /// Sets the extension type `u128` values are written as, like `rmp::encode::write_u128` does,
+    /// which is none by default.
+    ///
+    /// See `set_i128_ext_type`.
+    pub fn set_u128_ext_type(&mut self, ty: Option<i8>) {
+        self.config = self.config.with_u128_ext_type(ty);
+    }
+
+    /// Sets the extension type of the typed arrays handled by the `typed_array` module, which is
+    /// `TypedArray::DEFAULT_EXT_TYPE` by default.
+    pub fn set_typed_array_ext_type(&mut self, ty: i8) {
+        self.config = self.config.with_typed_array_ext_type(ty);
+    }
+
+    /// Changes how enum variants are identified, which is by their index by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rmp_serde as rmps;
+    /// #[macro_use]
+    /// extern crate serde_derive;
+    /// extern crate serde;
+    ///
+    /// use serde::Serialize;
+    /// use rmps::encode::VariantId;
+    ///
+    /// #[derive(Serialize)]
+    /// enum Shape {
+    ///     Circle(u32),
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut se = rmps::Serializer::new(Vec::new());
+    /// se.set_variant_id(VariantId::Name);
+    ///
+    /// Shape::Circle(3).serialize(&mut se).unwrap();
+    /// // {"Circle" => 3}
+    /// assert_eq!(vec![0x81, 0xa6, 0x43, 0x69, 0x72, 0x63, 0x6c, 0x65, 0x03], se.into_inner());
+    /// # }
+    /// ```
+    pub fn set_variant_id(&mut self, id: VariantId) {
+        self.config = self.config.with_variant_id(id);
+    }
+
+    /// Changes how enum variants wrap their identifier and payload, which is a single-entry map
+    /// by default.
+    pub fn set_enum_repr(&mut self, repr: EnumRepr) {
+        self.config = self.config.with_enum_repr(repr);
+    }
+
+    /// Enables or disables writing unit variants as a bare identifier without a wrapping map or
+    /// array, which is disabled by default.
+    ///
+    /// Together with `VariantId::Name` this writes unit variants as plain strings, like most
+    /// implementations in other languages do for enumerations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rmp_serde as rmps;
+    /// #[macro_use]
+    /// extern crate serde_derive;
+    /// extern crate serde;
+    ///
+    /// use serde::Serialize;
+    /// use rmps::encode::VariantId;
+    ///
+    /// #[derive(Serialize)]
+    /// enum Color {
+    ///     Red,
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut se = rmps::Serializer::new(Vec::new());
+    /// se.set_variant_id(VariantId::Name);
+    /// se.set_bare_unit_variants(true);
+    ///
+    /// Color::Red.serialize(&mut se).unwrap();
+    /// // "Red"
+    /// assert_eq!(vec![0xa3, 0x52, 0x65, 0x64], se.into_inner());
+    /// # }
+    /// ```
+    pub fn set_bare_unit_variants(&mut self, enabled: bool) {
+        self.config = self.config.with_bare_unit_variants(enabled);
     }
 
     /// Constructs a new `MessagePack` serializer whose output will be written to the writer
@@ -140,18 +371,36 @@ impl<W: Write> Serializer<W> {
     /// This is the default constructor, which returns a serializer that will serialize structs
     /// and enums using the most compact representation.
     pub fn new(wr: W) -> Self {
+        Serializer::with_config(wr, Config::default())
+    }
+
+    /// Constructs a new `MessagePack` serializer whose output will be written to the writer
+    /// specified, following the given configuration.
+    pub fn with_config(wr: W, config: Config) -> Self {
         Serializer {
             wr: wr,
-            depth: 1024,
+            depth: 0,
+            config: config,
         }
     }
 
+    /// Returns the configuration this serializer follows.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Replaces the configuration this serializer follows.
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
     #[deprecated(note = "use `Serializer::new` instead")]
     #[doc(hidden)]
     pub fn compact(wr: W) -> Self {
         Serializer {
             wr: wr,
-            depth: 1024,
+            depth: 0,
+            config: Config::default(),
         }
     }
 
@@ -160,7 +409,8 @@ impl<W: Write> Serializer<W> {
     pub fn new_named(wr: W) -> Self {
         Serializer {
             wr: wr,
-            depth: 1024,
+            depth: 0,
+            config: Config::default(),
         }
     }
 }
@@ -168,9 +418,52 @@ impl<W: Write> Serializer<W> {
 impl<'a, W: Write + 'a> Serializer<W> {
     #[inline]
     fn compound(&'a mut self) -> Result<Compound<'a, W>, Error> {
-        let c = Compound { se: self };
+        self.enter()?;
+        let c = Compound { se: self, unknown: None, variant: false };
         Ok(c)
     }
+
+    /// Returns a compound, which buffers the elements after a placeholder header until their
+    /// number is known.
+    #[inline]
+    fn unknown_length_compound<F>(&'a mut self, placeholder: F) -> Result<Compound<'a, W>, Error>
+        where F: FnOnce(&mut Vec<u8>) -> LenPlaceholder
+    {
+        self.enter()?;
+
+        let mut buf = Vec::with_capacity(128);
+        let header = placeholder(&mut buf);
+        let se = Serializer {
+            wr: buf,
+            depth: self.depth,
+            config: self.config,
+        };
+
+        let unknown = UnknownLength {
+            se: se,
+            header: header,
+            len: 0,
+        };
+        let c = Compound { se: self, unknown: Some(unknown), variant: false };
+        Ok(c)
+    }
+
+    /// Writes the header of a map or an array wrapping an enum variant, followed by its identifier.
+    fn variant(&mut self, idx: u32, name: &'static str) -> Result<(), Error> {
+        self.enter()?;
+        match self.config.enum_repr() {
+            EnumRepr::Map => encode::write_map_len(&mut self.wr, 1)?,
+            EnumRepr::Array => encode::write_array_len(&mut self.wr, 2)?,
+        };
+        self.variant_id(idx, name)
+    }
+
+    fn variant_id(&mut self, idx: u32, name: &'static str) -> Result<(), Error> {
+        match self.config.variant_id() {
+            VariantId::Index => serde::Serializer::serialize_u32(self, idx),
+            VariantId::Name => serde::Serializer::serialize_str(self, name),
+        }
+    }
 }
 
 /// Extends the serializer by allowing to generate extension wrappers.
@@ -209,12 +502,74 @@ impl<W: Write> UnderlyingWrite for Serializer<W> {
     fn into_inner(self) -> Self::Write {
         self.wr
     }
+
+    fn enter(&mut self) -> Result<(), Error> {
+        if self.depth >= self.config.max_depth() {
+            return Err(Error::DepthLimitExceeded);
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn write_variant_header(&mut self, idx: u32, name: &'static str) -> Result<(), Error> {
+        self.variant(idx, name)
+    }
+
+    fn config(&self) -> Config {
+        self.config
+    }
 }
 
 /// Part of serde serialization API.
 #[derive(Debug)]
 pub struct Compound<'a, W: 'a> {
     se: &'a mut Serializer<W>,
+    // Set for sequences and maps of unknown length.
+    unknown: Option<UnknownLength>,
+    // Set for variants, which are wrapped into a map entered along with the compound itself.
+    variant: bool,
+}
+
+// The elements of a sequence or map of unknown length, encoded into a memory buffer after a
+// placeholder header, because the underlying writer can't be rewound to patch it.
+#[derive(Debug)]
+struct UnknownLength {
+    se: Serializer<Vec<u8>>,
+    header: LenPlaceholder,
+    len: u32,
+}
+
+impl<'a, W: Write + 'a> Compound<'a, W> {
+    #[inline]
+    fn serialize_item<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        match self.unknown {
+            Some(ref mut unknown) => value.serialize(&mut unknown.se),
+            None => value.serialize(&mut *self.se),
+        }
+    }
+
+    fn end_compound(self) -> Result<(), Error> {
+        self.se.leave();
+        if self.variant {
+            self.se.leave();
+        }
+
+        if let Some(unknown) = self.unknown {
+            let mut buf = unknown.se.into_inner();
+            unknown.header.patch_vec(&mut buf, unknown.len, true);
+
+            self.se.wr
+                .write_all(&buf)
+                .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, W: Write + 'a> SerializeSeq for Compound<'a, W> {
@@ -222,11 +577,14 @@ impl<'a, W: Write + 'a> SerializeSeq for Compound<'a, W> {
     type Error = Error;
 
     fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
-        value.serialize(&mut *self.se)
+        if let Some(ref mut unknown) = self.unknown {
+            unknown.len += 1;
+        }
+        self.serialize_item(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -239,7 +597,7 @@ impl<'a, W: Write + 'a> SerializeTuple for Compound<'a, W> {
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -252,7 +610,7 @@ impl<'a, W: Write + 'a> SerializeTupleStruct for Compound<'a, W> {
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -265,7 +623,7 @@ impl<'a, W: Write + 'a> SerializeTupleVariant for Compound<'a, W> {
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -274,15 +632,18 @@ impl<'a, W: Write + 'a> SerializeMap for Compound<'a, W> {
     type Error = Error;
 
     fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
-        key.serialize(&mut *self.se)
+        if let Some(ref mut unknown) = self.unknown {
+            unknown.len += 1;
+        }
+        self.serialize_item(key)
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
-        value.serialize(&mut *self.se)
+        self.serialize_item(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -290,14 +651,17 @@ impl<'a, W: Write + 'a> SerializeStruct for Compound<'a, W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized + Serialize>(&mut self, _key: &'static str, value: &T) ->
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) ->
         Result<(), Self::Error>
     {
+        if self.se.config.struct_repr() == StructRepr::Map {
+            serde::Serializer::serialize_str(&mut *self.se, key)?;
+        }
         value.serialize(&mut *self.se)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -305,14 +669,14 @@ impl<'a, W: Write + 'a> SerializeStructVariant for Compound<'a, W> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized + Serialize>(&mut self, _key: &'static str, value: &T) ->
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) ->
         Result<(), Self::Error>
     {
-        value.serialize(&mut *self.se)
+        SerializeStruct::serialize_field(self, key, value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
+        self.end_compound()
     }
 }
 
@@ -331,42 +695,99 @@ where
     type SerializeStruct = Compound<'a, W>;
     type SerializeStructVariant = Compound<'a, W>;
 
+    fn is_human_readable(&self) -> bool {
+        self.config.is_human_readable()
+    }
+
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         encode::write_bool(&mut self.wr, v)
             .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(err)))
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        if !self.config.compact_ints() {
+            encode::write_i8(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_i64(v as i64)
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        if !self.config.compact_ints() {
+            encode::write_i16(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_i64(v as i64)
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        if !self.config.compact_ints() {
+            encode::write_i32(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_i64(v as i64)
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        encode::write_sint(&mut self.wr, v)?;
+        if self.config.compact_ints() {
+            encode::write_sint(&mut self.wr, v)?;
+        } else {
+            encode::write_i64(&mut self.wr, v)?;
+        }
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        match self.config.i128_ext_type() {
+            // Always an extension, so the wire type doesn't depend on the value.
+            Some(ty) => encode::write_i128(&mut self.wr, v, ty)?,
+            None if v >= i64::min_value() as i128 && v <= i64::max_value() as i128 => {
+                return self.serialize_i64(v as i64);
+            }
+            None => return Err(Error::Syntax(format!("{} doesn't fit in 64 bits", v))),
+        }
         Ok(())
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        if !self.config.compact_ints() {
+            encode::write_u8(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_u64(v as u64)
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        if !self.config.compact_ints() {
+            encode::write_u16(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_u64(v as u64)
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        if !self.config.compact_ints() {
+            encode::write_u32(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_u64(v as u64)
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        encode::write_uint(&mut self.wr, v)?;
+        if self.config.compact_ints() {
+            encode::write_uint(&mut self.wr, v)?;
+        } else {
+            encode::write_u64(&mut self.wr, v)?;
+        }
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        match self.config.u128_ext_type() {
+            Some(ty) => encode::write_u128(&mut self.wr, v, ty)?,
+            None if v <= u64::max_value() as u128 => return self.serialize_u64(v as u64),
+            None => return Err(Error::Syntax(format!("{} doesn't fit in 64 bits", v))),
+        }
         Ok(())
     }
 
@@ -376,7 +797,11 @@ where
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        encode::write_f64(&mut self.wr, v)?;
+        if self.config.compact_floats() && v as f32 as f64 == v {
+            encode::write_f32(&mut self.wr, v as f32)?;
+        } else {
+            encode::write_f64(&mut self.wr, v)?;
+        }
         Ok(())
     }
 
@@ -387,15 +812,23 @@ where
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        encode::write_str(&mut self.wr, v)?;
+        if self.config.legacy_raw() {
+            encode::write_raw(&mut self.wr, v.as_bytes())?;
+        } else {
+            encode::write_str(&mut self.wr, v)?;
+        }
         Ok(())
     }
 
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
-        encode::write_bin_len(&mut self.wr, value.len() as u32)?;
-        self.wr
-            .write_all(value)
-            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))
+        // The header and the payload are passed to the writer together, which saves a system call
+        // per value for unbuffered writers.
+        if self.config.legacy_raw() {
+            encode::write_raw_vectored(&mut self.wr, value)?;
+        } else {
+            encode::write_bin_vectored(&mut self.wr, value)?;
+        }
+        Ok(())
     }
 
     fn serialize_none(self) -> Result<(), Self::Error> {
@@ -416,32 +849,49 @@ where
         Ok(())
     }
 
-    fn serialize_unit_variant(self, _name: &str, idx: u32, _variant: &str) ->
+    fn serialize_unit_variant(self, _name: &str, idx: u32, variant: &'static str) ->
         Result<Self::Ok, Self::Error>
     {
+        if self.config.bare_unit_variants() {
+            return self.variant_id(idx, variant);
+        }
+
         // encode as a map from variant idx to nil, like: {idx => nil}
-        encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_u32(idx)?;
+        self.variant(idx, variant)?;
         encode::write_nil(&mut self.wr).map_err(|e| Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(e)))?;
+        self.leave();
         Ok(())
     }
 
-    fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(self, _name: &'static str, value: &T) -> Result<(), Self::Error> {
+    fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(self, name: &'static str, value: &T) -> Result<(), Self::Error> {
+        if name == MSGPACK_EXT_STRUCT_NAME {
+            let mut se = ExtSerializer::new(&mut self.wr, None);
+            value.serialize(&mut se)?;
+            return se.end();
+        }
+
+        if name == TYPED_ARRAY_STRUCT_NAME {
+            let mut se = ExtSerializer::new(&mut self.wr, Some(self.config.typed_array_ext_type()));
+            value.serialize(&mut se)?;
+            return se.end();
+        }
+
         // Encode as if it's inner type.
         value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(self, _name: &'static str, idx: u32, _variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(self, _name: &'static str, idx: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
         // encode as a map from variant idx to its attributed data, like: {idx => value}
-        encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_u32(idx)?;
-        value.serialize(self)
+        self.variant(idx, variant)?;
+        value.serialize(&mut *self)?;
+        self.leave();
+        Ok(())
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
         let len = match len {
             Some(len) => len,
-            None => return Err(Error::UnknownLength),
+            None => return self.unknown_length_compound(encode::push_array_len_placeholder),
         };
 
         encode::write_array_len(&mut self.wr, len as u32)?;
@@ -459,13 +909,14 @@ where
         self.serialize_tuple(len)
     }
 
-    fn serialize_tuple_variant(self,  name: &'static str,  idx: u32,  _variant: &'static str,  len: usize) ->
+    fn serialize_tuple_variant(self,  name: &'static str,  idx: u32,  variant: &'static str,  len: usize) ->
         Result<Self::SerializeTupleVariant, Error>
     {
         // encode as a map from variant idx to a sequence of its attributed data, like: {idx => [v1,...,vN]}
-        encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_u32(idx)?;
-        self.serialize_tuple_struct(name, len)
+        self.variant(idx, variant)?;
+        let mut c = self.serialize_tuple_struct(name, len)?;
+        c.variant = true;
+        Ok(c)
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
@@ -474,24 +925,228 @@ where
                 encode::write_map_len(&mut self.wr, len as u32)?;
                 self.compound()
             }
-            None => Err(Error::UnknownLength),
+            None => self.unknown_length_compound(encode::push_map_len_placeholder),
         }
     }
 
     fn serialize_struct(self, _name: &'static str, len: usize) ->
         Result<Self::SerializeStruct, Self::Error>
     {
-        encode::write_array_len(&mut self.wr, len as u32)?;
+        match self.config.struct_repr() {
+            StructRepr::Tuple => encode::write_array_len(&mut self.wr, len as u32)?,
+            StructRepr::Map => encode::write_map_len(&mut self.wr, len as u32)?,
+        };
         self.compound()
     }
 
-    fn serialize_struct_variant(self, name: &'static str, id: u32, _variant: &'static str, len: usize) ->
+    fn serialize_struct_variant(self, name: &'static str, id: u32, variant: &'static str, len: usize) ->
         Result<Self::SerializeStructVariant, Error>
     {
         // encode as a map from variant idx to a sequence of its attributed data, like: {idx => [v1,...,vN]}
-        encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_u32(id)?;
-        self.serialize_struct(name, len)
+        self.variant(id, variant)?;
+        let mut c = self.serialize_struct(name, len)?;
+        c.variant = true;
+        Ok(c)
+    }
+}
+
+/// Writes an extension, which is passed as a `(i8, bytes)` tuple wrapped into a newtype struct
+/// named `MSGPACK_EXT_STRUCT_NAME`, or as bytes alone if the type is already known.
+#[derive(Debug)]
+struct ExtSerializer<'a, W: 'a> {
+    wr: &'a mut W,
+    ty: Option<i8>,
+    written: bool,
+}
+
+impl<'a, W: Write + 'a> ExtSerializer<'a, W> {
+    fn new(wr: &'a mut W, ty: Option<i8>) -> Self {
+        ExtSerializer {
+            wr: wr,
+            ty: ty,
+            written: false,
+        }
+    }
+
+    fn end(self) -> Result<(), Error> {
+        if self.written {
+            Ok(())
+        } else {
+            Err(ext_syntax_error())
+        }
+    }
+}
+
+fn ext_syntax_error() -> Error {
+    Error::Syntax("expected an extension as a tuple of type and bytes".into())
+}
+
+impl<'a, 'b, W: Write + 'a> SerializeTuple for &'b mut ExtSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: Write + 'a> serde::Serializer for &'b mut ExtSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = serde::ser::Impossible<(), Error>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = serde::ser::Impossible<(), Error>;
+    type SerializeTupleVariant = serde::ser::Impossible<(), Error>;
+    type SerializeMap = serde::ser::Impossible<(), Error>;
+    type SerializeStruct = serde::ser::Impossible<(), Error>;
+    type SerializeStructVariant = serde::ser::Impossible<(), Error>;
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        if len == 2 && self.ty.is_none() {
+            Ok(self)
+        } else {
+            Err(ext_syntax_error())
+        }
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        if self.ty.is_some() {
+            return Err(ext_syntax_error());
+        }
+
+        self.ty = Some(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        let ty = match self.ty {
+            Some(ty) if !self.written => ty,
+            _ => return Err(ext_syntax_error()),
+        };
+
+        encode::write_ext_meta(self.wr, v.len() as u32, ty)?;
+        self.wr
+            .write_all(v)
+            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))?;
+        self.written = true;
+        Ok(())
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _idx: u32, _variant: &'static str) ->
+        Result<Self::Ok, Self::Error>
+    {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, _value: &T) ->
+        Result<Self::Ok, Self::Error>
+    {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, _idx: u32, _variant: &'static str, _value: &T) ->
+        Result<Self::Ok, Self::Error>
+    {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) ->
+        Result<Self::SerializeTupleStruct, Error>
+    {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _idx: u32, _variant: &'static str, _len: usize) ->
+        Result<Self::SerializeTupleVariant, Error>
+    {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Error> {
+        Err(ext_syntax_error())
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _id: u32, _variant: &'static str, _len: usize) ->
+        Result<Self::SerializeStructVariant, Error>
+    {
+        Err(ext_syntax_error())
     }
 }
 
@@ -523,6 +1178,29 @@ where
     val.serialize(&mut se)
 }
 
+/// Serialize the given data structure as MessagePack into the asynchronous I/O stream.
+/// This function uses compact representation - structures as arrays
+///
+/// The value is serialized into a memory buffer first, which is then written without blocking the
+/// executor thread.
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[cfg(feature = "async")]
+pub fn write_async<'a, W, T>(wr: &'a mut W, val: &T) -> impl Future<Output = Result<(), Error>> + 'a
+where
+    W: AsyncWrite + Unpin + ?Sized,
+    T: Serialize + ?Sized
+{
+    match to_vec(val) {
+        Ok(buf) => {
+            Either::Left(encode::asynchronous::write_all(wr, buf).map(|res| {
+                res.map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))
+            }))
+        }
+        Err(err) => Either::Right(ready(Err(err))),
+    }
+}
+
 /// Serialize the given data structure as a MessagePack byte vector.
 /// This method uses compact representation, structs are serialized as arrays
 ///
@@ -552,3 +1230,49 @@ where
     write_named(&mut wr, val)?;
     Ok(wr)
 }
+
+/// Serialize the given data structure as MessagePack, appending it to the buffer.
+/// This function uses compact representation - structures as arrays
+///
+/// The buffer grows as needed. Use `BytesMut::split` or `BytesMut::freeze` afterwards to get
+/// the message as `Bytes` without copying.
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+///
+/// # Examples
+///
+/// ```
+/// extern crate bytes;
+/// extern crate rmp_serde as rmps;
+///
+/// use bytes::BytesMut;
+///
+/// # fn main() {
+/// let mut buf = BytesMut::with_capacity(64);
+/// rmps::encode::write_bytes_mut(&mut buf, &("le message", 42)).unwrap();
+///
+/// let frame = buf.split().freeze();
+/// assert_eq!(("le message", 42), rmps::from_slice(&frame).unwrap());
+/// # }
+/// ```
+#[cfg(feature = "bytes")]
+pub fn write_bytes_mut<T>(buf: &mut BytesMut, val: &T) -> Result<(), Error>
+where
+    T: Serialize + ?Sized
+{
+    write(&mut buf.writer(), val)
+}
+
+/// Serialize the given data structure as MessagePack, appending it to the buffer.
+/// This function serializes structures as maps
+///
+/// See `write_bytes_mut` for details.
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[cfg(feature = "bytes")]
+pub fn write_bytes_mut_named<T>(buf: &mut BytesMut, val: &T) -> Result<(), Error>
+where
+    T: Serialize + ?Sized
+{
+    write_named(&mut buf.writer(), val)
+}
@@ -7,6 +7,7 @@ use serde::{Serialize, Serializer};
 use serde::ser::{SerializeStruct, SerializeStructVariant};
 
 use encode::{Error, Ext, UnderlyingWrite};
+use Config;
 
 /// Serializer wrapper, that overrides struct serialization by packing as a map with field names.
 ///
@@ -48,6 +49,22 @@ where
     fn into_inner(self) -> Self::Write {
         self.se.into_inner()
     }
+
+    fn enter(&mut self) -> Result<(), Error> {
+        self.se.enter()
+    }
+
+    fn leave(&mut self) {
+        self.se.leave()
+    }
+
+    fn write_variant_header(&mut self, idx: u32, name: &'static str) -> Result<(), Error> {
+        self.se.write_variant_header(idx, name)
+    }
+
+    fn config(&self) -> Config {
+        self.se.config()
+    }
 }
 
 impl<'a, S> Serializer for &'a mut StructMapSerializer<S>
@@ -66,6 +83,11 @@ where
     type SerializeStruct = Self;
     type SerializeStructVariant = Self;
 
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.se.config().is_human_readable()
+    }
+
     #[inline]
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         self.se.serialize_bool(v)
@@ -91,11 +113,21 @@ where
         self.se.serialize_i64(v)
     }
 
+    #[inline]
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.se.serialize_i128(v)
+    }
+
     #[inline]
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         self.se.serialize_u8(v)
     }
 
+    #[inline]
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.se.serialize_u128(v)
+    }
+
     #[inline]
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
         self.se.serialize_u16(v)
@@ -207,14 +239,15 @@ where
 
     #[inline]
     fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        self.se.enter()?;
         encode::write_map_len(self.se.get_mut(), len as u32)?;
         Ok(self)
     }
 
     #[inline]
-    fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
-        encode::write_array_len(self.se.get_mut(), 2)?;
-        self.se.serialize_u32(variant_index)?;
+    fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.se.write_variant_header(variant_index, variant)?;
+        self.se.enter()?;
         encode::write_map_len(self.se.get_mut(), len as u32)?;
         Ok(self)
     }
@@ -231,11 +264,12 @@ where
     fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) ->
         Result<(), Self::Error>
     {
-        encode::write_str(self.se.get_mut(), key)?;
+        self.se.serialize_str(key)?;
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
         Ok(())
     }
 }
@@ -251,11 +285,13 @@ where
     fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) ->
         Result<(), Self::Error>
     {
-        encode::write_str(self.se.get_mut(), key)?;
+        self.se.serialize_str(key)?;
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
+        self.se.leave();
         Ok(())
     }
 }
@@ -298,6 +334,22 @@ where
     fn into_inner(self) -> Self::Write {
         self.se.into_inner()
     }
+
+    fn enter(&mut self) -> Result<(), Error> {
+        self.se.enter()
+    }
+
+    fn leave(&mut self) {
+        self.se.leave()
+    }
+
+    fn write_variant_header(&mut self, idx: u32, name: &'static str) -> Result<(), Error> {
+        self.se.write_variant_header(idx, name)
+    }
+
+    fn config(&self) -> Config {
+        self.se.config()
+    }
 }
 
 impl<'a, S> Serializer for &'a mut StructTupleSerializer<S>
@@ -316,6 +368,11 @@ where
     type SerializeStruct = Self;
     type SerializeStructVariant = Self;
 
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.se.config().is_human_readable()
+    }
+
     #[inline]
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         self.se.serialize_bool(v)
@@ -341,11 +398,21 @@ where
         self.se.serialize_i64(v)
     }
 
+    #[inline]
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.se.serialize_i128(v)
+    }
+
     #[inline]
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         self.se.serialize_u8(v)
     }
 
+    #[inline]
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.se.serialize_u128(v)
+    }
+
     #[inline]
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
         self.se.serialize_u16(v)
@@ -457,14 +524,15 @@ where
 
     #[inline]
     fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        self.se.enter()?;
         encode::write_array_len(self.se.get_mut(), len as u32)?;
         Ok(self)
     }
 
     #[inline]
-    fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
-        encode::write_array_len(&mut self.se.get_mut(), 2)?;
-        self.se.serialize_u32(variant_index)?;
+    fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.se.write_variant_header(variant_index, variant)?;
+        self.se.enter()?;
         encode::write_array_len(self.se.get_mut(), len as u32)?;
         Ok(self)
     }
@@ -485,6 +553,7 @@ where
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
         Ok(())
     }
 }
@@ -504,6 +573,8 @@ where
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.se.leave();
+        self.se.leave();
         Ok(())
     }
 }
@@ -66,6 +66,12 @@ extern crate rmp;
 extern crate byteorder;
 #[macro_use]
 extern crate serde;
+#[cfg(feature = "bytes")]
+extern crate bytes;
+#[cfg(feature = "async")]
+extern crate futures_io;
+#[cfg(feature = "async")]
+extern crate futures_util;
 
 use std::fmt::{self, Display, Formatter};
 use std::mem;
@@ -74,12 +80,211 @@ use std::str::{self, Utf8Error};
 use serde::{Deserialize, Serialize};
 use serde::de;
 
+pub use config::Config;
 pub use decode::{Deserializer, from_read, from_read_ref, from_slice};
 pub use encode::{Serializer, to_vec, to_vec_named};
 
+mod config;
 pub mod decode;
 pub mod encode;
 pub mod ext;
+pub mod typed_array;
+
+/// The name of a newtype struct, which both `Serializer` and `Deserializer` treat as a MessagePack
+/// extension given as a tuple of its type id and data.
+///
+/// `ExtValue` covers the common case. Types with their own wire representation as an extension,
+/// like UUIDs or decimals, may use this name directly: wrap a tuple of the `i8` type and the data
+/// serialized as bytes into `serialize_newtype_struct`, and call `deserialize_newtype_struct` to
+/// read it back from a sequence of the same two elements. Other serializers see a plain newtype
+/// struct around a tuple.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rmp_serde as rmps;
+/// extern crate serde;
+///
+/// use std::fmt::{self, Formatter};
+///
+/// use serde::{Deserialize, Deserializer, Serialize, Serializer};
+/// use serde::de::{self, SeqAccess, Visitor};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Uuid([u8; 16]);
+///
+/// struct Bytes<'a>(&'a [u8]);
+///
+/// impl<'a> Serialize for Bytes<'a> {
+///     fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+///         se.serialize_bytes(self.0)
+///     }
+/// }
+///
+/// impl Serialize for Uuid {
+///     fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+///         se.serialize_newtype_struct(rmps::MSGPACK_EXT_STRUCT_NAME, &(2i8, Bytes(&self.0)))
+///     }
+/// }
+///
+/// struct UuidVisitor;
+///
+/// impl<'de> Visitor<'de> for UuidVisitor {
+///     type Value = Uuid;
+///
+///     fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+///         fmt.write_str("a UUID extension")
+///     }
+///
+///     fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Uuid, D::Error> {
+///         de.deserialize_tuple(2, self)
+///     }
+///
+///     fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Uuid, A::Error> {
+///         let ty: i8 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+///         let data: &[u8] = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+///
+///         if ty != 2 || data.len() != 16 {
+///             return Err(de::Error::custom("not a UUID"));
+///         }
+///
+///         let mut buf = [0; 16];
+///         buf.copy_from_slice(data);
+///         Ok(Uuid(buf))
+///     }
+/// }
+///
+/// impl<'de> Deserialize<'de> for Uuid {
+///     fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Uuid, D::Error> {
+///         de.deserialize_newtype_struct(rmps::MSGPACK_EXT_STRUCT_NAME, UuidVisitor)
+///     }
+/// }
+///
+/// # fn main() {
+/// let id = Uuid([0x42; 16]);
+///
+/// let buf = rmps::to_vec(&id).unwrap();
+/// assert_eq!([0xd8, 0x02], buf[..2]);
+/// assert_eq!(id, rmps::from_slice(&buf).unwrap());
+/// # }
+/// ```
+pub const MSGPACK_EXT_STRUCT_NAME: &str = "_ExtStruct";
+
+/// The name of a newtype struct, which both `Serializer` and `Deserializer` treat as a typed array
+/// extension with the data given as bytes.
+const TYPED_ARRAY_STRUCT_NAME: &str = "_TypedArray";
+
+/// A MessagePack extension of an application-defined type, i.e. an `i8` type id and the data.
+///
+/// Both `Serializer` and `Deserializer` recognize this type and map it to the extension family,
+/// so it round-trips through MessagePack without losing the type id. When deserializing from a
+/// self-describing context, like an untagged enum, extensions come as this type as well.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rmp_serde as rmps;
+///
+/// use rmps::ExtValue;
+///
+/// # fn main() {
+/// let val = ExtValue(7, vec![0x01, 0x02, 0x03, 0x04]);
+///
+/// let buf = rmps::to_vec(&val).unwrap();
+/// assert_eq!(vec![0xd6, 0x07, 0x01, 0x02, 0x03, 0x04], buf);
+/// assert_eq!(val, rmps::from_slice(&buf).unwrap());
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ExtValue(pub i8, pub Vec<u8>);
+
+impl Serialize for ExtValue {
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        se.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &(self.0, Bytes(&self.1)))
+    }
+}
+
+struct ExtValueVisitor;
+
+impl<'de> de::Visitor<'de> for ExtValueVisitor {
+    type Value = ExtValue;
+
+    fn expecting(&self, fmt: &mut Formatter) -> Result<(), fmt::Error> {
+        "a MessagePack extension".fmt(fmt)
+    }
+
+    fn visit_newtype_struct<D>(self, de: D) -> Result<Self::Value, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_tuple(2, self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where A: de::SeqAccess<'de>
+    {
+        let ty: i8 = match seq.next_element()? {
+            Some(ty) => ty,
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+
+        let data = match seq.next_element::<ByteBuf>()? {
+            Some(data) => data.0,
+            None => return Err(de::Error::invalid_length(1, &self)),
+        };
+
+        Ok(ExtValue(ty, data))
+    }
+}
+
+impl<'de> Deserialize<'de> for ExtValue {
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, ExtValueVisitor)
+    }
+}
+
+// Serializes a byte slice as bytes rather than a sequence, which serde does by default.
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        se.serialize_bytes(self.0)
+    }
+}
+
+// Deserializes bytes into a vector rather than from a sequence, which serde does by default.
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_byte_buf(ByteBufVisitor)
+    }
+}
+
+struct ByteBufVisitor;
+
+impl<'de> de::Visitor<'de> for ByteBufVisitor {
+    type Value = ByteBuf;
+
+    fn expecting(&self, fmt: &mut Formatter) -> Result<(), fmt::Error> {
+        "bytes".fmt(fmt)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(ByteBuf(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(ByteBuf(v))
+    }
+}
 
 /// Helper that allows both to encode and decode strings no matter whether they contain valid or
 /// invalid UTF-8.
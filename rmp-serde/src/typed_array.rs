@@ -0,0 +1,85 @@
+//! Serialization of `rmp::TypedArray` as a MessagePack extension.
+//!
+//! Use this module with the `#[serde(with = "...")]` attribute to store a typed array field in the
+//! same wire format `rmp::encode::write_typed_array` writes, so that it can be read by plain
+//! `rmp` or `rmpv` as well. The extension type is `TypedArray::DEFAULT_EXT_TYPE` unless set by
+//! `Config::with_typed_array_ext_type`. Other serializers see a newtype struct around the data as
+//! bytes.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate rmp;
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate rmp_serde as rmps;
+//!
+//! use rmp::{Endian, TypedArray};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Samples {
+//!     #[serde(with = "rmps::typed_array")]
+//!     data: TypedArray,
+//! }
+//!
+//! fn main() {
+//!     let val = Samples {
+//!         data: TypedArray::from_f32(&[0.25, 0.5], Endian::Little),
+//!     };
+//!
+//!     let buf = rmps::to_vec(&val).unwrap();
+//!     assert_eq!(val, rmps::from_slice(&buf).unwrap());
+//!
+//!     // The field is a plain extension.
+//!     let mut rd = &buf[1..];
+//!     assert_eq!(val.data, rmp::decode::read_typed_array(&mut rd, TypedArray::DEFAULT_EXT_TYPE).unwrap());
+//! }
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserializer, Serializer};
+use serde::de::{self, Visitor};
+
+use rmp::TypedArray;
+
+use {Bytes, TYPED_ARRAY_STRUCT_NAME};
+
+/// Serializes the typed array as a MessagePack extension.
+pub fn serialize<S>(array: &TypedArray, se: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+{
+    let mut data = Vec::with_capacity(array.data_len() as usize);
+    rmp::encode::write_typed_array_data(&mut data, array).expect("writing to a vector can't fail");
+
+    se.serialize_newtype_struct(TYPED_ARRAY_STRUCT_NAME, &Bytes(&data))
+}
+
+/// Deserializes the typed array from a MessagePack extension.
+pub fn deserialize<'de, D>(de: D) -> Result<TypedArray, D::Error>
+    where D: Deserializer<'de>
+{
+    de.deserialize_newtype_struct(TYPED_ARRAY_STRUCT_NAME, TypedArrayVisitor)
+}
+
+struct TypedArrayVisitor;
+
+impl<'de> Visitor<'de> for TypedArrayVisitor {
+    type Value = TypedArray;
+
+    fn expecting(&self, fmt: &mut Formatter) -> Result<(), fmt::Error> {
+        "a typed array extension".fmt(fmt)
+    }
+
+    fn visit_newtype_struct<D>(self, de: D) -> Result<Self::Value, D::Error>
+        where D: Deserializer<'de>
+    {
+        de.deserialize_bytes(self)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+        where E: de::Error
+    {
+        rmp::decode::read_typed_array_data(&mut &v[..], v.len() as u32).map_err(de::Error::custom)
+    }
+}
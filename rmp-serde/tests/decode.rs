@@ -10,6 +10,7 @@ use serde::de;
 use serde::Deserialize;
 
 use rmp::Marker;
+use rmp::decode::DecodeLimits;
 use rmps::{Deserializer, Raw, RawRef};
 use rmps::decode::{self, Error};
 
@@ -507,3 +508,367 @@ fn fail_str_invalid_utf8() {
         err => panic!("unexpected error: {:?}", err),
     }
 }
+
+// Accepts bytes only, unlike `ByteBuf`, which also accepts strings.
+#[derive(Debug, PartialEq)]
+struct OnlyBytes(Vec<u8>);
+
+impl<'de> Deserialize<'de> for OnlyBytes {
+    fn deserialize<D>(de: D) -> Result<OnlyBytes, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct BytesVisitor;
+
+        impl<'de> de::Visitor<'de> for BytesVisitor {
+            type Value = OnlyBytes;
+
+            fn expecting(&self, fmt: &mut Formatter) -> Result<(), fmt::Error> {
+                fmt.write_str("bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<OnlyBytes, E> {
+                Ok(OnlyBytes(v.to_vec()))
+            }
+        }
+
+        de.deserialize_bytes(BytesVisitor)
+    }
+}
+
+#[test]
+fn pass_legacy_raw_into_bytes() {
+    // Valid UTF-8 and arbitrary bytes, both written as raw.
+    let buf = [0xa2, 0x6f, 0x6b, 0xa2, 0xcc, 0x80, 0xc4, 0x01, 0x00];
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_legacy_raw(true);
+
+    assert_eq!(OnlyBytes(vec![0x6f, 0x6b]), Deserialize::deserialize(&mut de).unwrap());
+    assert_eq!(OnlyBytes(vec![0xcc, 0x80]), Deserialize::deserialize(&mut de).unwrap());
+    assert_eq!(OnlyBytes(vec![0x00]), Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_raw_into_bytes_without_legacy_mode() {
+    let buf = [0xa2, 0x6f, 0x6b];
+
+    let mut de = Deserializer::new(&buf[..]);
+    let res: Result<OnlyBytes, Error> = Deserialize::deserialize(&mut de);
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn pass_legacy_raw_into_string() {
+    let buf = [0xda, 0x00, 0x02, 0x6f, 0x6b];
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_legacy_raw(true);
+    let actual: String = Deserialize::deserialize(&mut de).unwrap();
+
+    assert_eq!("ok", actual);
+}
+
+#[test]
+fn pass_from_read_ref_with_config() {
+    use rmps::Config;
+
+    let buf = [0xa2, 0xcc, 0x80];
+
+    let config = Config::default().with_legacy_raw(true).with_human_readable(false);
+    let de = &mut Deserializer::from_read_ref_with_config(&buf[..], config);
+
+    assert!(!serde::Deserializer::is_human_readable(&de));
+    assert_eq!(OnlyBytes(vec![0xcc, 0x80]), Deserialize::deserialize(de).unwrap());
+
+    let config = Config::default().with_max_depth(1);
+    let mut de = Deserializer::from_read_ref_with_config(&[0x91, 0x90][..], config);
+    let res: Result<de::IgnoredAny, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_from_unpacker_in_chunks() {
+    // [42, "le message"], [43, "ok"]
+    let buf = [
+        0x92, 0x2a, 0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65, 0x92, 0x2b,
+        0xa2, 0x6f, 0x6b,
+    ];
+
+    for chunk in 1..buf.len() + 1 {
+        let mut unpacker = rmp::decode::Unpacker::new();
+        let mut messages: Vec<(u8, String)> = Vec::new();
+
+        for data in buf.chunks(chunk) {
+            unpacker.feed(data);
+            while let Some(msg) = decode::from_unpacker(&mut unpacker).unwrap() {
+                messages.push(msg);
+            }
+        }
+
+        assert_eq!(vec![(42, "le message".to_string()), (43, "ok".to_string())], messages);
+    }
+}
+
+#[test]
+fn fail_from_unpacker_reserved() {
+    let mut unpacker = rmp::decode::Unpacker::new();
+    unpacker.feed(&[0x91, 0xc1]);
+
+    match decode::from_unpacker::<Vec<u8>>(&mut unpacker) {
+        Err(Error::TypeMismatch(Marker::Reserved)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_from_unpacker_with_limits() {
+    // 1030 nested arrays, just deeper than the default limit.
+    let mut buf = vec![0x91; 1029];
+    buf.push(0x90);
+
+    let limits = DecodeLimits { max_depth: 1030, ..DecodeLimits::default() };
+    let mut unpacker = rmp::decode::Unpacker::with_limits(limits);
+    unpacker.feed(&buf);
+
+    // The recursion needs more stack than test threads have by default.
+    let handle = std::thread::Builder::new().stack_size(64 << 20).spawn(move || {
+        decode::from_unpacker::<de::IgnoredAny>(&mut unpacker).unwrap().is_some()
+    }).unwrap();
+    assert!(handle.join().unwrap());
+}
+
+#[test]
+fn fail_hostile_bin_len_from_read() {
+    // Claims 4 GiB of data, which must not be allocated upfront.
+    let buf = [0xc6, 0xff, 0xff, 0xff, 0xff, 0x00, 0x01];
+    let mut de = Deserializer::new(Cursor::new(&buf[..]));
+
+    let res: Result<serde_bytes::ByteBuf, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::InvalidDataRead(ref err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_str_len_over_limit() {
+    let buf = [0xd9, 0x03, 0x61, 0x62, 0x63];
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_limits(DecodeLimits { max_data_len: 2, ..DecodeLimits::default() });
+
+    let res: Result<String, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::LengthLimitExceeded(Marker::Str8)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_fixarray_len_over_limit() {
+    let buf = [0x93, 0x01, 0x02, 0x03];
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_limits(DecodeLimits { max_container_len: 2, ..DecodeLimits::default() });
+
+    let res: Result<Vec<u8>, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::LengthLimitExceeded(Marker::FixArray(3))) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_map32_len_over_limit_from_read() {
+    let buf = [0xdf, 0xff, 0xff, 0xff, 0xff];
+    let mut de = Deserializer::new(Cursor::new(&buf[..]));
+    de.set_limits(DecodeLimits { max_container_len: 1024, ..DecodeLimits::default() });
+
+    let res: Result<std::collections::BTreeMap<u8, u8>, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::LengthLimitExceeded(Marker::Map32)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_total_len_within_limit() {
+    let buf = [0x92, 0xa2, 0x6c, 0x65, 0x2a];
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_limits(DecodeLimits { max_total_len: 5, ..DecodeLimits::default() });
+
+    assert_eq!(("le".to_string(), 42), Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_total_len_over_limit() {
+    let buf = [0x92, 0xa2, 0x6c, 0x65, 0x2a];
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_limits(DecodeLimits { max_total_len: 4, ..DecodeLimits::default() });
+
+    let res: Result<(String, u8), Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::TotalLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_deeply_nested_array() {
+    let mut buf = vec![0x91; 100000];
+    buf.push(0xc0);
+
+    let mut de = Deserializer::new(&buf[..]);
+    let res: Result<de::IgnoredAny, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_deeply_nested_map_from_read() {
+    let mut buf = Vec::new();
+    for _ in 0..100000 {
+        buf.extend_from_slice(&[0x81, 0xc0]);
+    }
+    buf.push(0xc0);
+
+    let mut de = Deserializer::new(Cursor::new(&buf[..]));
+    let res: Result<de::IgnoredAny, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_nested_array_at_max_depth() {
+    let buf = [0x91, 0x91, 0x91, 0x2a];
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_max_depth(3);
+    assert_eq!(vec![vec![vec![42]]], Vec::<Vec<Vec<u8>>>::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_max_depth(2);
+    let res: Result<Vec<Vec<Vec<u8>>>, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_sibling_arrays_do_not_add_up() {
+    let buf = [0x93, 0x91, 0x01, 0x91, 0x02, 0x91, 0x03];
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_max_depth(2);
+    assert_eq!(vec![vec![1], vec![2], vec![3]], Vec::<Vec<u8>>::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn pass_stream() {
+    use rmps::decode::StreamDeserializer;
+
+    // "a", "le".
+    let buf = [0xa1, 0x61, 0xa2, 0x6c, 0x65];
+
+    let mut stream = StreamDeserializer::<_, Raw>::new(&buf[..0]);
+    assert!(stream.next().is_none());
+    assert_eq!(0, stream.byte_offset());
+
+    let mut stream = StreamDeserializer::<_, Raw>::new(Cursor::new(&buf[..]));
+    assert_eq!(Some("a"), stream.next().unwrap().unwrap().as_str());
+    assert_eq!(2, stream.byte_offset());
+    assert_eq!(Some("le"), stream.next().unwrap().unwrap().as_str());
+    assert_eq!(5, stream.byte_offset());
+    assert!(stream.next().is_none());
+    assert_eq!(5, stream.into_inner().position());
+}
+
+#[test]
+fn pass_stream_of_values() {
+    use rmps::decode::StreamDeserializer;
+
+    // [1, 2] [] [3].
+    let buf = [0x92, 0x01, 0x02, 0x90, 0x91, 0x03];
+
+    let mut stream = StreamDeserializer::<_, Vec<u8>>::new(&buf[..]);
+    let mut offsets = Vec::new();
+    let mut vals = Vec::new();
+    while let Some(val) = stream.next() {
+        vals.push(val.unwrap());
+        offsets.push(stream.byte_offset());
+    }
+
+    assert_eq!(vec![vec![1, 2], vec![], vec![3]], vals);
+    assert_eq!(vec![3, 4, 6], offsets);
+}
+
+#[test]
+fn fail_stream_truncated() {
+    use std::io::ErrorKind;
+    use rmps::decode::StreamDeserializer;
+
+    // [1], then [2, <missing>].
+    let buf = [0x91, 0x01, 0x92, 0x02];
+
+    let mut stream = StreamDeserializer::<_, Vec<u8>>::new(&buf[..]);
+    assert_eq!(vec![1], stream.next().unwrap().unwrap());
+    match stream.next() {
+        Some(Err(Error::InvalidMarkerRead(ref err))) if err.kind() == ErrorKind::UnexpectedEof => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(4, stream.byte_offset());
+    assert!(stream.next().is_none());
+
+    // A u16 missing its second byte.
+    let mut stream = StreamDeserializer::<_, u16>::new(&[0x2a, 0xcd, 0x01][..]);
+    assert_eq!(42, stream.next().unwrap().unwrap());
+    match stream.next() {
+        Some(Err(Error::InvalidDataRead(ref err))) if err.kind() == ErrorKind::UnexpectedEof => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn fail_stream_stops_after_error() {
+    use rmps::decode::StreamDeserializer;
+
+    // 1, "le", 2.
+    let buf = [0x01, 0xa2, 0x6c, 0x65, 0x02];
+
+    let mut stream = StreamDeserializer::<_, u8>::new(&buf[..]);
+    assert_eq!(1, stream.next().unwrap().unwrap());
+    match stream.next() {
+        Some(Err(Error::Syntax(..))) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn pass_stream_limits_per_message() {
+    use rmps::Config;
+    use rmps::decode::StreamDeserializer;
+
+    // "abc" three times, which is 12 bytes in total.
+    let buf = [0xa3, 0x61, 0x62, 0x63, 0xa3, 0x61, 0x62, 0x63, 0xa3, 0x61, 0x62, 0x63];
+
+    let limits = DecodeLimits { max_total_len: 4, ..DecodeLimits::default() };
+    let stream = StreamDeserializer::<_, String>::with_config(&buf[..], Config::default().with_limits(limits));
+    let vals: Result<Vec<String>, Error> = stream.collect();
+    assert_eq!(vec!["abc", "abc", "abc"], vals.unwrap());
+
+    let limits = DecodeLimits { max_total_len: 3, ..DecodeLimits::default() };
+    let mut stream = StreamDeserializer::<_, String>::with_config(&buf[..], Config::default().with_limits(limits));
+    match stream.next() {
+        Some(Err(Error::TotalLimitExceeded)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -306,6 +306,65 @@ fn pass_struct_variant() {
     }
 }
 
+#[test]
+fn pass_enum_of_any_repr() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum {
+        Unit,
+        New(u8),
+        Struct { f1: u8 },
+    }
+
+    let cases = vec![
+        // {1 => 5}, {"New" => 5}, [1, 5], ["New", 5].
+        (Enum::New(5), vec![0x81, 0x01, 0x05]),
+        (Enum::New(5), vec![0x81, 0xa3, 0x4e, 0x65, 0x77, 0x05]),
+        (Enum::New(5), vec![0x92, 0x01, 0x05]),
+        (Enum::New(5), vec![0x92, 0xa3, 0x4e, 0x65, 0x77, 0x05]),
+        // {"Struct" => [7]}, [2, [7]].
+        (Enum::Struct { f1: 7 }, vec![0x81, 0xa6, 0x53, 0x74, 0x72, 0x75, 0x63, 0x74, 0x91, 0x07]),
+        (Enum::Struct { f1: 7 }, vec![0x92, 0x02, 0x91, 0x07]),
+        // {0 => nil}, [0, nil], 0, "Unit".
+        (Enum::Unit, vec![0x81, 0x00, 0xc0]),
+        (Enum::Unit, vec![0x92, 0x00, 0xc0]),
+        (Enum::Unit, vec![0x00]),
+        (Enum::Unit, vec![0xa4, 0x55, 0x6e, 0x69, 0x74]),
+    ];
+
+    for (expected, buf) in cases {
+        assert_eq!(expected, rmps::from_slice(&buf).unwrap());
+        assert_eq!(expected, rmps::from_read(&buf[..]).unwrap());
+    }
+}
+
+#[test]
+fn fail_enum_bare_variant_with_payload() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum {
+        Unit,
+        New(u8),
+    }
+
+    // "New" without a payload.
+    assert!(rmps::from_slice::<Enum>(&[0xa3, 0x4e, 0x65, 0x77]).is_err());
+    // An unknown name.
+    assert!(rmps::from_slice::<Enum>(&[0xa3, 0x4f, 0x6c, 0x64]).is_err());
+}
+
+#[test]
+fn fail_enum_array_mismatch() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum {
+        A(i32),
+    }
+
+    // [0, 36, 37].
+    match rmps::from_slice::<Enum>(&[0x93, 0x00, 0x24, 0x25]).unwrap_err() {
+        Error::LengthMismatch(3) => (),
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
+
 #[test]
 fn pass_adjacently_tagged_enum() {
     // ["Foo", 123]
@@ -410,3 +469,108 @@ fn pass_from_ref() {
 
     assert_eq!(Dog { name: "Bobby", age: 8 }, rmps::from_read_ref(&buf).unwrap());
 }
+
+#[test]
+fn fail_typed_array_from_foreign_ext() {
+    #[derive(Debug, Deserialize)]
+    struct Samples(#[serde(with = "rmps::typed_array")] rmp::TypedArray);
+
+    let buf = [0xd4, 0x01, 0x03];
+
+    assert!(rmps::from_slice::<Samples>(&buf).is_err());
+}
+
+#[test]
+fn pass_ignored_ext() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        a: u8,
+    }
+
+    // {"a": 1, "b": ext(1, [0x00, 0x01, 0x02])}, where the unknown field is skipped.
+    let buf = [0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0xc7, 0x03, 0x01, 0x00, 0x01, 0x02];
+
+    assert_eq!(Struct { a: 1 }, rmps::from_slice(&buf).unwrap());
+}
+
+#[test]
+fn fail_ext_value_from_other_type() {
+    let buf = [0x92, 0x01, 0xc4, 0x00];
+
+    match rmps::from_slice::<rmps::ExtValue>(&buf) {
+        Err(Error::TypeMismatch(rmp::Marker::FixArray(2))) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_ext_as_other_type() {
+    let buf = [0xd4, 0x01, 0x00];
+
+    assert!(rmps::from_slice::<u8>(&buf).is_err());
+    assert!(rmps::from_slice::<Vec<u8>>(&buf).is_err());
+}
+
+#[test]
+fn fail_deeply_nested_newtype_variant() {
+    #[derive(Debug, Deserialize)]
+    enum List {
+        Cons(Box<List>),
+        Nil,
+    }
+
+    // Each level is a single-entry map from the variant index to the value: {0 => ...}.
+    let mut buf = Vec::new();
+    for _ in 0..100000 {
+        buf.extend_from_slice(&[0x81, 0x00]);
+    }
+    buf.extend_from_slice(&[0x81, 0x01, 0xc0]);
+
+    let mut de = Deserializer::new(&buf[..]);
+    let res: Result<List, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_deeply_nested_struct() {
+    #[derive(Debug, Deserialize)]
+    struct Tree {
+        children: Vec<Tree>,
+    }
+
+    // A struct is an array of its fields, so each level takes two arrays.
+    let mut buf = vec![0x91; 100000];
+    buf.push(0x90);
+
+    let mut de = Deserializer::new(&buf[..]);
+    let res: Result<Tree, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_nested_unit_variant_at_max_depth() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum {
+        A,
+    }
+
+    let buf = [0x91, 0x81, 0x00, 0xc0];
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_max_depth(2);
+    assert_eq!(vec![Enum::A], Vec::<Enum>::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_max_depth(1);
+    let res: Result<Vec<Enum>, Error> = Deserialize::deserialize(&mut de);
+    match res.err() {
+        Some(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -7,7 +7,7 @@ use std::io::Cursor;
 
 use serde::Serialize;
 
-use rmps::{Raw, RawRef, Serializer};
+use rmps::{Config, Raw, RawRef, Serializer};
 use rmps::encode::{self, Error};
 
 #[test]
@@ -243,6 +243,51 @@ fn pass_seq() {
     assert_eq!([0x92, 0xa2, 0x6c, 0x65, 0xa4, 0x73, 0x68, 0x69, 0x74], buf);
 }
 
+#[test]
+fn pass_seq_of_unknown_length() {
+    struct Iter(u32);
+
+    impl Serialize for Iter {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            se.collect_seq((0..self.0).filter(|val| val % 2 == 1))
+        }
+    }
+
+    let mut buf = Vec::new();
+    Iter(6).serialize(&mut Serializer::new(&mut buf)).unwrap();
+    assert_eq!(vec![0x93, 0x01, 0x03, 0x05], buf);
+
+    // The header is compacted to its shortest form.
+    let buf = rmps::to_vec(&Iter(40000)).unwrap();
+    assert_eq!([0xdc, 0x4e, 0x20, 0x01], buf[..4]);
+    assert_eq!(3 + 64 + 64 * 2 + 19872 * 3, buf.len());
+}
+
+#[test]
+fn pass_nested_maps_of_unknown_length() {
+    struct Map(Vec<Vec<u8>>);
+
+    impl Serialize for Map {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            se.collect_map(self.0.iter().enumerate().map(|(idx, val)| (idx, Bytes(val))))
+        }
+    }
+
+    struct Bytes<'a>(&'a [u8]);
+
+    impl<'a> Serialize for Bytes<'a> {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            se.collect_seq(self.0)
+        }
+    }
+
+    let mut se = Serializer::new(Vec::new());
+    Map(vec![vec![], vec![42]]).serialize(&mut se).unwrap();
+
+    // Expect: {0: [], 1: [42]}.
+    assert_eq!(vec![0x82, 0x00, 0x90, 0x01, 0x91, 0x2a], se.into_inner());
+}
+
 #[test]
 fn pass_map() {
     use std::collections::BTreeMap;
@@ -303,6 +348,38 @@ fn pass_bin() {
     assert_eq!(vec![0xc4, 0x02, 0xcc, 0x80], buf);
 }
 
+#[test]
+fn pass_bin_single_write() {
+    use std::io::{self, IoSlice, Write};
+
+    use serde_bytes::Bytes;
+
+    struct Calls(Vec<Vec<u8>>);
+
+    impl Write for Calls {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+            let call: Vec<u8> = bufs.iter().flat_map(|buf| buf.iter().cloned()).collect();
+            let len = call.len();
+            self.0.push(call);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut se = Serializer::new(Calls(Vec::new()));
+    Bytes::new(&[0xcc, 0x80]).serialize(&mut se).unwrap();
+
+    assert_eq!(vec![vec![0xc4, 0x02, 0xcc, 0x80]], se.into_inner().0);
+}
+
 #[test]
 fn pass_to_vec() {
     assert_eq!(vec![0xc0], encode::to_vec(&()).unwrap());
@@ -365,3 +442,127 @@ fn pass_raw_ref_invalid_utf8() {
 
     assert_eq!(vec![0xa4, 0x92, 0xcc, 0xc8, 0x90], buf);
 }
+
+#[test]
+fn pass_legacy_raw() {
+    use serde_bytes::Bytes;
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_legacy_raw(true);
+
+    "le message".serialize(&mut se).unwrap();
+    "a".repeat(32).serialize(&mut se).unwrap();
+    Bytes::from(&[0xcc, 0x80][..]).serialize(&mut se).unwrap();
+
+    let buf = se.into_inner();
+    assert_eq!([0xaa], buf[..1]);
+    assert_eq!([0xda, 0x00, 0x20], buf[11..14]);
+    assert_eq!([0xa2, 0xcc, 0x80], buf[46..]);
+}
+
+#[test]
+fn pass_fixed_width_ints() {
+    let config = Config::default().with_compact_ints(false);
+    let mut se = Serializer::with_config(Vec::new(), config);
+
+    (1u8, 1u16, 1u32, 1u64, -1i8, -1i16, -1i32, -1i64).serialize(&mut se).unwrap();
+
+    let expected = vec![
+        0x98,
+        0xcc, 0x01,
+        0xcd, 0x00, 0x01,
+        0xce, 0x00, 0x00, 0x00, 0x01,
+        0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0xd0, 0xff,
+        0xd1, 0xff, 0xff,
+        0xd2, 0xff, 0xff, 0xff, 0xff,
+        0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    ];
+    assert_eq!(expected, se.into_inner());
+}
+
+#[test]
+fn pass_compact_floats() {
+    let config = Config::default().with_compact_floats(true);
+    let mut se = Serializer::with_config(Vec::new(), config);
+
+    (0.5f64, 0.1f64).serialize(&mut se).unwrap();
+
+    // 0.5 fits into f32 exactly, 0.1 doesn't.
+    let expected = vec![
+        0x92,
+        0xca, 0x3f, 0x00, 0x00, 0x00,
+        0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a,
+    ];
+    assert_eq!(expected, se.into_inner());
+}
+
+#[test]
+fn pass_human_readable_config() {
+    use serde::Serializer as SerializerTrait;
+    use rmps::encode::Ext;
+
+    let se = &mut Serializer::new(Vec::new());
+    assert!(se.is_human_readable());
+
+    let se = &mut Serializer::with_config(Vec::new(), Config::default().with_human_readable(false));
+    assert!(!se.is_human_readable());
+
+    let se = &mut Serializer::with_config(Vec::new(), Config::default().with_human_readable(false))
+        .with_struct_map();
+    assert!(!se.is_human_readable());
+}
+
+// Serializes itself as the given number of nested single-element arrays around a nil, without
+// building a deep structure in memory, which would overflow the stack when dropped.
+struct Nested(usize);
+
+impl Serialize for Nested {
+    fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        if self.0 == 0 {
+            return se.serialize_unit();
+        }
+
+        let mut seq = se.serialize_seq(Some(1))?;
+        seq.serialize_element(&Nested(self.0 - 1))?;
+        seq.end()
+    }
+}
+
+#[test]
+fn fail_deeply_nested_seq() {
+    let mut se = Serializer::new(Vec::new());
+
+    match Nested(100000).serialize(&mut se) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_nested_seq_at_max_depth() {
+    let mut se = Serializer::new(Vec::new());
+    se.set_max_depth(3);
+
+    Nested(3).serialize(&mut se).unwrap();
+    assert_eq!(vec![0x91, 0x91, 0x91, 0xc0], se.into_inner());
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_max_depth(3);
+
+    match Nested(4).serialize(&mut se) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_sibling_seqs_do_not_add_up() {
+    let mut se = Serializer::new(Vec::new());
+    se.set_max_depth(2);
+
+    (Nested(1), Nested(1), Nested(1)).serialize(&mut se).unwrap();
+    assert_eq!(vec![0x93, 0x91, 0xc0, 0x91, 0xc0, 0x91, 0xc0], se.into_inner());
+}
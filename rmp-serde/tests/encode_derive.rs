@@ -6,7 +6,7 @@ extern crate rmp_serde as rmps;
 
 use serde::Serialize;
 use rmps::Serializer;
-use rmps::encode::{Ext, UnderlyingWrite};
+use rmps::encode::{EnumRepr, Error, Ext, UnderlyingWrite, VariantId};
 
 #[test]
 fn pass_unit_struct() {
@@ -164,10 +164,120 @@ fn serialize_struct_variant_as_map() {
         .with_struct_map();
     Enum::V1 { f1: 42 }.serialize(&mut se).unwrap();
 
+    // Expect: {0 => {"f1": 42}}.
+    assert_eq!(vec![0x81, 0x00, 0x81, 0xa2, 0x66, 0x31, 0x2a], se.into_inner());
+}
+
+#[test]
+fn serialize_struct_variant_as_tuple() {
+    #[derive(Serialize)]
+    enum Enum {
+        V1 {
+            f1: u32,
+        }
+    }
+
+    let mut se = Serializer::new(Vec::new())
+        .with_struct_map()
+        .with_struct_tuple();
+    Enum::V1 { f1: 42 }.serialize(&mut se).unwrap();
+
+    // Expect: {0 => [42]}, the same as without wrapping.
+    assert_eq!(vec![0x81, 0x00, 0x91, 0x2a], se.into_inner());
+}
+
+#[test]
+fn serialize_struct_variant_as_map_in_array() {
+    #[derive(Serialize)]
+    enum Enum {
+        V1 {
+            f1: u32,
+        }
+    }
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_enum_repr(EnumRepr::Array);
+    let mut se = se.with_struct_map();
+    Enum::V1 { f1: 42 }.serialize(&mut se).unwrap();
+
     // Expect: [0, {"f1": 42}].
     assert_eq!(vec![0x92, 0x00, 0x81, 0xa2, 0x66, 0x31, 0x2a], se.into_inner());
 }
 
+#[test]
+fn pass_variants_by_name() {
+    #[derive(Serialize)]
+    enum Enum {
+        Unit,
+        New(u8),
+        Tuple(u8, u8),
+        Struct { f1: u8 },
+    }
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_variant_id(VariantId::Name);
+
+    Enum::Unit.serialize(&mut se).unwrap();
+    Enum::New(1).serialize(&mut se).unwrap();
+    Enum::Tuple(2, 3).serialize(&mut se).unwrap();
+    Enum::Struct { f1: 4 }.serialize(&mut se).unwrap();
+
+    // Expect: {"Unit" => nil} {"New" => 1} {"Tuple" => [2, 3]} {"Struct" => [4]}.
+    let expected = vec![
+        0x81, 0xa4, 0x55, 0x6e, 0x69, 0x74, 0xc0,
+        0x81, 0xa3, 0x4e, 0x65, 0x77, 0x01,
+        0x81, 0xa5, 0x54, 0x75, 0x70, 0x6c, 0x65, 0x92, 0x02, 0x03,
+        0x81, 0xa6, 0x53, 0x74, 0x72, 0x75, 0x63, 0x74, 0x91, 0x04,
+    ];
+    assert_eq!(expected, se.into_inner());
+}
+
+#[test]
+fn pass_variants_as_array() {
+    #[derive(Serialize)]
+    enum Enum {
+        Unit,
+        New(u8),
+        Struct { f1: u8 },
+    }
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_enum_repr(EnumRepr::Array);
+
+    Enum::Unit.serialize(&mut se).unwrap();
+    Enum::New(1).serialize(&mut se).unwrap();
+    Enum::Struct { f1: 4 }.serialize(&mut se).unwrap();
+
+    // Expect: [0, nil] [1, 1] [2, [4]].
+    assert_eq!(vec![0x92, 0x00, 0xc0, 0x92, 0x01, 0x01, 0x92, 0x02, 0x91, 0x04], se.into_inner());
+}
+
+#[test]
+fn pass_bare_unit_variants() {
+    #[derive(Serialize)]
+    enum Enum {
+        Unit,
+        New(u8),
+    }
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_bare_unit_variants(true);
+    Enum::Unit.serialize(&mut se).unwrap();
+    Enum::New(1).serialize(&mut se).unwrap();
+
+    // Expect: 0 {1 => 1}.
+    assert_eq!(vec![0x00, 0x81, 0x01, 0x01], se.into_inner());
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_bare_unit_variants(true);
+    se.set_variant_id(VariantId::Name);
+    se.set_max_depth(0);
+    Enum::Unit.serialize(&mut se).unwrap();
+
+    // Expect: "Unit", which is not a container, so fits into zero depth.
+    assert_eq!(vec![0xa4, 0x55, 0x6e, 0x69, 0x74], se.into_inner());
+}
+
 #[test]
 fn pass_struct_as_map_using_ext() {
     #[derive(Serialize)]
@@ -191,6 +301,38 @@ fn pass_struct_as_map_using_ext() {
                se.into_inner());
 }
 
+#[test]
+fn pass_struct_as_map_using_config() {
+    use rmps::Config;
+    use rmps::encode::StructRepr;
+
+    #[derive(Serialize)]
+    struct Dog<'a> {
+        name: &'a str,
+        age: u16,
+    }
+
+    #[derive(Serialize)]
+    enum Enum {
+        V1 {
+            f1: u32,
+        }
+    }
+
+    let config = Config::default().with_struct_repr(StructRepr::Map);
+    let mut se = Serializer::with_config(Vec::new(), config);
+
+    Dog { name: "Bobby", age: 8 }.serialize(&mut se).unwrap();
+    Enum::V1 { f1: 42 }.serialize(&mut se).unwrap();
+
+    // Expect: {"name": "Bobby", "age": 8} {0 => {"f1": 42}}.
+    let expected = vec![
+        0x82, 0xa4, 0x6e, 0x61, 0x6d, 0x65, 0xa5, 0x42, 0x6f, 0x62, 0x62, 0x79, 0xa3, 0x61, 0x67, 0x65, 0x08,
+        0x81, 0x00, 0x81, 0xa2, 0x66, 0x31, 0x2a,
+    ];
+    assert_eq!(expected, se.into_inner());
+}
+
 #[test]
 fn pass_struct_as_tuple_using_double_ext() {
     #[derive(Serialize)]
@@ -266,3 +408,50 @@ fn pass_struct_as_map_using_triple_ext_many_times() {
     assert_eq!(vec![0x82, 0xa4, 0x6e, 0x61, 0x6d, 0x65, 0xa5, 0x42, 0x6f, 0x62, 0x62, 0x79, 0xa3, 0x61, 0x67, 0x65, 0x08],
                se.into_inner());
 }
+
+#[test]
+fn pass_nested_variants_at_max_depth() {
+    #[derive(Serialize)]
+    enum List {
+        Cons(u8, Box<List>),
+        Nil,
+    }
+
+    let val = List::Cons(1, Box::new(List::Nil));
+
+    // {0 => [1, {1 => nil}]}
+    let mut se = Serializer::new(Vec::new());
+    se.set_max_depth(3);
+    val.serialize(&mut se).unwrap();
+    assert_eq!(vec![0x81, 0x00, 0x92, 0x01, 0x81, 0x01, 0xc0], se.into_inner());
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_max_depth(2);
+    match val.serialize(&mut se) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_nested_struct_map_over_max_depth() {
+    #[derive(Serialize)]
+    struct Node {
+        next: Option<Box<Node>>,
+    }
+
+    let val = Node { next: Some(Box::new(Node { next: None })) };
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_max_depth(2);
+    let mut se = se.with_struct_map();
+    val.serialize(&mut se).unwrap();
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_max_depth(1);
+    let mut se = se.with_struct_map();
+    match val.serialize(&mut se) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
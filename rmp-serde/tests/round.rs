@@ -3,6 +3,10 @@ extern crate serde;
 extern crate serde_derive;
 extern crate rmp;
 extern crate rmp_serde as rmps;
+#[cfg(feature = "async")]
+extern crate futures_executor;
+#[cfg(feature = "bytes")]
+extern crate bytes;
 
 use std::borrow::Cow;
 use std::io::Cursor;
@@ -65,6 +69,34 @@ fn round_trip_option_cow() {
     assert_eq!(expected, Deserialize::deserialize(&mut de).unwrap());
 }
 
+#[test]
+fn round_trip_flatten() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Inner {
+        b: u8,
+        c: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Outer {
+        a: u8,
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    let expected = Outer {
+        a: 1,
+        inner: Inner { b: 2, c: "le message".into() },
+    };
+
+    let buf = rmps::to_vec(&expected).unwrap();
+    // Expect: {"a": 1, "b": 2, "c": "le message"}.
+    assert_eq!([0x83, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02, 0xa1, 0x63, 0xaa], buf[..10]);
+
+    let actual: Outer = rmps::from_slice(&buf).unwrap();
+    assert_eq!(expected, actual);
+}
+
 #[test]
 fn round_enum_with_newtype_struct() {
     use serde::Serialize;
@@ -155,3 +187,390 @@ fn round_struct_as_map() {
 
     assert_eq!(dog1, check);
 }
+
+#[cfg(feature = "async")]
+#[test]
+fn round_trip_async() {
+    use futures_executor::block_on;
+    use rmps::decode::from_read_async;
+    use rmps::encode::write_async;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Dog {
+        name: String,
+        age: u16,
+    }
+
+    let dog = Dog {
+        name: "Frankie".into(),
+        age: 42,
+    };
+
+    let mut buf = Vec::new();
+    block_on(write_async(&mut buf, &dog)).unwrap();
+    block_on(write_async(&mut buf, &(1, "two"))).unwrap();
+
+    let mut rd = &buf[..];
+    assert_eq!(dog, block_on(from_read_async::<_, Dog>(&mut rd)).unwrap());
+    assert_eq!((1, "two".to_string()), block_on(from_read_async(&mut rd)).unwrap());
+    assert!(rd.is_empty());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn fail_async_over_default_depth() {
+    use futures_executor::block_on;
+    use rmps::decode::{from_read_async, Error};
+
+    // 1100 nested arrays, of which the last one isn't sent.
+    let buf = vec![0x91; 1100];
+    let mut rd = &buf[..];
+
+    match block_on(from_read_async::<_, serde::de::IgnoredAny>(&mut rd)) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn round_trip_i128_as_ext() {
+    use rmp::{DEFAULT_I128_EXT_TYPE, DEFAULT_U128_EXT_TYPE};
+    use rmps::Config;
+
+    let config = Config::default()
+        .with_i128_ext_type(Some(DEFAULT_I128_EXT_TYPE))
+        .with_u128_ext_type(Some(DEFAULT_U128_EXT_TYPE));
+
+    let mut se = Serializer::with_config(Vec::new(), config);
+    (i128::min_value(), u128::max_value(), 42i128).serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    // Each integer is a fixext16, regardless of the value.
+    assert_eq!(3 * 18 + 1, buf.len());
+    assert_eq!([0xd8, 0x7d], buf[1..3]);
+
+    let mut rd = &buf[1..];
+    assert_eq!(i128::min_value(), rmp::decode::read_i128(&mut rd, DEFAULT_I128_EXT_TYPE).unwrap());
+    assert_eq!(u128::max_value(), rmp::decode::read_u128(&mut rd, DEFAULT_U128_EXT_TYPE).unwrap());
+
+    let mut de = Deserializer::with_config(&buf[..], config);
+    let val: (i128, u128, i128) = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!((i128::min_value(), u128::max_value(), 42), val);
+
+    // Native integers are accepted as well.
+    let mut de = Deserializer::with_config(&[0xd0, 0xd6][..], config);
+    assert_eq!(-42i128, Deserialize::deserialize(&mut de).unwrap());
+
+    // Without the setting the extension is foreign.
+    assert!(rmps::from_slice::<(i128, u128, i128)>(&buf).is_err());
+}
+
+#[test]
+fn round_trip_i128_as_native_int_by_default() {
+    let buf = rmps::to_vec(&(i128::from(i64::min_value()), u128::from(u64::max_value()))).unwrap();
+
+    assert_eq!(vec![0x92, 0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0, 0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], buf);
+    assert_eq!((i128::from(i64::min_value()), u128::from(u64::max_value())), rmps::from_slice(&buf).unwrap());
+
+    // Wider values have no native representation.
+    assert!(rmps::to_vec(&(i128::from(i64::min_value()) - 1)).is_err());
+    assert!(rmps::to_vec(&(u128::from(u64::max_value()) + 1)).is_err());
+}
+
+#[test]
+fn round_trip_i128_ext_type_untagged() {
+    use rmps::{Config, ExtValue};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Field {
+        Int(i64),
+        Ext(ExtValue),
+    }
+
+    // An application extension of the type set for `i128` is still an extension when the type
+    // isn't known upfront.
+    let config = Config::default().with_i128_ext_type(Some(rmp::DEFAULT_I128_EXT_TYPE));
+    let val = vec![Field::Int(-42), Field::Ext(ExtValue(rmp::DEFAULT_I128_EXT_TYPE, vec![0x42; 16]))];
+
+    let mut se = Serializer::with_config(Vec::new(), config);
+    val.serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    let mut de = Deserializer::with_config(&buf[..], config);
+    assert_eq!(val, Vec::<Field>::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn round_trip_typed_array() {
+    use rmp::{Endian, TypedArray};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Samples {
+        #[serde(with = "rmps::typed_array")]
+        data: TypedArray,
+        rate: u32,
+    }
+
+    let val = Samples {
+        data: TypedArray::from_f16_bits(&[0x3c00, 0xc000], Endian::Big),
+        rate: 48000,
+    };
+
+    let buf = rmps::to_vec_named(&val).unwrap();
+    assert_eq!(val, rmps::from_slice(&buf).unwrap());
+    assert_eq!(val, rmps::from_read(&buf[..]).unwrap());
+
+    let mut expected = Vec::new();
+    rmp::encode::write_typed_array(&mut expected, &val.data, TypedArray::DEFAULT_EXT_TYPE).unwrap();
+    assert!(buf.windows(expected.len()).any(|w| w == &expected[..]));
+}
+
+#[test]
+fn round_trip_typed_array_ext_type() {
+    use rmp::{Endian, TypedArray};
+    use rmps::Config;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Samples(#[serde(with = "rmps::typed_array")] TypedArray);
+
+    let val = Samples(TypedArray::from_i32(&[1, 2], Endian::Little));
+    let config = Config::default().with_typed_array_ext_type(3);
+
+    let mut se = Serializer::with_config(Vec::new(), config);
+    val.serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    assert_eq!(val.0, rmp::decode::read_typed_array(&mut &buf[..], 3).unwrap());
+
+    let mut de = Deserializer::with_config(&buf[..], config);
+    assert_eq!(val, Samples::deserialize(&mut de).unwrap());
+    assert!(rmps::from_slice::<Samples>(&buf).is_err());
+}
+
+#[test]
+fn round_trip_enum_reprs() {
+    use rmps::encode::{EnumRepr, Ext, UnderlyingWrite, VariantId};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Enum {
+        Unit,
+        New(String),
+        Tuple(u8, Option<u8>),
+        Struct { f1: Vec<Enum> },
+    }
+
+    let val = vec![
+        Enum::Unit,
+        Enum::New("le message".into()),
+        Enum::Tuple(1, None),
+        Enum::Struct { f1: vec![Enum::Unit, Enum::Tuple(2, Some(3))] },
+    ];
+
+    for &id in [VariantId::Index, VariantId::Name].iter() {
+        for &repr in [EnumRepr::Map, EnumRepr::Array].iter() {
+            for &bare in [false, true].iter() {
+                let mut se = Serializer::new(Vec::new());
+                se.set_variant_id(id);
+                se.set_enum_repr(repr);
+                se.set_bare_unit_variants(bare);
+                val.serialize(&mut se).unwrap();
+                let buf = se.into_inner();
+
+                assert_eq!(val, rmps::from_slice::<Vec<Enum>>(&buf).unwrap());
+
+                let mut se = Serializer::new(Vec::new());
+                se.set_variant_id(id);
+                se.set_enum_repr(repr);
+                se.set_bare_unit_variants(bare);
+                let mut se = se.with_struct_map();
+                val.serialize(&mut se).unwrap();
+                let buf = se.into_inner();
+
+                assert_eq!(val, rmps::from_slice::<Vec<Enum>>(&buf).unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn round_trip_with_config() {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use rmp::decode::DecodeLimits;
+    use rmps::Config;
+    use rmps::decode::Error;
+    use rmps::encode::{EnumRepr, StructRepr, VariantId};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Cat,
+        Dog { barks: bool },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Pet {
+        name: String,
+        kind: Kind,
+        weight: f64,
+        age: u8,
+        addr: IpAddr,
+    }
+
+    let config = Config::default()
+        .with_struct_repr(StructRepr::Map)
+        .with_variant_id(VariantId::Name)
+        .with_enum_repr(EnumRepr::Array)
+        .with_bare_unit_variants(true)
+        .with_human_readable(false)
+        .with_compact_ints(false)
+        .with_compact_floats(true)
+        .with_limits(DecodeLimits { max_data_len: 16, ..DecodeLimits::default() });
+
+    let val = vec![
+        Pet {
+            name: "Tom".into(),
+            kind: Kind::Cat,
+            weight: 4.5,
+            age: 3,
+            addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+        },
+        Pet {
+            name: "Rex".into(),
+            kind: Kind::Dog { barks: true },
+            weight: 20.25,
+            age: 7,
+            addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        },
+    ];
+
+    let mut se = Serializer::with_config(Vec::new(), config);
+    val.serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    let mut de = Deserializer::with_config(&buf[..], config);
+    assert_eq!(val, Vec::<Pet>::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::from_read_ref(&buf);
+    de.set_config(config);
+    assert_eq!(val, Vec::<Pet>::deserialize(&mut de).unwrap());
+
+    // The limits of the configuration apply to the deserializer.
+    let buf = rmps::to_vec(&"a".repeat(17)).unwrap();
+    let mut de = Deserializer::with_config(&buf[..], config);
+    match String::deserialize(&mut de) {
+        Err(Error::LengthLimitExceeded(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn round_trip_ext_value() {
+    use rmps::ExtValue;
+
+    let cases = [
+        (1, vec![0xd4, 0x05]),
+        (2, vec![0xd5, 0x05]),
+        (4, vec![0xd6, 0x05]),
+        (8, vec![0xd7, 0x05]),
+        (16, vec![0xd8, 0x05]),
+        (0, vec![0xc7, 0x00, 0x05]),
+        (3, vec![0xc7, 0x03, 0x05]),
+        (255, vec![0xc7, 0xff, 0x05]),
+        (256, vec![0xc8, 0x01, 0x00, 0x05]),
+        (65536, vec![0xc9, 0x00, 0x01, 0x00, 0x00, 0x05]),
+    ];
+
+    for &(len, ref header) in cases.iter() {
+        let val = ExtValue(5, (0..len).map(|v| v as u8).collect());
+
+        let buf = rmps::to_vec(&val).unwrap();
+        assert_eq!(&header[..], &buf[..header.len()]);
+        assert_eq!(header.len() + len, buf.len());
+
+        assert_eq!(val, rmps::from_slice::<ExtValue>(&buf).unwrap());
+        assert_eq!(val, rmps::from_read::<_, ExtValue>(&buf[..]).unwrap());
+    }
+}
+
+#[test]
+fn round_trip_ext_value_in_struct() {
+    use rmps::ExtValue;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        id: u32,
+        stamp: ExtValue,
+        tags: Vec<ExtValue>,
+    }
+
+    let val = Event {
+        id: 42,
+        stamp: ExtValue(-1, vec![0x00, 0x00, 0x00, 0x00, 0x5a, 0x4f, 0x6b, 0x1c]),
+        tags: vec![ExtValue(1, vec![]), ExtValue(2, vec![0x01])],
+    };
+
+    let buf = rmps::to_vec_named(&val).unwrap();
+    assert_eq!(val, rmps::from_slice(&buf).unwrap());
+
+    let buf = rmps::to_vec(&val).unwrap();
+    assert_eq!(val, rmps::from_slice(&buf).unwrap());
+
+    // The timestamp is a plain fixext8, readable by anyone.
+    let mut rd = &buf[2..];
+    let meta = rmp::decode::read_ext_meta(&mut rd).unwrap();
+    assert_eq!((-1, 8), (meta.typeid, meta.size));
+}
+
+#[test]
+fn round_trip_ext_value_untagged() {
+    use rmps::ExtValue;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Field {
+        Int(u64),
+        Ext(ExtValue),
+    }
+
+    let val = vec![Field::Int(1), Field::Ext(ExtValue(9, vec![0x01, 0x02]))];
+
+    let buf = rmps::to_vec(&val).unwrap();
+    assert_eq!(val, rmps::from_slice::<Vec<Field>>(&buf).unwrap());
+
+    // A `fixext16`, which is buffered like any other extension.
+    let val = vec![Field::Ext(ExtValue(125, vec![0; 16])), Field::Int(2)];
+
+    let buf = rmps::to_vec(&val).unwrap();
+    assert_eq!(val, rmps::from_slice::<Vec<Field>>(&buf).unwrap());
+    assert_eq!(val, rmps::from_read::<_, Vec<Field>>(&buf[..]).unwrap());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn round_trip_bytes_mut() {
+    use bytes::BytesMut;
+    use rmps::encode::{write_bytes_mut, write_bytes_mut_named};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Dog {
+        name: String,
+        age: u16,
+    }
+
+    let dog = Dog {
+        name: "Frankie".into(),
+        age: 42,
+    };
+
+    let mut buf = BytesMut::new();
+    write_bytes_mut(&mut buf, &dog).unwrap();
+    let compact = buf.split().freeze();
+    write_bytes_mut_named(&mut buf, &dog).unwrap();
+    let named = buf.split().freeze();
+
+    assert_eq!(rmps::to_vec(&dog).unwrap(), &compact[..]);
+    assert_eq!(rmps::to_vec_named(&dog).unwrap(), &named[..]);
+    assert_eq!(dog, rmps::from_slice::<Dog>(&named).unwrap());
+    assert!(buf.is_empty());
+}
@@ -50,7 +50,8 @@ impl From<ValueReadError> for Error {
         match err {
             ValueReadError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
             ValueReadError::InvalidDataRead(err) => Error::InvalidDataRead(err),
-            ValueReadError::TypeMismatch(marker) => Error::TypeMismatch(marker),
+            ValueReadError::TypeMismatch(marker) |
+            ValueReadError::NonCanonical(marker) => Error::TypeMismatch(marker),
         }
     }
 }
@@ -60,7 +61,8 @@ impl From<NumValueReadError> for Error {
         match err {
             NumValueReadError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
             NumValueReadError::InvalidDataRead(err) => Error::InvalidDataRead(err),
-            NumValueReadError::TypeMismatch(marker) => Error::TypeMismatch(marker),
+            NumValueReadError::TypeMismatch(marker) |
+            NumValueReadError::NonCanonical(marker) => Error::TypeMismatch(marker),
             NumValueReadError::OutOfRange => Error::OutOfRange,
         }
     }
@@ -75,6 +77,7 @@ impl<'a> From<DecodeStringError<'a>> for Error {
                 Error::Uncategorized("InvalidDataRead".to_string())
             }
             DecodeStringError::TypeMismatch(..) => Error::Uncategorized("TypeMismatch".to_string()),
+            DecodeStringError::NonCanonical(..) => Error::Uncategorized("NonCanonical".to_string()),
             DecodeStringError::BufferSizeTooSmall(..) => {
                 Error::Uncategorized("BufferSizeTooSmall".to_string())
             }
@@ -0,0 +1,537 @@
+//! Asynchronous counterparts of the decoding functions, reading from `futures_io::AsyncRead`.
+//!
+//! The functions have the same names, arguments and errors as their blocking versions, but return
+//! futures, which never block the executor thread while waiting for data. Each future reads exactly
+//! the same bytes the blocking function would and keeps the partially read data between polls, so
+//! the reader may deliver the input in chunks of any size.
+//!
+//! Interrupted reads are retried, while reaching the end of the stream in the middle of a value is
+//! reported as an `UnexpectedEof` error.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate futures_executor;
+//! extern crate rmp;
+//!
+//! use futures_executor::block_on;
+//! use rmp::decode::asynchronous::{read_array_len, read_int};
+//!
+//! # fn main() {
+//! let buf = [0x92, 0x2a, 0xcd, 0x01, 0x2c];
+//! let mut rd = &buf[..];
+//!
+//! assert_eq!(2, block_on(read_array_len(&mut rd)).unwrap());
+//! assert_eq!(42, block_on(read_int::<u8, _>(&mut rd)).unwrap());
+//! assert_eq!(300, block_on(read_int::<u16, _>(&mut rd)).unwrap());
+//! # }
+//! ```
+
+use std::cmp;
+use std::io;
+use std::pin::Pin;
+use std::str::from_utf8;
+use std::future::Future;
+use std::task::{Context, Poll};
+
+use futures_io::AsyncRead;
+use num_traits::cast::FromPrimitive;
+
+use {Family, Marker, Payload};
+use decode;
+use super::{DecodeLimits, DecodeStringError, ExtMeta, LimitError, Limiter, MarkerReadError,
+            NumValueReadError, ValueReadError};
+use super::read::unexpected_eof;
+
+// Payloads of values read by `read_value_bytes` are appended to the buffer in chunks of this size,
+// so that a huge length prefix can't make us allocate memory for data that never arrives.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Attempts to read a single byte from the given reader and to decode it as a MessagePack marker.
+pub fn read_marker<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<Marker, MarkerReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadMarker { rd: rd }
+}
+
+/// Attempts to read a single byte from the given reader and to decode it as a nil value.
+///
+/// See `decode::read_nil` for details.
+pub fn read_nil<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<(), ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, |marker| marker == Marker::Null, |rd| decode::read_nil(rd))
+}
+
+/// Attempts to read a single byte from the given reader and to decode it as a boolean value.
+///
+/// See `decode::read_bool` for details.
+pub fn read_bool<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<bool, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, |marker| marker == Marker::True || marker == Marker::False,
+                  |rd| decode::read_bool(rd))
+}
+
+/// Attempts to read up to 9 bytes from the given reader and to decode them as integral `T` value.
+///
+/// See `decode::read_int` for details.
+pub fn read_int<'a, T, R>(rd: &'a mut R) -> impl Future<Output = Result<T, NumValueReadError>> + 'a
+    where T: FromPrimitive + 'a,
+          R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, is_int, |rd| decode::read_int(rd))
+}
+
+/// Attempts to read exactly 5 bytes from the given reader and to decode them as `f32` value.
+///
+/// See `decode::read_f32` for details.
+pub fn read_f32<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<f32, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, |marker| marker == Marker::F32, |rd| decode::read_f32(rd))
+}
+
+/// Attempts to read exactly 9 bytes from the given reader and to decode them as `f64` value.
+///
+/// See `decode::read_f64` for details.
+pub fn read_f64<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<f64, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, |marker| marker == Marker::F64, |rd| decode::read_f64(rd))
+}
+
+/// Attempts to read up to 5 bytes from the given reader and to decode them as a string length.
+///
+/// See `decode::read_str_len` for details.
+pub fn read_str_len<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<u32, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, is_str, |rd| decode::read_str_len(rd))
+}
+
+/// Attempts to read up to 5 bytes from the given reader and to decode them as a binary length.
+///
+/// See `decode::read_bin_len` for details.
+pub fn read_bin_len<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<u32, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, |marker| match marker {
+        Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => true,
+        _ => false,
+    }, |rd| decode::read_bin_len(rd))
+}
+
+/// Attempts to read up to 5 bytes from the given reader and to decode them as an array length.
+///
+/// See `decode::read_array_len` for details.
+pub fn read_array_len<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<u32, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, |marker| match marker {
+        Marker::FixArray(..) | Marker::Array16 | Marker::Array32 => true,
+        _ => false,
+    }, |rd| decode::read_array_len(rd))
+}
+
+/// Attempts to read up to 5 bytes from the given reader and to decode them as a map length.
+///
+/// See `decode::read_map_len` for details.
+pub fn read_map_len<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<u32, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, |marker| match marker {
+        Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => true,
+        _ => false,
+    }, |rd| decode::read_map_len(rd))
+}
+
+/// Attempts to read up to 6 bytes from the given reader and to decode them as an extension
+/// header, i.e. its data length and type id.
+///
+/// See `decode::read_ext_meta` for details.
+pub fn read_ext_meta<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<ExtMeta, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadHead::new(rd, |marker| match marker {
+        Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 |
+        Marker::FixExt16 | Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => true,
+        _ => false,
+    }, |rd| decode::read_ext_meta(rd))
+}
+
+/// Attempts to read a string from the given reader and copy it to the buffer provided.
+///
+/// See `decode::read_str` for details.
+pub fn read_str<'a, 'r, R>(rd: &'a mut R, buf: &'r mut [u8]) ->
+    impl Future<Output = Result<&'r str, DecodeStringError<'r>>> + 'a
+    where R: AsyncRead + Unpin + ?Sized,
+          'r: 'a
+{
+    ReadStr {
+        head: ReadHead::new(rd, is_str, |rd| decode::read_str_len(rd)),
+        buf: Some(buf),
+        len: None,
+        pos: 0,
+    }
+}
+
+/// Attempts to read a single complete MessagePack value from the given reader, returning its
+/// encoded bytes.
+///
+/// Nested arrays and maps are read entirely, so the result can be decoded using the blocking
+/// functions or any higher-level library without waiting for more data. Payloads are read in
+/// chunks, therefore memory is allocated only for the data that has actually been received.
+///
+/// The value is checked against `DecodeLimits::default()`, use `read_value_bytes_with_limits` to
+/// receive untrusted input.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either a marker or the
+/// data, and `ValueReadError::TypeMismatch` when encountering the reserved `0xc1` marker.
+pub fn read_value_bytes<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<Vec<u8>, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    read_value_bytes_with_limits(rd, &DecodeLimits::default())
+}
+
+/// Attempts to read a single complete MessagePack value from the given reader, returning its
+/// encoded bytes, unless the value exceeds the given limits.
+///
+/// Each header is checked by a `Limiter` as soon as it arrives, so a value is rejected before its
+/// declared data is read or any memory is allocated for it.
+///
+/// # Errors
+///
+/// In addition to the errors described in `read_value_bytes`, this function will return
+/// `ValueReadError::InvalidDataRead` with an `ErrorKind::InvalidData` error wrapping `LimitError`
+/// if the value exceeds the limits.
+///
+/// # Examples
+///
+/// ```
+/// extern crate futures_executor;
+/// extern crate rmp;
+///
+/// use futures_executor::block_on;
+/// use rmp::decode::DecodeLimits;
+/// use rmp::decode::asynchronous::read_value_bytes_with_limits;
+///
+/// # fn main() {
+/// let limits = DecodeLimits { max_depth: 1, ..DecodeLimits::default() };
+///
+/// let buf = [0x91, 0x2a];
+/// let value = block_on(read_value_bytes_with_limits(&mut &buf[..], &limits)).unwrap();
+/// assert_eq!(&buf[..], &value[..]);
+///
+/// let buf = [0x91, 0x91, 0x2a];
+/// assert!(block_on(read_value_bytes_with_limits(&mut &buf[..], &limits)).is_err());
+/// # }
+/// ```
+pub fn read_value_bytes_with_limits<'a, R>(rd: &'a mut R, limits: &DecodeLimits) ->
+    impl Future<Output = Result<Vec<u8>, ValueReadError>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    ReadValueBytes {
+        rd: rd,
+        buf: Vec::new(),
+        pending: vec![1],
+        limiter: Limiter::new(*limits),
+        stage: Stage::Marker,
+    }
+}
+
+fn is_int(marker: Marker) -> bool {
+    match marker {
+        Marker::FixPos(..) | Marker::FixNeg(..) |
+        Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64 |
+        Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64 => true,
+        _ => false,
+    }
+}
+
+fn is_str(marker: Marker) -> bool {
+    match marker {
+        Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => true,
+        _ => false,
+    }
+}
+
+// Returns the number of bytes following the given marker, that are required to decode either a
+// scalar value or the header of a string, binary, container or extension, including its type id.
+fn head_len(marker: Marker) -> usize {
+    match (marker.family(), marker.payload(&[])) {
+        (Family::Nil, Some(Payload::Bytes(len))) |
+        (Family::Bool, Some(Payload::Bytes(len))) |
+        (Family::Int, Some(Payload::Bytes(len))) |
+        (Family::Float, Some(Payload::Bytes(len))) => len as usize,
+        _ => marker.header_len(),
+    }
+}
+
+// Reads some bytes into the buffer, retrying interrupted reads and treating the end of the stream
+// as an error.
+fn poll_read_some<R>(rd: &mut R, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>>
+    where R: AsyncRead + Unpin + ?Sized
+{
+    loop {
+        match Pin::new(&mut *rd).poll_read(cx, buf) {
+            Poll::Ready(Ok(0)) => return Poll::Ready(Err(unexpected_eof())),
+            Poll::Ready(Err(ref err)) if err.kind() == io::ErrorKind::Interrupted => {}
+            res => return res,
+        }
+    }
+}
+
+struct ReadMarker<'a, R: ?Sized + 'a> {
+    rd: &'a mut R,
+}
+
+impl<'a, R: AsyncRead + Unpin + ?Sized> Future for ReadMarker<'a, R> {
+    type Output = Result<Marker, MarkerReadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut buf = [0];
+        match poll_read_some(self.get_mut().rd, cx, &mut buf) {
+            Poll::Ready(Ok(..)) => Poll::Ready(Ok(Marker::from_u8(buf[0]))),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(MarkerReadError(err))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+// Reads a marker followed by at most 8 bytes of data into a buffer on the stack and decodes them
+// using the blocking function once complete.
+struct ReadHead<'a, R: ?Sized + 'a, T, E> {
+    rd: &'a mut R,
+    buf: [u8; 9],
+    pos: usize,
+    len: usize,
+    accepts: fn(Marker) -> bool,
+    decode: fn(&mut &[u8]) -> Result<T, E>,
+}
+
+impl<'a, R: ?Sized + 'a, T, E> ReadHead<'a, R, T, E> {
+    fn new(rd: &'a mut R, accepts: fn(Marker) -> bool, decode: fn(&mut &[u8]) -> Result<T, E>) -> Self {
+        ReadHead {
+            rd: rd,
+            buf: [0; 9],
+            pos: 0,
+            len: 1,
+            accepts: accepts,
+            decode: decode,
+        }
+    }
+}
+
+impl<'a, R, T, E> Future for ReadHead<'a, R, T, E>
+    where R: AsyncRead + Unpin + ?Sized,
+          E: From<ValueReadError>
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        while this.pos < this.len {
+            match poll_read_some(this.rd, cx, &mut this.buf[this.pos..this.len]) {
+                Poll::Ready(Ok(n)) => this.pos += n,
+                Poll::Ready(Err(err)) if this.pos == 0 => {
+                    return Poll::Ready(Err(ValueReadError::InvalidMarkerRead(err).into()));
+                }
+                Poll::Ready(Err(err)) => {
+                    return Poll::Ready(Err(ValueReadError::InvalidDataRead(err).into()));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+
+            if this.len == 1 {
+                // Don't read any further if the marker is not the one expected, just like the
+                // blocking functions do.
+                let marker = Marker::from_u8(this.buf[0]);
+                if !(this.accepts)(marker) {
+                    return Poll::Ready(Err(ValueReadError::TypeMismatch(marker).into()));
+                }
+                this.len += head_len(marker);
+            }
+        }
+
+        Poll::Ready((this.decode)(&mut &this.buf[..this.len]))
+    }
+}
+
+struct ReadStr<'a, 'r, R: ?Sized + 'a> {
+    head: ReadHead<'a, R, u32, ValueReadError>,
+    buf: Option<&'r mut [u8]>,
+    len: Option<usize>,
+    pos: usize,
+}
+
+impl<'a, 'r, R: AsyncRead + Unpin + ?Sized> Future for ReadStr<'a, 'r, R> {
+    type Output = Result<&'r str, DecodeStringError<'r>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let len = match this.len {
+            Some(len) => len,
+            None => {
+                let len = match Pin::new(&mut this.head).poll(cx) {
+                    Poll::Ready(Ok(len)) => len,
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(err.into())),
+                    Poll::Pending => return Poll::Pending,
+                };
+
+                let buf_len = this.buf.as_ref().map_or(0, |buf| buf.len());
+                if buf_len < len as usize {
+                    return Poll::Ready(Err(DecodeStringError::BufferSizeTooSmall(len)));
+                }
+
+                this.len = Some(len as usize);
+                len as usize
+            }
+        };
+
+        while this.pos < len {
+            let buf = this.buf.as_mut().expect("future polled after completion");
+            match poll_read_some(this.head.rd, cx, &mut buf[this.pos..len]) {
+                Poll::Ready(Ok(n)) => this.pos += n,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(DecodeStringError::InvalidDataRead(err))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let buf: &'r [u8] = this.buf.take().expect("future polled after completion");
+        let buf = &buf[..len];
+        match from_utf8(buf) {
+            Ok(decoded) => Poll::Ready(Ok(decoded)),
+            Err(err) => Poll::Ready(Err(DecodeStringError::InvalidUtf8(buf, err))),
+        }
+    }
+}
+
+enum Stage {
+    Marker,
+    // The marker and the number of bytes that follow it.
+    Head(Marker, usize),
+    // The number of payload bytes left.
+    Payload(u64),
+}
+
+struct ReadValueBytes<'a, R: ?Sized + 'a> {
+    rd: &'a mut R,
+    buf: Vec<u8>,
+    // The number of values left to read at each level of nesting, the outermost one being the value
+    // itself.
+    pending: Vec<u64>,
+    limiter: Limiter,
+    stage: Stage,
+}
+
+impl<'a, R: AsyncRead + Unpin + ?Sized> ReadValueBytes<'a, R> {
+    // Appends up to `len` bytes from the reader to the buffer, returning how many have been read.
+    fn poll_append(&mut self, cx: &mut Context, len: usize) -> Poll<io::Result<usize>> {
+        let start = self.buf.len();
+        self.buf.resize(start + len, 0);
+        let res = poll_read_some(self.rd, cx, &mut self.buf[start..]);
+        let nread = match res {
+            Poll::Ready(Ok(n)) => n,
+            _ => 0,
+        };
+        self.buf.truncate(start + nread);
+        res
+    }
+}
+
+impl<'a, R: AsyncRead + Unpin + ?Sized> Future for ReadValueBytes<'a, R> {
+    type Output = Result<Vec<u8>, ValueReadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            match this.stage {
+                Stage::Marker if this.pending.last() == Some(&0) => {
+                    this.pending.pop();
+                    if this.pending.is_empty() {
+                        return Poll::Ready(Ok(::std::mem::replace(&mut this.buf, Vec::new())));
+                    }
+                    this.limiter.leave();
+                }
+                Stage::Marker => {
+                    match this.poll_append(cx, 1) {
+                        Poll::Ready(Ok(..)) => {}
+                        Poll::Ready(Err(err)) => {
+                            return Poll::Ready(Err(ValueReadError::InvalidMarkerRead(err)));
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+
+                    let marker = Marker::from_u8(this.buf[this.buf.len() - 1]);
+                    if marker == Marker::Reserved {
+                        return Poll::Ready(Err(ValueReadError::TypeMismatch(marker)));
+                    }
+                    if let Err(err) = this.limiter.check_marker(marker) {
+                        return Poll::Ready(Err(limit_error(err)));
+                    }
+
+                    if let Some(pending) = this.pending.last_mut() {
+                        *pending -= 1;
+                    }
+                    this.stage = Stage::Head(marker, marker.header_len());
+                }
+                Stage::Head(marker, 0) => {
+                    let head = &this.buf[this.buf.len() - marker.header_len()..];
+                    let payload = match marker.payload(head) {
+                        Some(Payload::Bytes(len)) => {
+                            if let Err(err) = this.limiter.check_len(marker, len) {
+                                return Poll::Ready(Err(limit_error(err)));
+                            }
+                            len as u64
+                        }
+                        Some(Payload::Values(count)) => {
+                            // Maps are limited by the number of key-value pairs.
+                            let len = match marker.family() {
+                                Family::Map => count / 2,
+                                _ => count,
+                            };
+                            let res = this.limiter.check_len(marker, len as u32)
+                                .and_then(|()| this.limiter.enter());
+                            if let Err(err) = res {
+                                return Poll::Ready(Err(limit_error(err)));
+                            }
+                            this.pending.push(count);
+                            0
+                        }
+                        None => 0,
+                    };
+                    this.stage = Stage::Payload(payload);
+                }
+                Stage::Head(marker, left) => {
+                    match this.poll_append(cx, left) {
+                        Poll::Ready(Ok(n)) => this.stage = Stage::Head(marker, left - n),
+                        Poll::Ready(Err(err)) => {
+                            return Poll::Ready(Err(ValueReadError::InvalidDataRead(err)));
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                Stage::Payload(0) => this.stage = Stage::Marker,
+                Stage::Payload(left) => {
+                    match this.poll_append(cx, cmp::min(left, CHUNK_SIZE as u64) as usize) {
+                        Poll::Ready(Ok(n)) => this.stage = Stage::Payload(left - n as u64),
+                        Poll::Ready(Err(err)) => {
+                            return Poll::Ready(Err(ValueReadError::InvalidDataRead(err)));
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn limit_error(err: LimitError) -> ValueReadError {
+    ValueReadError::InvalidDataRead(io::Error::new(io::ErrorKind::InvalidData, err))
+}
@@ -1,7 +1,10 @@
-use std::io::Read;
+#[cfg(feature = "std")]
+use std::error;
+use core::fmt::{self, Display, Formatter};
 
-use Marker;
-use super::{read_marker, read_data_i8, read_data_u8, read_data_u16, read_data_u32, ValueReadError};
+use {Marker, Timestamp};
+use super::{read_marker, read_data_i8, read_data_u8, read_data_u16, read_data_u32, read_data_u64,
+            read_data_i64, RmpRead, Error, MarkerReadError, ValueReadError};
 
 /// Attempts to read exactly 3 bytes from the given reader and interpret them as a fixext1 type
 /// with data attached.
@@ -20,7 +23,7 @@ use super::{read_marker, read_data_i8, read_data_u8, read_data_u16, read_data_u3
 ///
 /// This function will silently retry on every EINTR received from the underlying `Read` until
 /// successful read.
-pub fn read_fixext1<R: Read>(rd: &mut R) -> Result<(i8, u8), ValueReadError> {
+pub fn read_fixext1<R: RmpRead>(rd: &mut R) -> Result<(i8, u8), ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::FixExt1 => {
             let ty = try!(read_data_i8(rd));
@@ -44,7 +47,7 @@ pub fn read_fixext1<R: Read>(rd: &mut R) -> Result<(i8, u8), ValueReadError> {
 ///
 /// This function will return `ValueReadError` on any I/O error while reading either the marker or
 /// the data.
-pub fn read_fixext2<R: Read>(rd: &mut R) -> Result<(i8, [u8; 2]), ValueReadError> {
+pub fn read_fixext2<R: RmpRead>(rd: &mut R) -> Result<(i8, [u8; 2]), ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::FixExt2 => {
             let mut buf = [0; 2];
@@ -67,7 +70,7 @@ pub fn read_fixext2<R: Read>(rd: &mut R) -> Result<(i8, [u8; 2]), ValueReadError
 ///
 /// This function will return `ValueReadError` on any I/O error while reading either the marker or
 /// the data.
-pub fn read_fixext4<R: Read>(rd: &mut R) -> Result<(i8, [u8; 4]), ValueReadError> {
+pub fn read_fixext4<R: RmpRead>(rd: &mut R) -> Result<(i8, [u8; 4]), ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::FixExt4 => {
             let mut buf = [0; 4];
@@ -90,7 +93,7 @@ pub fn read_fixext4<R: Read>(rd: &mut R) -> Result<(i8, [u8; 4]), ValueReadError
 ///
 /// This function will return `ValueReadError` on any I/O error while reading either the marker or
 /// the data.
-pub fn read_fixext8<R: Read>(rd: &mut R) -> Result<(i8, [u8; 8]), ValueReadError> {
+pub fn read_fixext8<R: RmpRead>(rd: &mut R) -> Result<(i8, [u8; 8]), ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::FixExt8 => {
             let mut buf = [0; 8];
@@ -113,7 +116,7 @@ pub fn read_fixext8<R: Read>(rd: &mut R) -> Result<(i8, [u8; 8]), ValueReadError
 ///
 /// This function will return `ValueReadError` on any I/O error while reading either the marker or
 /// the data.
-pub fn read_fixext16<R: Read>(rd: &mut R) -> Result<(i8, [u8; 16]), ValueReadError> {
+pub fn read_fixext16<R: RmpRead>(rd: &mut R) -> Result<(i8, [u8; 16]), ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::FixExt16 => {
             let mut buf = [0; 16];
@@ -123,9 +126,9 @@ pub fn read_fixext16<R: Read>(rd: &mut R) -> Result<(i8, [u8; 16]), ValueReadErr
     }
 }
 
-fn read_fixext_data<R: Read>(rd: &mut R, buf: &mut [u8]) -> Result<i8, ValueReadError> {
+fn read_fixext_data<R: RmpRead>(rd: &mut R, buf: &mut [u8]) -> Result<i8, ValueReadError> {
     let id = try!(read_data_i8(rd));
-    match rd.read_exact(buf) {
+    match rd.read_exact_buf(buf) {
         Ok(()) => Ok(id),
         Err(err) => Err(ValueReadError::InvalidDataRead(From::from(err))),
     }
@@ -150,7 +153,59 @@ pub struct ExtMeta {
     pub size: u32,
 }
 
-pub fn read_ext_meta<R: Read>(rd: &mut R) -> Result<ExtMeta, ValueReadError> {
+impl ExtMeta {
+    /// Returns the kind of the extension type.
+    pub fn kind(&self) -> ExtTypeKind {
+        ext_type_kind(self.typeid)
+    }
+
+    /// Returns `true` if the extension type is an application-specific one.
+    pub fn is_application(&self) -> bool {
+        self.kind() == ExtTypeKind::Application
+    }
+
+    /// Returns `true` if the extension type is reserved by the MessagePack specification, whether
+    /// its meaning is defined or not.
+    pub fn is_reserved(&self) -> bool {
+        !self.is_application()
+    }
+}
+
+/// Extension type id kinds according to the MessagePack specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtTypeKind {
+    /// Application-specific type in `[0; 127]` range.
+    Application,
+    /// Reserved type whose meaning is defined by the MessagePack specification, like `-1` for
+    /// timestamps.
+    Predefined,
+    /// Reserved type with no meaning defined yet.
+    Reserved,
+}
+
+/// Classifies the given extension type id, telling application-specific types apart from the
+/// ones reserved by the MessagePack specification.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{ext_type_kind, ExtTypeKind};
+///
+/// assert_eq!(ExtTypeKind::Application, ext_type_kind(42));
+/// assert_eq!(ExtTypeKind::Predefined, ext_type_kind(-1));
+/// assert_eq!(ExtTypeKind::Reserved, ext_type_kind(-42));
+/// ```
+pub fn ext_type_kind(ty: i8) -> ExtTypeKind {
+    if ty >= 0 {
+        ExtTypeKind::Application
+    } else if ty == Timestamp::EXT_TYPE {
+        ExtTypeKind::Predefined
+    } else {
+        ExtTypeKind::Reserved
+    }
+}
+
+pub fn read_ext_meta<R: RmpRead>(rd: &mut R) -> Result<ExtMeta, ValueReadError> {
     let size = match read_marker(rd)? {
         Marker::FixExt1 => 1,
         Marker::FixExt2 => 2,
@@ -171,3 +226,143 @@ pub fn read_ext_meta<R: Read>(rd: &mut R) -> Result<ExtMeta, ValueReadError> {
 
     Ok(meta)
 }
+
+/// An error which can occur when attempting to read a MessagePack timestamp from the reader.
+#[derive(Debug)]
+pub enum TimestampReadError {
+    /// Failed to read the marker.
+    InvalidMarkerRead(Error),
+    /// Failed to read the data.
+    InvalidDataRead(Error),
+    /// The type decoded isn't an extension.
+    TypeMismatch(Marker),
+    /// The extension header isn't encoded in its shortest form.
+    NonCanonical(Marker),
+    /// The extension type id isn't the timestamp one.
+    ExtTypeMismatch(i8),
+    /// The extension data size doesn't match any of the timestamp formats.
+    InvalidDataLength(u32),
+    /// The nanoseconds part is not less than a second.
+    InvalidNanoseconds(u32),
+}
+
+#[cfg(feature = "std")]
+impl error::Error for TimestampReadError {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            TimestampReadError::InvalidMarkerRead(ref err) |
+            TimestampReadError::InvalidDataRead(ref err) => Some(err),
+            TimestampReadError::TypeMismatch(..) |
+            TimestampReadError::NonCanonical(..) |
+            TimestampReadError::ExtTypeMismatch(..) |
+            TimestampReadError::InvalidDataLength(..) |
+            TimestampReadError::InvalidNanoseconds(..) => None,
+        }
+    }
+}
+
+impl Display for TimestampReadError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            TimestampReadError::InvalidMarkerRead(..) => "failed to read MessagePack marker".fmt(f),
+            TimestampReadError::InvalidDataRead(..) => "failed to read MessagePack data".fmt(f),
+            TimestampReadError::TypeMismatch(..) => {
+                "the type decoded isn't match with the expected one".fmt(f)
+            }
+            TimestampReadError::NonCanonical(marker) => {
+                write!(f, "the extension header isn't encoded in its shortest form, found {:?}",
+                       marker)
+            }
+            TimestampReadError::ExtTypeMismatch(ty) => {
+                write!(f, "the extension type {} isn't a timestamp", ty)
+            }
+            TimestampReadError::InvalidDataLength(len) => {
+                write!(f, "invalid timestamp data length: {}", len)
+            }
+            TimestampReadError::InvalidNanoseconds(nsecs) => {
+                write!(f, "timestamp nanoseconds out of range: {}", nsecs)
+            }
+        }
+    }
+}
+
+impl From<MarkerReadError> for TimestampReadError {
+    fn from(err: MarkerReadError) -> TimestampReadError {
+        TimestampReadError::InvalidMarkerRead(err.0)
+    }
+}
+
+impl From<ValueReadError> for TimestampReadError {
+    fn from(err: ValueReadError) -> TimestampReadError {
+        match err {
+            ValueReadError::InvalidMarkerRead(err) => TimestampReadError::InvalidMarkerRead(err),
+            ValueReadError::InvalidDataRead(err) => TimestampReadError::InvalidDataRead(err),
+            ValueReadError::TypeMismatch(marker) => TimestampReadError::TypeMismatch(marker),
+            ValueReadError::NonCanonical(marker) => TimestampReadError::NonCanonical(marker),
+        }
+    }
+}
+
+/// Attempts to read up to 15 bytes from the given reader and to decode them as a MessagePack
+/// timestamp.
+///
+/// All three timestamp formats are supported: timestamp 32, timestamp 64 and timestamp 96.
+///
+/// # Errors
+///
+/// This function will return `TimestampReadError` on any I/O error while reading either the
+/// marker or the data.
+///
+/// It also returns `TimestampReadError::TypeMismatch` if the actual type is not an extension,
+/// `TimestampReadError::ExtTypeMismatch` if the extension type id is not `-1` and
+/// `TimestampReadError::InvalidNanoseconds` if the nanoseconds part is not less than a second.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Timestamp;
+/// use rmp::decode::read_timestamp;
+///
+/// let buf = [0xd7, 0xff, 0xa1, 0xdc, 0xd7, 0xc8, 0x5a, 0x4a, 0xf6, 0xa5];
+///
+/// assert_eq!(Timestamp::new(1514862245, 678901234).unwrap(),
+///            read_timestamp(&mut &buf[..]).unwrap());
+/// ```
+pub fn read_timestamp<R: RmpRead>(rd: &mut R) -> Result<Timestamp, TimestampReadError> {
+    let meta = read_ext_meta(rd)?;
+
+    if meta.typeid != Timestamp::EXT_TYPE {
+        return Err(TimestampReadError::ExtTypeMismatch(meta.typeid));
+    }
+
+    read_timestamp_data(rd, meta.size)
+}
+
+/// Attempts to read the data part of a timestamp extension, which is `len` bytes length, from the
+/// given reader.
+///
+/// This function is useful when the extension metadata has already been read, for example using
+/// `read_ext_meta`, or when the timestamp is stored as a raw extension payload.
+///
+/// # Errors
+///
+/// This function will return `TimestampReadError` on any I/O error while reading the data.
+///
+/// It also returns `TimestampReadError::InvalidDataLength` if `len` is not one of 4, 8 or 12 and
+/// `TimestampReadError::InvalidNanoseconds` if the nanoseconds part is not less than a second.
+pub fn read_timestamp_data<R: RmpRead>(rd: &mut R, len: u32) -> Result<Timestamp, TimestampReadError> {
+    let (secs, nsecs) = match len {
+        4 => (read_data_u32(rd)? as i64, 0),
+        8 => {
+            let data = read_data_u64(rd)?;
+            ((data & 0x3_ffff_ffff) as i64, (data >> 34) as u32)
+        }
+        12 => {
+            let nsecs = read_data_u32(rd)?;
+            (read_data_i64(rd)?, nsecs)
+        }
+        len => return Err(TimestampReadError::InvalidDataLength(len)),
+    };
+
+    Timestamp::new(secs, nsecs).ok_or(TimestampReadError::InvalidNanoseconds(nsecs))
+}
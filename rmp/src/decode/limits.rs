@@ -0,0 +1,244 @@
+#[cfg(feature = "std")]
+use std::error;
+use core::fmt::{self, Display, Formatter};
+
+use {Family, Marker, Payload};
+
+/// Limits applied when decoding untrusted input.
+///
+/// Declared lengths come straight from the wire, so without limits a message of a few bytes can
+/// claim a string of 4 GiB, or an array of billions of elements. These limits are checked by
+/// `validate`, `Unpacker` and `Limiter`, and the decoders built on top of the latter.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::DecodeLimits;
+///
+/// let limits = DecodeLimits { max_depth: 16, ..DecodeLimits::default() };
+///
+/// assert_eq!(16, limits.max_depth);
+/// assert_eq!(u32::MAX, limits.max_data_len);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Maximum number of nested arrays and maps. A single scalar value has zero depth, while an
+    /// array of scalars has depth of one.
+    pub max_depth: usize,
+    /// Maximum declared length of strings, binaries and extensions in bytes.
+    pub max_data_len: u32,
+    /// Maximum declared length of arrays in elements and of maps in key-value pairs.
+    pub max_container_len: u32,
+    /// Maximum size of a whole message in bytes.
+    pub max_total_len: u64,
+}
+
+impl Default for DecodeLimits {
+    /// Returns limits with the depth of 1024, which matches the default of `rmp-serde`, and no
+    /// length restriction other than the format one.
+    fn default() -> DecodeLimits {
+        DecodeLimits {
+            max_depth: 1024,
+            max_data_len: u32::MAX,
+            max_container_len: u32::MAX,
+            max_total_len: u64::MAX,
+        }
+    }
+}
+
+impl DecodeLimits {
+    /// Returns whether the given declared length of a value with the given marker is within the
+    /// limits.
+    ///
+    /// Scalars have no declared length, so they are always accepted.
+    pub fn allows_len(&self, marker: Marker, len: u32) -> bool {
+        match marker.family() {
+            Family::Str | Family::Bin | Family::Ext => len <= self.max_data_len,
+            Family::Array | Family::Map => len <= self.max_container_len,
+            _ => true,
+        }
+    }
+}
+
+/// An error returned by `Limiter` when the data being decoded exceeds the `DecodeLimits`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LimitError {
+    /// Arrays and maps are nested deeper than allowed.
+    DepthLimitExceeded,
+    /// A string, binary, extension, array or map with the given marker is longer than allowed.
+    LengthLimitExceeded(Marker),
+    /// The message is larger than allowed.
+    TotalLimitExceeded,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for LimitError {}
+
+impl Display for LimitError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            LimitError::DepthLimitExceeded => "depth limit exceeded".fmt(f),
+            LimitError::LengthLimitExceeded(marker) => {
+                write!(f, "length limit exceeded by {:?}", marker)
+            }
+            LimitError::TotalLimitExceeded => "total length limit exceeded".fmt(f),
+        }
+    }
+}
+
+/// Checks a single message against the `DecodeLimits` while it is being decoded.
+///
+/// Unlike `validate`, which needs the whole message in memory, a limiter accompanies a decoder
+/// reading from a stream. The decoder reports each marker it reads to `check_marker`, each length
+/// prefix to `check_len`, and each array or map it descends into to `enter` and `leave`. A length
+/// is reported before anything is allocated for it, so a hostile length is rejected before it
+/// costs anything.
+///
+/// The size of the message is accounted from what the markers and lengths imply, so a message is
+/// rejected as soon as it claims more data than allowed, not after reading it.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Marker;
+/// use rmp::decode::{read_marker, read_data_u32, DecodeLimits, LimitError, Limiter};
+///
+/// let limits = DecodeLimits { max_data_len: 1024, ..DecodeLimits::default() };
+/// let mut limiter = Limiter::new(limits);
+///
+/// // A 5-byte message claiming a 4 GiB binary.
+/// let buf = [0xc6, 0xff, 0xff, 0xff, 0xff];
+/// let mut rd = &buf[..];
+///
+/// let marker = read_marker(&mut rd).unwrap();
+/// limiter.check_marker(marker).unwrap();
+///
+/// let len = read_data_u32(&mut rd).unwrap();
+/// assert_eq!(Err(LimitError::LengthLimitExceeded(Marker::Bin32)), limiter.check_len(marker, len));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Limiter {
+    limits: DecodeLimits,
+    depth: usize,
+    total: u64,
+}
+
+impl Limiter {
+    /// Constructs a new `Limiter` for a message, which is about to be decoded.
+    pub fn new(limits: DecodeLimits) -> Limiter {
+        Limiter {
+            limits: limits,
+            depth: 0,
+            total: 0,
+        }
+    }
+
+    /// Returns the limits this limiter checks against.
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    /// Returns the number of arrays and maps currently entered.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of bytes accounted so far.
+    pub fn total_len(&self) -> u64 {
+        self.total
+    }
+
+    /// Starts checking the next message from scratch, keeping the limits.
+    pub fn reset(&mut self) {
+        self.depth = 0;
+        self.total = 0;
+    }
+
+    /// Accounts for a marker just read, along with its header and, for values of fixed size, the
+    /// data.
+    ///
+    /// Values with the length encoded in the marker itself, like `FixStr` or `FixArray`, are
+    /// checked completely here.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LimitError` if the length encoded in the marker or the message size exceeds the
+    /// limits.
+    pub fn check_marker(&mut self, marker: Marker) -> Result<(), LimitError> {
+        // Length prefixes decode as zero, leaving only the fixed sizes.
+        let (len, data) = match marker.payload(&[0; 5]) {
+            Some(Payload::Bytes(len)) => (len, len),
+            Some(Payload::Values(count)) if marker.family() == Family::Map => ((count / 2) as u32, 0),
+            Some(Payload::Values(count)) => (count as u32, 0),
+            None => (0, 0),
+        };
+
+        if !self.limits.allows_len(marker, len) {
+            return Err(LimitError::LengthLimitExceeded(marker));
+        }
+
+        self.account(1 + marker.header_len() as u64 + data as u64)
+    }
+
+    /// Checks the length decoded from the length prefix of a string, binary, extension, array or
+    /// map, accounting for the data of the former three.
+    ///
+    /// For arrays the length is the number of elements, for maps the number of key-value pairs.
+    /// Markers without a length prefix are ignored, since `check_marker` has checked them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LimitError` if the length or the message size exceeds the limits.
+    pub fn check_len(&mut self, marker: Marker, len: u32) -> Result<(), LimitError> {
+        let has_prefix = match marker {
+            Marker::Str8 | Marker::Str16 | Marker::Str32 |
+            Marker::Bin8 | Marker::Bin16 | Marker::Bin32 |
+            Marker::Ext8 | Marker::Ext16 | Marker::Ext32 |
+            Marker::Array16 | Marker::Array32 |
+            Marker::Map16 | Marker::Map32 => true,
+            _ => false,
+        };
+        if !has_prefix {
+            return Ok(());
+        }
+
+        if !self.limits.allows_len(marker, len) {
+            return Err(LimitError::LengthLimitExceeded(marker));
+        }
+
+        match marker.family() {
+            Family::Str | Family::Bin | Family::Ext => self.account(len as u64),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enters an array or a map.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LimitError::DepthLimitExceeded` if the nesting becomes deeper than allowed, in
+    /// which case the depth is left unchanged.
+    pub fn enter(&mut self) -> Result<(), LimitError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(LimitError::DepthLimitExceeded);
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Leaves an array or a map previously entered.
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn account(&mut self, len: u64) -> Result<(), LimitError> {
+        self.total = self.total.saturating_add(len);
+
+        if self.total > self.limits.max_total_len {
+            Err(LimitError::TotalLimitExceeded)
+        } else {
+            Ok(())
+        }
+    }
+}
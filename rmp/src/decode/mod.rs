@@ -9,32 +9,65 @@
 //! non-blocking socket and it returns EWOULDBLOCK) be sure that you buffer the data externally
 //! to avoid data loss (using `BufRead` readers with manual consuming or some other way).
 
+mod read;
 mod sint;
 mod uint;
 mod dec;
+mod number;
 mod str;
 mod ext;
-
+mod typed;
+mod skip;
+mod token;
+mod validate;
+mod limits;
+mod unpack;
+mod offset;
+mod strict;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+
+pub use self::read::RmpRead;
+#[cfg(not(feature = "std"))]
+pub use self::read::BytesReadError;
 pub use self::sint::{read_nfix, read_i8, read_i16, read_i32, read_i64};
 pub use self::uint::{read_pfix, read_u8, read_u16, read_u32, read_u64};
 pub use self::dec::{read_f32, read_f64};
-pub use self::str::{read_str_len, read_str, read_str_from_slice, read_str_ref, DecodeStringError};
+pub use self::number::{read_number, read_float, Number, NumConversion};
+pub use self::str::{read_str_len, read_raw_len, read_str, read_str_from_slice, read_str_ref,
+                    read_str_lossy_from_slice, read_str_bytes, read_str_lossy, DecodeStringError,
+                    StrBytes};
 pub use self::ext::{read_fixext1, read_fixext2, read_fixext4, read_fixext8, read_fixext16,
-                    read_ext_meta, ExtMeta};
-
+                    read_ext_meta, read_timestamp, read_timestamp_data, ext_type_kind, ExtMeta,
+                    ExtTypeKind, TimestampReadError};
+pub use self::typed::{read_i128, read_u128, read_typed_array, read_typed_array_data, TypedReadError};
+pub use self::skip::{skip_value, skip_value_from_slice};
+pub use self::token::{Token, Tokenizer};
+pub use self::validate::{validate, validate_messages, ValidateError};
+pub use self::limits::{DecodeLimits, LimitError, Limiter};
+pub use self::unpack::Unpacker;
+pub use self::offset::{OffsetReader, DecodeError, DecodeErrorKind};
+pub use self::strict::{read_int_strict, read_f64_strict, read_str_len_strict, read_bin_len_strict,
+                       read_array_len_strict, read_map_len_strict, read_ext_meta_strict};
+
+#[cfg(feature = "std")]
 use std::error;
-use std::fmt::{self, Display, Formatter};
-use std::io::Read;
+use core::fmt::{self, Display, Formatter};
 
-use byteorder::{self, ReadBytesExt};
+use byteorder::{BigEndian, ByteOrder};
 
 use num_traits::cast::FromPrimitive;
 
 use Marker;
 
 /// An error that can occur when attempting to read bytes from the reader.
+#[cfg(feature = "std")]
 pub type Error = ::std::io::Error;
 
+/// An error that can occur when attempting to read bytes from the reader.
+#[cfg(not(feature = "std"))]
+pub type Error = BytesReadError;
+
 /// An error that can occur when attempting to read a MessagePack marker from the reader.
 #[derive(Debug)]
 pub struct MarkerReadError(pub Error);
@@ -48,8 +81,12 @@ pub enum ValueReadError {
     InvalidDataRead(Error),
     /// The type decoded isn't match with the expected one.
     TypeMismatch(Marker),
+    /// The value is valid, but isn't encoded in its shortest form, which is rejected by the strict
+    /// decoding functions.
+    NonCanonical(Marker),
 }
 
+#[cfg(feature = "std")]
 impl error::Error for ValueReadError {
     fn description(&self) -> &str {
         match *self {
@@ -58,6 +95,7 @@ impl error::Error for ValueReadError {
             ValueReadError::TypeMismatch(..) => {
                 "the type decoded isn't match with the expected one"
             }
+            ValueReadError::NonCanonical(..) => "the value isn't encoded in its shortest form",
         }
     }
 
@@ -65,14 +103,24 @@ impl error::Error for ValueReadError {
         match *self {
             ValueReadError::InvalidMarkerRead(ref err) |
             ValueReadError::InvalidDataRead(ref err) => Some(err),
-            ValueReadError::TypeMismatch(..) => None,
+            ValueReadError::TypeMismatch(..) |
+            ValueReadError::NonCanonical(..) => None,
         }
     }
 }
 
 impl Display for ValueReadError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        error::Error::description(self).fmt(f)
+        match *self {
+            ValueReadError::InvalidMarkerRead(..) => "failed to read MessagePack marker".fmt(f),
+            ValueReadError::InvalidDataRead(..) => "failed to read MessagePack data".fmt(f),
+            ValueReadError::TypeMismatch(..) => {
+                "the type decoded isn't match with the expected one".fmt(f)
+            }
+            ValueReadError::NonCanonical(marker) => {
+                write!(f, "the value isn't encoded in its shortest form, found {:?}", marker)
+            }
+        }
     }
 }
 
@@ -91,8 +139,10 @@ impl From<Error> for MarkerReadError {
 }
 
 /// Attempts to read a single byte from the given reader and to decode it as a MessagePack marker.
-pub fn read_marker<R: Read>(rd: &mut R) -> Result<Marker, MarkerReadError> {
-    Ok(Marker::from_u8(try!(rd.read_u8())))
+pub fn read_marker<R: RmpRead>(rd: &mut R) -> Result<Marker, MarkerReadError> {
+    let mut buf = [0];
+    try!(rd.read_exact_buf(&mut buf));
+    Ok(Marker::from_u8(buf[0]))
 }
 
 /// Attempts to read a single byte from the given reader and to decode it as a nil value.
@@ -111,7 +161,7 @@ pub fn read_marker<R: Read>(rd: &mut R) -> Result<Marker, MarkerReadError> {
 ///
 /// This function will silently retry on every EINTR received from the underlying `Read` until
 /// successful read.
-pub fn read_nil<R: Read>(rd: &mut R) -> Result<(), ValueReadError> {
+pub fn read_nil<R: RmpRead>(rd: &mut R) -> Result<(), ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::Null => Ok(()),
         marker => Err(ValueReadError::TypeMismatch(marker)),
@@ -135,7 +185,7 @@ pub fn read_nil<R: Read>(rd: &mut R) -> Result<(), ValueReadError> {
 ///
 /// This function will silently retry on every EINTR received from the underlying `Read` until
 /// successful read.
-pub fn read_bool<R: Read>(rd: &mut R) -> Result<bool, ValueReadError> {
+pub fn read_bool<R: RmpRead>(rd: &mut R) -> Result<bool, ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::True => Ok(true),
         Marker::False => Ok(false),
@@ -154,8 +204,12 @@ pub enum NumValueReadError {
     TypeMismatch(Marker),
     /// Out of range integral type conversion attempted.
     OutOfRange,
+    /// The value is valid, but isn't encoded in its shortest form, which is rejected by the strict
+    /// decoding functions.
+    NonCanonical(Marker),
 }
 
+#[cfg(feature = "std")]
 impl error::Error for NumValueReadError {
     fn description(&self) -> &str {
         match *self {
@@ -165,6 +219,7 @@ impl error::Error for NumValueReadError {
                 "the type decoded isn't match with the expected one"
             }
             NumValueReadError::OutOfRange => "out of range integral type conversion attempted",
+            NumValueReadError::NonCanonical(..) => "the value isn't encoded in its shortest form",
         }
     }
 
@@ -173,14 +228,25 @@ impl error::Error for NumValueReadError {
             NumValueReadError::InvalidMarkerRead(ref err) |
             NumValueReadError::InvalidDataRead(ref err) => Some(err),
             NumValueReadError::TypeMismatch(..) |
-            NumValueReadError::OutOfRange => None,
+            NumValueReadError::OutOfRange |
+            NumValueReadError::NonCanonical(..) => None,
         }
     }
 }
 
 impl Display for NumValueReadError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        error::Error::description(self).fmt(f)
+        match *self {
+            NumValueReadError::InvalidMarkerRead(..) => "failed to read MessagePack marker".fmt(f),
+            NumValueReadError::InvalidDataRead(..) => "failed to read MessagePack data".fmt(f),
+            NumValueReadError::TypeMismatch(..) => {
+                "the type decoded isn't match with the expected one".fmt(f)
+            }
+            NumValueReadError::OutOfRange => "out of range integral type conversion attempted".fmt(f),
+            NumValueReadError::NonCanonical(marker) => {
+                write!(f, "the value isn't encoded in its shortest form, found {:?}", marker)
+            }
+        }
     }
 }
 
@@ -198,60 +264,83 @@ impl From<ValueReadError> for NumValueReadError {
             ValueReadError::InvalidMarkerRead(err) => NumValueReadError::InvalidMarkerRead(err),
             ValueReadError::InvalidDataRead(err) => NumValueReadError::InvalidDataRead(err),
             ValueReadError::TypeMismatch(err) => NumValueReadError::TypeMismatch(err),
+            ValueReadError::NonCanonical(err) => NumValueReadError::NonCanonical(err),
         }
     }
 }
 
 // Helper functions to map I/O error into the `InvalidDataRead` error.
 
+fn read_data<R: RmpRead>(rd: &mut R, buf: &mut [u8]) -> Result<(), ValueReadError> {
+    rd.read_exact_buf(buf).map_err(ValueReadError::InvalidDataRead)
+}
+
 #[doc(hidden)]
-pub fn read_data_u8<R: Read>(rd: &mut R) -> Result<u8, ValueReadError> {
-    rd.read_u8().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_u8<R: RmpRead>(rd: &mut R) -> Result<u8, ValueReadError> {
+    let mut buf = [0; 1];
+    read_data(rd, &mut buf)?;
+    Ok(buf[0])
 }
 
 #[doc(hidden)]
-pub fn read_data_u16<R: Read>(rd: &mut R) -> Result<u16, ValueReadError> {
-    rd.read_u16::<byteorder::BigEndian>().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_u16<R: RmpRead>(rd: &mut R) -> Result<u16, ValueReadError> {
+    let mut buf = [0; 2];
+    read_data(rd, &mut buf)?;
+    Ok(BigEndian::read_u16(&buf))
 }
 
 #[doc(hidden)]
-pub fn read_data_u32<R: Read>(rd: &mut R) -> Result<u32, ValueReadError> {
-    rd.read_u32::<byteorder::BigEndian>().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_u32<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
+    let mut buf = [0; 4];
+    read_data(rd, &mut buf)?;
+    Ok(BigEndian::read_u32(&buf))
 }
 
 #[doc(hidden)]
-pub fn read_data_u64<R: Read>(rd: &mut R) -> Result<u64, ValueReadError> {
-    rd.read_u64::<byteorder::BigEndian>().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_u64<R: RmpRead>(rd: &mut R) -> Result<u64, ValueReadError> {
+    let mut buf = [0; 8];
+    read_data(rd, &mut buf)?;
+    Ok(BigEndian::read_u64(&buf))
 }
 
 #[doc(hidden)]
-pub fn read_data_i8<R: Read>(rd: &mut R) -> Result<i8, ValueReadError> {
-    rd.read_i8().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_i8<R: RmpRead>(rd: &mut R) -> Result<i8, ValueReadError> {
+    read_data_u8(rd).map(|val| val as i8)
 }
 
 #[doc(hidden)]
-pub fn read_data_i16<R: Read>(rd: &mut R) -> Result<i16, ValueReadError> {
-    rd.read_i16::<byteorder::BigEndian>().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_i16<R: RmpRead>(rd: &mut R) -> Result<i16, ValueReadError> {
+    let mut buf = [0; 2];
+    read_data(rd, &mut buf)?;
+    Ok(BigEndian::read_i16(&buf))
 }
 
 #[doc(hidden)]
-pub fn read_data_i32<R: Read>(rd: &mut R) -> Result<i32, ValueReadError> {
-    rd.read_i32::<byteorder::BigEndian>().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_i32<R: RmpRead>(rd: &mut R) -> Result<i32, ValueReadError> {
+    let mut buf = [0; 4];
+    read_data(rd, &mut buf)?;
+    Ok(BigEndian::read_i32(&buf))
 }
 
 #[doc(hidden)]
-pub fn read_data_i64<R: Read>(rd: &mut R) -> Result<i64, ValueReadError> {
-    rd.read_i64::<byteorder::BigEndian>().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_i64<R: RmpRead>(rd: &mut R) -> Result<i64, ValueReadError> {
+    let mut buf = [0; 8];
+    read_data(rd, &mut buf)?;
+    Ok(BigEndian::read_i64(&buf))
 }
 
 #[doc(hidden)]
-pub fn read_data_f32<R: Read>(rd: &mut R) -> Result<f32, ValueReadError> {
-    rd.read_f32::<byteorder::BigEndian>().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_f32<R: RmpRead>(rd: &mut R) -> Result<f32, ValueReadError> {
+    let mut buf = [0; 4];
+    read_data(rd, &mut buf)?;
+    Ok(BigEndian::read_f32(&buf))
 }
 
 #[doc(hidden)]
-pub fn read_data_f64<R: Read>(rd: &mut R) -> Result<f64, ValueReadError> {
-    rd.read_f64::<byteorder::BigEndian>().map_err(ValueReadError::InvalidDataRead)
+pub fn read_data_f64<R: RmpRead>(rd: &mut R) -> Result<f64, ValueReadError> {
+    let mut buf = [0; 8];
+    read_data(rd, &mut buf)?;
+    Ok(BigEndian::read_f64(&buf))
 }
 
 /// Attempts to read up to 9 bytes from the given reader and to decode them as integral `T` value.
@@ -284,7 +373,7 @@ pub fn read_data_f64<R: Read>(rd: &mut R) -> Result<f64, ValueReadError> {
 /// assert_eq!(300usize, rmp::decode::read_int(&mut &buf[..]).unwrap());
 /// assert_eq!(300isize, rmp::decode::read_int(&mut &buf[..]).unwrap());
 /// ```
-pub fn read_int<T: FromPrimitive, R: Read>(rd: &mut R) -> Result<T, NumValueReadError> {
+pub fn read_int<T: FromPrimitive, R: RmpRead>(rd: &mut R) -> Result<T, NumValueReadError> {
     let val = match try!(read_marker(rd)) {
         Marker::FixPos(val) => T::from_u8(val),
         Marker::FixNeg(val) => T::from_i8(val),
@@ -315,7 +404,7 @@ pub fn read_int<T: FromPrimitive, R: Read>(rd: &mut R) -> Result<T, NumValueRead
 // TODO: Docs.
 // NOTE: EINTR is managed internally.
 pub fn read_array_len<R>(rd: &mut R) -> Result<u32, ValueReadError>
-    where R: Read
+    where R: RmpRead
 {
     match try!(read_marker(rd)) {
         Marker::FixArray(size) => Ok(size as u32),
@@ -336,7 +425,7 @@ pub fn read_array_len<R>(rd: &mut R) -> Result<u32, ValueReadError>
 /// This function will silently retry on every EINTR received from the underlying `Read` until
 /// successful read.
 // TODO: Docs.
-pub fn read_map_len<R: Read>(rd: &mut R) -> Result<u32, ValueReadError> {
+pub fn read_map_len<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::FixMap(size) => Ok(size as u32),
         Marker::Map16 => Ok(try!(read_data_u16(rd)) as u32),
@@ -352,7 +441,7 @@ pub fn read_map_len<R: Read>(rd: &mut R) -> Result<u32, ValueReadError> {
 /// This function will silently retry on every EINTR received from the underlying `Read` until
 /// successful read.
 // TODO: Docs.
-pub fn read_bin_len<R: Read>(rd: &mut R) -> Result<u32, ValueReadError> {
+pub fn read_bin_len<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
     match try!(read_marker(rd)) {
         Marker::Bin8 => Ok(try!(read_data_u8(rd)) as u32),
         Marker::Bin16 => Ok(try!(read_data_u16(rd)) as u32),
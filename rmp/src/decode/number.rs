@@ -0,0 +1,152 @@
+use num_traits::cast::{FromPrimitive, ToPrimitive};
+
+use Marker;
+use super::{read_marker, read_data_u8, read_data_u16, read_data_u32, read_data_u64, read_data_i8,
+            read_data_i16, read_data_i32, read_data_i64, read_data_f32, read_data_f64, RmpRead,
+            NumValueReadError, ValueReadError};
+
+/// A numeric value of any MessagePack integer or float type.
+///
+/// Non-negative integers are always represented as `U64`, while `I64` holds negative ones only,
+/// regardless of the marker used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    /// A non-negative integer.
+    U64(u64),
+    /// A negative integer.
+    I64(i64),
+    /// A 32-bit float.
+    F32(f32),
+    /// A 64-bit float.
+    F64(f64),
+}
+
+/// Controls which numeric conversions are allowed when reading a value as a different type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumConversion {
+    /// Allow only conversions that preserve the value exactly, for example the integer `3` or any
+    /// `f32` into `f64`, but not `0.1f64` into `f32` or `2.5` into an integer.
+    Lossless,
+    /// Allow conversions that round or truncate the value, as long as it stays within the range of
+    /// the target type. Floats that overflow `f32` become infinite.
+    Lossy,
+}
+
+impl Number {
+    /// Converts this number into the given numeric type.
+    ///
+    /// Returns `None` if the number does not fit in the target type, or, with the
+    /// `NumConversion::Lossless` mode, if the conversion would change its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::{Number, NumConversion};
+    ///
+    /// assert_eq!(Some(3.0f64), Number::U64(3).convert(NumConversion::Lossless));
+    /// assert_eq!(Some(3u8), Number::F64(3.0).convert(NumConversion::Lossless));
+    /// assert_eq!(None::<u8>, Number::F64(2.5).convert(NumConversion::Lossless));
+    /// assert_eq!(Some(2u8), Number::F64(2.5).convert(NumConversion::Lossy));
+    /// ```
+    pub fn convert<T: FromPrimitive + ToPrimitive>(self, conv: NumConversion) -> Option<T> {
+        let lossless = conv == NumConversion::Lossless;
+
+        // A conversion is exact when converting the result back yields the original value. Floats
+        // are compared bitwise, so that NaN payloads and the sign of zero are preserved.
+        match self {
+            Number::U64(val) => T::from_u64(val).filter(|v| !lossless || v.to_u64() == Some(val)),
+            Number::I64(val) => T::from_i64(val).filter(|v| !lossless || v.to_i64() == Some(val)),
+            Number::F32(val) => {
+                T::from_f32(val).filter(|v| {
+                    !lossless || v.to_f32().map(f32::to_bits) == Some(val.to_bits())
+                })
+            }
+            Number::F64(val) => {
+                T::from_f64(val).filter(|v| {
+                    !lossless || v.to_f64().map(f64::to_bits) == Some(val.to_bits())
+                })
+            }
+        }
+    }
+}
+
+/// Attempts to read up to 9 bytes from the given reader and to decode them as a number of any
+/// integer or float type.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It also returns `ValueReadError::TypeMismatch` if the actual type is not a number.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_number, Number};
+///
+/// assert_eq!(Number::U64(300), read_number(&mut &[0xcd, 0x01, 0x2c][..]).unwrap());
+/// assert_eq!(Number::I64(-1), read_number(&mut &[0xff][..]).unwrap());
+/// assert_eq!(Number::F32(1.5), read_number(&mut &[0xca, 0x3f, 0xc0, 0x00, 0x00][..]).unwrap());
+/// ```
+pub fn read_number<R: RmpRead>(rd: &mut R) -> Result<Number, ValueReadError> {
+    let num = match read_marker(rd)? {
+        Marker::FixPos(val) => Number::U64(val as u64),
+        Marker::FixNeg(val) => Number::I64(val as i64),
+        Marker::U8 => Number::U64(read_data_u8(rd)? as u64),
+        Marker::U16 => Number::U64(read_data_u16(rd)? as u64),
+        Marker::U32 => Number::U64(read_data_u32(rd)? as u64),
+        Marker::U64 => Number::U64(read_data_u64(rd)?),
+        Marker::I8 => sint(read_data_i8(rd)? as i64),
+        Marker::I16 => sint(read_data_i16(rd)? as i64),
+        Marker::I32 => sint(read_data_i32(rd)? as i64),
+        Marker::I64 => sint(read_data_i64(rd)?),
+        Marker::F32 => Number::F32(read_data_f32(rd)?),
+        Marker::F64 => Number::F64(read_data_f64(rd)?),
+        marker => return Err(ValueReadError::TypeMismatch(marker)),
+    };
+
+    Ok(num)
+}
+
+/// Attempts to read up to 9 bytes from the given reader and to decode them as a float `T` value.
+///
+/// Unlike `read_f32` and `read_f64`, this function accepts any integer or float marker and
+/// converts the value to `T` according to the given conversion mode, which makes it suitable for
+/// peers that encode whole-number floats as integers.
+///
+/// # Errors
+///
+/// This function will return `NumValueReadError` on any I/O error while reading either the marker
+/// or the data.
+///
+/// It also returns `NumValueReadError::TypeMismatch` if the actual type is not a number, and
+/// `NumValueReadError::OutOfRange` if it can't be converted to `T` in the given mode.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_float, NumConversion};
+///
+/// let buf = [0x03];
+/// assert_eq!(3.0, read_float::<f64, _>(&mut &buf[..], NumConversion::Lossless).unwrap());
+///
+/// // 0.1 as F64.
+/// let buf = [0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a];
+/// assert!(read_float::<f32, _>(&mut &buf[..], NumConversion::Lossless).is_err());
+/// assert_eq!(0.1, read_float::<f32, _>(&mut &buf[..], NumConversion::Lossy).unwrap());
+/// ```
+pub fn read_float<T, R>(rd: &mut R, conv: NumConversion) -> Result<T, NumValueReadError>
+    where T: FromPrimitive + ToPrimitive,
+          R: RmpRead
+{
+    read_number(rd)?.convert(conv).ok_or(NumValueReadError::OutOfRange)
+}
+
+fn sint(val: i64) -> Number {
+    if val < 0 {
+        Number::I64(val)
+    } else {
+        Number::U64(val as u64)
+    }
+}
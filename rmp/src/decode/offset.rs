@@ -0,0 +1,234 @@
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(feature = "std")]
+use std::io;
+use core::fmt::{self, Display, Formatter};
+use core::str::Utf8Error;
+
+use Marker;
+#[cfg(not(feature = "std"))]
+use super::RmpRead;
+use super::{Error, MarkerReadError, ValueReadError, NumValueReadError, DecodeStringError};
+
+/// A reader wrapper, which counts the number of bytes read through it.
+///
+/// Together with `OffsetReader::decode` it allows to turn the low-level decoding errors into
+/// `DecodeError`, which tells where in the stream the failure happened and what was expected
+/// there.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_array_len, read_int, OffsetReader};
+///
+/// // [42, "le message"]
+/// let buf = [0x92, 0x2a, 0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65];
+/// let mut rd = OffsetReader::new(&buf[..]);
+///
+/// assert_eq!(2, rd.decode("array", read_array_len).unwrap());
+/// assert_eq!(42, rd.decode("u8", read_int::<u8, _>).unwrap());
+///
+/// let err = rd.decode("u16", read_int::<u16, _>).unwrap_err();
+/// assert_eq!(2, err.offset);
+/// assert_eq!("expected u16 at byte 2, found FixStr(10)", format!("{}", err));
+/// ```
+#[derive(Debug)]
+pub struct OffsetReader<R> {
+    rd: R,
+    offset: u64,
+}
+
+impl<R> OffsetReader<R> {
+    /// Wraps the given reader, starting to count from zero.
+    pub fn new(rd: R) -> OffsetReader<R> {
+        OffsetReader::with_offset(rd, 0)
+    }
+
+    /// Wraps the given reader, starting to count from the given offset.
+    ///
+    /// This is useful when the reader is positioned in the middle of a larger stream.
+    pub fn with_offset(rd: R, offset: u64) -> OffsetReader<R> {
+        OffsetReader {
+            rd: rd,
+            offset: offset,
+        }
+    }
+
+    /// Returns the offset of the next byte to be read.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.rd
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Note that reading from it directly is not counted.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.rd
+    }
+
+    /// Unwraps this `OffsetReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.rd
+    }
+
+    /// Decodes a value using the given function, attaching the offset where the value starts and
+    /// the `expected` description to the error on failure.
+    ///
+    /// Any decoding function of this module can be passed here, as long as its error converts into
+    /// `DecodeErrorKind`.
+    pub fn decode<T, E, F>(&mut self, expected: &'static str, f: F) -> Result<T, DecodeError>
+        where F: FnOnce(&mut Self) -> Result<T, E>,
+              E: Into<DecodeErrorKind>
+    {
+        let offset = self.offset;
+
+        f(self).map_err(|err| {
+            DecodeError {
+                expected: expected,
+                offset: offset,
+                kind: err.into(),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: io::Read> io::Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.rd.read(buf)?;
+        self.offset += len as u64;
+        Ok(len)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<R: RmpRead> RmpRead for OffsetReader<R> {
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.rd.read_exact_buf(buf)?;
+        self.offset += buf.len() as u64;
+        Ok(())
+    }
+}
+
+/// The cause of a `DecodeError`, unifying the errors of all decoding functions.
+#[derive(Debug)]
+pub enum DecodeErrorKind {
+    /// Failed to read the marker.
+    InvalidMarkerRead(Error),
+    /// Failed to read the data.
+    InvalidDataRead(Error),
+    /// The marker found doesn't match the expected type.
+    TypeMismatch(Marker),
+    /// The value isn't encoded in its shortest form, which is rejected by the strict decoding
+    /// functions.
+    NonCanonical(Marker),
+    /// The value doesn't fit in the expected numeric type.
+    OutOfRange,
+    /// The string of the given length doesn't fit in the buffer provided.
+    BufferSizeTooSmall(u32),
+    /// The string isn't valid UTF-8.
+    InvalidUtf8(Utf8Error),
+}
+
+impl From<MarkerReadError> for DecodeErrorKind {
+    fn from(err: MarkerReadError) -> DecodeErrorKind {
+        DecodeErrorKind::InvalidMarkerRead(err.0)
+    }
+}
+
+impl From<ValueReadError> for DecodeErrorKind {
+    fn from(err: ValueReadError) -> DecodeErrorKind {
+        match err {
+            ValueReadError::InvalidMarkerRead(err) => DecodeErrorKind::InvalidMarkerRead(err),
+            ValueReadError::InvalidDataRead(err) => DecodeErrorKind::InvalidDataRead(err),
+            ValueReadError::TypeMismatch(marker) => DecodeErrorKind::TypeMismatch(marker),
+            ValueReadError::NonCanonical(marker) => DecodeErrorKind::NonCanonical(marker),
+        }
+    }
+}
+
+impl From<NumValueReadError> for DecodeErrorKind {
+    fn from(err: NumValueReadError) -> DecodeErrorKind {
+        match err {
+            NumValueReadError::InvalidMarkerRead(err) => DecodeErrorKind::InvalidMarkerRead(err),
+            NumValueReadError::InvalidDataRead(err) => DecodeErrorKind::InvalidDataRead(err),
+            NumValueReadError::TypeMismatch(marker) => DecodeErrorKind::TypeMismatch(marker),
+            NumValueReadError::NonCanonical(marker) => DecodeErrorKind::NonCanonical(marker),
+            NumValueReadError::OutOfRange => DecodeErrorKind::OutOfRange,
+        }
+    }
+}
+
+impl<'a> From<DecodeStringError<'a>> for DecodeErrorKind {
+    fn from(err: DecodeStringError<'a>) -> DecodeErrorKind {
+        match err {
+            DecodeStringError::InvalidMarkerRead(err) => DecodeErrorKind::InvalidMarkerRead(err),
+            DecodeStringError::InvalidDataRead(err) => DecodeErrorKind::InvalidDataRead(err),
+            DecodeStringError::TypeMismatch(marker) => DecodeErrorKind::TypeMismatch(marker),
+            DecodeStringError::NonCanonical(marker) => DecodeErrorKind::NonCanonical(marker),
+            DecodeStringError::BufferSizeTooSmall(len) => DecodeErrorKind::BufferSizeTooSmall(len),
+            DecodeStringError::InvalidUtf8(_, err) => DecodeErrorKind::InvalidUtf8(err),
+        }
+    }
+}
+
+/// A decoding error with the offset of the value that failed to decode and the description of
+/// what was expected there.
+///
+/// It is displayed like "expected u16 at byte 1423, found Str8", which makes failures in
+/// production traceable from logs.
+#[derive(Debug)]
+pub struct DecodeError {
+    /// The description of the expected value, for example "u16" or "map".
+    pub expected: &'static str,
+    /// The offset of the first byte of the value, i.e. of its marker.
+    pub offset: u64,
+    /// The cause of the error.
+    pub kind: DecodeErrorKind,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for DecodeError {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self.kind {
+            DecodeErrorKind::InvalidMarkerRead(ref err) |
+            DecodeErrorKind::InvalidDataRead(ref err) => Some(err),
+            DecodeErrorKind::InvalidUtf8(ref err) => Some(err),
+            DecodeErrorKind::TypeMismatch(..) |
+            DecodeErrorKind::NonCanonical(..) |
+            DecodeErrorKind::OutOfRange |
+            DecodeErrorKind::BufferSizeTooSmall(..) => None,
+        }
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "expected {} at byte {}", self.expected, self.offset)?;
+
+        match self.kind {
+            DecodeErrorKind::InvalidMarkerRead(ref err) => {
+                write!(f, ", but failed to read the marker: {}", err)
+            }
+            DecodeErrorKind::InvalidDataRead(ref err) => {
+                write!(f, ", but failed to read the data: {}", err)
+            }
+            DecodeErrorKind::TypeMismatch(marker) => write!(f, ", found {:?}", marker),
+            DecodeErrorKind::NonCanonical(marker) => {
+                write!(f, ", found {:?} not in its shortest form", marker)
+            }
+            DecodeErrorKind::OutOfRange => f.write_str(", found a value out of range"),
+            DecodeErrorKind::BufferSizeTooSmall(len) => {
+                write!(f, ", found a string of {} bytes, which doesn't fit the buffer", len)
+            }
+            DecodeErrorKind::InvalidUtf8(ref err) => {
+                write!(f, ", found a string, which isn't valid UTF-8: {}", err)
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(not(feature = "std"))]
+use core::fmt::{self, Display, Formatter};
+
+use super::Error;
+
+/// A minimal source of bytes used by the decoding functions.
+///
+/// With the `std` feature enabled (the default) this trait is implemented for every type that
+/// implements `std::io::Read`, so you should never need to implement it yourself.
+///
+/// Without the standard library it is implemented for byte slices, which are advanced as the data
+/// is read, exactly like `std::io::Read` does for them.
+pub trait RmpRead {
+    /// Reads the exact number of bytes required to fill the given buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is exhausted before the buffer is filled. The contents of
+    /// the buffer are unspecified in this case.
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), Error>;
+}
+
+#[cfg(feature = "std")]
+impl<R: io::Read + ?Sized> RmpRead for R {
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.read_exact(buf)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl RmpRead for &[u8] {
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        if self.len() < buf.len() {
+            *self = &self[self.len()..];
+            return Err(BytesReadError);
+        }
+
+        let (head, tail) = self.split_at(buf.len());
+        buf.copy_from_slice(head);
+        *self = tail;
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<R: RmpRead + ?Sized> RmpRead for &mut R {
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        (**self).read_exact_buf(buf)
+    }
+}
+
+/// An error returned when a byte slice has been exhausted before the value was read completely.
+///
+/// It takes place of `std::io::Error` when the `std` feature is disabled.
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BytesReadError;
+
+#[cfg(not(feature = "std"))]
+impl Display for BytesReadError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.write_str("unexpected end of data")
+    }
+}
+
+// Returns true if the error is caused by the source being exhausted.
+#[cfg(feature = "std")]
+pub fn is_eof(err: &Error) -> bool {
+    err.kind() == io::ErrorKind::UnexpectedEof
+}
+
+#[cfg(not(feature = "std"))]
+pub fn is_eof(_err: &Error) -> bool {
+    true
+}
+
+// Constructs an error signaling that the source has been exhausted.
+#[cfg(feature = "std")]
+pub fn unexpected_eof() -> Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")
+}
+
+#[cfg(not(feature = "std"))]
+pub fn unexpected_eof() -> Error {
+    BytesReadError
+}
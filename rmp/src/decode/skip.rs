@@ -0,0 +1,104 @@
+use core::cmp;
+
+use Payload;
+use super::{read_marker, RmpRead, ValueReadError};
+
+/// Attempts to skip a single complete MessagePack value from the given reader, returning the
+/// number of bytes consumed.
+///
+/// Nested arrays and maps are skipped entirely. The traversal is iterative, tracking only the
+/// number of values left to skip, so deeply nested input can't overflow the stack. Payloads are
+/// discarded through a small buffer on the stack, which means that no allocation is made.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either a marker or
+/// the data, including unexpected EOF in the middle of a payload.
+///
+/// It also returns `ValueReadError::TypeMismatch` when encountering the reserved `0xc1` marker.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_int, skip_value};
+///
+/// // [[1, 2], {"a": nil}], 42
+/// let buf = [0x92, 0x92, 0x01, 0x02, 0x81, 0xa1, 0x61, 0xc0, 0x2a];
+/// let mut rd = &buf[..];
+///
+/// assert_eq!(8, skip_value(&mut rd).unwrap());
+/// assert_eq!(42, read_int::<u8, _>(&mut rd).unwrap());
+/// ```
+pub fn skip_value<R: RmpRead>(rd: &mut R) -> Result<u64, ValueReadError> {
+    let mut pending = 1u64;
+    let mut nread = 0u64;
+
+    while pending > 0 {
+        pending -= 1;
+
+        let marker = read_marker(rd)?;
+        nread += 1;
+
+        let header = marker.header_len();
+        let mut buf = [0; 5];
+        rd.read_exact_buf(&mut buf[..header]).map_err(ValueReadError::InvalidDataRead)?;
+
+        // The type id of extensions is a part of the header, so only the data is left to skip.
+        let payload = match marker.payload(&buf[..header]) {
+            Some(Payload::Bytes(len)) => len as u64,
+            Some(Payload::Values(count)) => {
+                pending += count;
+                0
+            }
+            None => return Err(ValueReadError::TypeMismatch(marker)),
+        };
+
+        skip_data(rd, payload)?;
+        nread += header as u64 + payload;
+    }
+
+    Ok(nread)
+}
+
+/// Attempts to skip a single complete MessagePack value from the given slice, returning the
+/// sub-slice covering the skipped value and the remaining tail.
+///
+/// # Errors
+///
+/// This function fails in the same cases as `skip_value` does. Unexpected EOF is reported when the
+/// slice ends in the middle of the value.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::skip_value_from_slice;
+///
+/// // [1, 2], "a"
+/// let buf = [0x92, 0x01, 0x02, 0xa1, 0x61];
+///
+/// let (value, tail) = skip_value_from_slice(&buf[..]).unwrap();
+///
+/// assert_eq!([0x92, 0x01, 0x02], value);
+/// assert_eq!([0xa1, 0x61], tail);
+/// ```
+pub fn skip_value_from_slice<T: ?Sized + AsRef<[u8]>>(buf: &T) ->
+    Result<(&[u8], &[u8]), ValueReadError>
+{
+    let buf = buf.as_ref();
+    let len = skip_value(&mut &buf[..])?;
+
+    // The value has been read from the slice, so its length must fit in `usize`.
+    Ok(buf.split_at(len as usize))
+}
+
+fn skip_data<R: RmpRead>(rd: &mut R, mut len: u64) -> Result<(), ValueReadError> {
+    let mut buf = [0; 256];
+
+    while len > 0 {
+        let chunk = cmp::min(len, buf.len() as u64) as usize;
+        rd.read_exact_buf(&mut buf[..chunk]).map_err(ValueReadError::InvalidDataRead)?;
+        len -= chunk as u64;
+    }
+
+    Ok(())
+}
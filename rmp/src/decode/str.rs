@@ -1,21 +1,29 @@
+#[cfg(feature = "std")]
 use std::error;
-use std::io::{self, Read};
-use std::fmt::{self, Display, Formatter};
-use std::str::{Utf8Error, from_utf8};
+use core::fmt::{self, Display, Formatter};
+use core::str::{Utf8Error, from_utf8};
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
 
 use Marker;
-use super::{read_marker, read_data_u8, read_data_u16, read_data_u32, Error, ValueReadError};
+use super::{read_marker, read_data_u8, read_data_u16, read_data_u32, RmpRead, Error,
+            ValueReadError};
 
 #[derive(Debug)]
 pub enum DecodeStringError<'a> {
     InvalidMarkerRead(Error),
     InvalidDataRead(Error),
     TypeMismatch(Marker),
+    /// The string header isn't encoded in its shortest form.
+    NonCanonical(Marker),
     /// The given buffer is not large enough to accumulate the specified amount of bytes.
     BufferSizeTooSmall(u32),
     InvalidUtf8(&'a [u8], Utf8Error),
 }
 
+#[cfg(feature = "std")]
 impl<'a> error::Error for DecodeStringError<'a> {
     fn description(&self) -> &str {
         "error while decoding string"
@@ -26,6 +34,7 @@ impl<'a> error::Error for DecodeStringError<'a> {
             DecodeStringError::InvalidMarkerRead(ref err) |
             DecodeStringError::InvalidDataRead(ref err) => Some(err),
             DecodeStringError::TypeMismatch(..) |
+            DecodeStringError::NonCanonical(..) |
             DecodeStringError::BufferSizeTooSmall(..) => None,
             DecodeStringError::InvalidUtf8(_, ref err) => Some(err),
         }
@@ -34,7 +43,7 @@ impl<'a> error::Error for DecodeStringError<'a> {
 
 impl<'a> Display for DecodeStringError<'a> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        error::Error::description(self).fmt(f)
+        "error while decoding string".fmt(f)
     }
 }
 
@@ -44,6 +53,7 @@ impl<'a> From<ValueReadError> for DecodeStringError<'a> {
             ValueReadError::InvalidMarkerRead(err) => DecodeStringError::InvalidMarkerRead(err),
             ValueReadError::InvalidDataRead(err) => DecodeStringError::InvalidDataRead(err),
             ValueReadError::TypeMismatch(marker) => DecodeStringError::TypeMismatch(marker),
+            ValueReadError::NonCanonical(marker) => DecodeStringError::NonCanonical(marker),
         }
     }
 }
@@ -61,12 +71,12 @@ impl<'a> From<ValueReadError> for DecodeStringError<'a> {
 ///
 /// It also returns `ValueReadError::TypeMismatch` if the actual type is not equal with the
 /// expected one, indicating you with the actual type.
-pub fn read_str_len<R: Read>(rd: &mut R) -> Result<u32, ValueReadError> {
+pub fn read_str_len<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
     Ok(read_str_len_with_nread(rd)?.0)
 }
 
 fn read_str_len_with_nread<R>(rd: &mut R) -> Result<(u32, usize), ValueReadError>
-    where R: Read
+    where R: RmpRead
 {
     match read_marker(rd)? {
         Marker::FixStr(size) => Ok((size as u32, 1)),
@@ -77,6 +87,40 @@ fn read_str_len_with_nread<R>(rd: &mut R) -> Result<(u32, usize), ValueReadError
     }
 }
 
+/// Attempts to read up to 5 bytes from the given reader and to decode them as a raw length,
+/// treating string and binary markers alike.
+///
+/// The old MessagePack specification (before 2013) had a single raw type for both strings and
+/// binary data, which is written using the string markers. Use this function to read data that may
+/// have been produced either by an old implementation or by `write_raw`, when the caller knows
+/// itself whether the payload is a string or bytes.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It also returns `ValueReadError::TypeMismatch` if the actual type is neither a string nor a
+/// binary.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::read_raw_len;
+///
+/// assert_eq!(42, read_raw_len(&mut &[0xda, 0x00, 0x2a][..]).unwrap());
+/// assert_eq!(42, read_raw_len(&mut &[0xc4, 0x2a][..]).unwrap());
+/// ```
+pub fn read_raw_len<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
+    match read_marker(rd)? {
+        Marker::FixStr(size) => Ok(size as u32),
+        Marker::Str8 | Marker::Bin8 => Ok(read_data_u8(rd)? as u32),
+        Marker::Str16 | Marker::Bin16 => Ok(read_data_u16(rd)? as u32),
+        Marker::Str32 | Marker::Bin32 => Ok(read_data_u32(rd)?),
+        marker => Err(ValueReadError::TypeMismatch(marker)),
+    }
+}
+
 /// Attempts to read a string data from the given reader and copy it to the buffer provided.
 ///
 /// On success returns a borrowed string type, allowing to view the copied bytes as properly utf-8
@@ -108,7 +152,7 @@ fn read_str_len_with_nread<R>(rd: &mut R) -> Result<(u32, usize), ValueReadError
 /// This function is **unstable**, because it needs review.
 // TODO: Stabilize. Mark error values for each error case (in docs).
 pub fn read_str<'r, R>(rd: &mut R, mut buf: &'r mut [u8]) -> Result<&'r str, DecodeStringError<'r>>
-    where R: Read
+    where R: RmpRead
 {
     let len = try!(read_str_len(rd));
     let ulen = len as usize;
@@ -124,12 +168,12 @@ pub fn read_str_data<'r, R>(rd: &mut R,
                             len: u32,
                             buf: &'r mut [u8])
                             -> Result<&'r str, DecodeStringError<'r>>
-    where R: Read
+    where R: RmpRead
 {
     debug_assert_eq!(len as usize, buf.len());
 
     // Trying to copy exact `len` bytes.
-    match rd.read_exact(buf) {
+    match rd.read_exact_buf(buf) {
         Ok(()) => {
             match from_utf8(buf) {
                 Ok(decoded) => Ok(decoded),
@@ -145,9 +189,7 @@ pub fn read_str_data<'r, R>(rd: &mut R,
 // TODO: Also it's possible to implement all borrowing functions for all `BufRead` implementors.
 #[deprecated(since = "0.8.6", note = "useless, use `read_str_from_slice` instead")]
 pub fn read_str_ref(rd: &[u8]) -> Result<&[u8], DecodeStringError> {
-    let mut cur = io::Cursor::new(rd);
-    let len = try!(read_str_len(&mut cur));
-    let start = cur.position() as usize;
+    let (len, start) = try!(read_str_len_with_nread(&mut &rd[..]));
     Ok(&rd[start..start + len as usize])
 }
 
@@ -190,3 +232,188 @@ pub fn read_str_from_slice<T: ?Sized + AsRef<[u8]>>(buf: &T) ->
         Err(DecodeStringError::BufferSizeTooSmall(len))
     }
 }
+
+/// Attempts to read and decode a string value from the slice, returning a borrowed string, where
+/// invalid UTF-8 sequences are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// The string is borrowed from the slice if it's valid UTF-8, and copied otherwise.
+///
+/// # Errors
+///
+/// Returns `DecodeStringError::BufferSizeTooSmall` if the slice ends before the string data does,
+/// and the usual errors if the value is not a string.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::read_str_lossy_from_slice;
+///
+/// let buf = [0xa3, 0x61, 0xff, 0x62, 0xc0];
+/// let (val, tail) = read_str_lossy_from_slice(&buf[..]).unwrap();
+///
+/// assert_eq!("a\u{fffd}b", val);
+/// assert_eq!([0xc0], tail);
+/// ```
+pub fn read_str_lossy_from_slice<T: ?Sized + AsRef<[u8]>>(buf: &T) ->
+    Result<(Cow<str>, &[u8]), DecodeStringError>
+{
+    let buf = buf.as_ref();
+    let (len, nread) = read_str_len_with_nread(&mut &buf[..])?;
+    let ulen = len as usize;
+
+    if buf[nread..].len() >= ulen {
+        let (head, tail) = buf.split_at(nread + ulen);
+        Ok((String::from_utf8_lossy(&head[nread..]), tail))
+    } else {
+        Err(DecodeStringError::BufferSizeTooSmall(len))
+    }
+}
+
+/// The data of a string value, which is not necessarily valid UTF-8.
+///
+/// The MessagePack specification requires strings to be UTF-8 encoded, but older or broken
+/// implementations may produce anything. This type keeps the original bytes along with the result
+/// of UTF-8 validation, leaving the decision of what to do with invalid data to the caller.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrBytes {
+    bytes: Vec<u8>,
+    err: Option<Utf8Error>,
+}
+
+impl StrBytes {
+    /// Wraps the given bytes, validating them as UTF-8.
+    pub fn new(bytes: Vec<u8>) -> StrBytes {
+        let err = from_utf8(&bytes).err();
+
+        StrBytes {
+            bytes: bytes,
+            err: err,
+        }
+    }
+
+    /// Returns true if the bytes are valid UTF-8.
+    pub fn is_valid(&self) -> bool {
+        self.err.is_none()
+    }
+
+    /// Returns the error describing why the bytes are not valid UTF-8, if they are not.
+    pub fn utf8_error(&self) -> Option<Utf8Error> {
+        self.err
+    }
+
+    /// Returns the string if the bytes are valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        match self.err {
+            Some(..) => None,
+            None => from_utf8(&self.bytes).ok(),
+        }
+    }
+
+    /// Returns the original bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the string, where invalid UTF-8 sequences are replaced with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn to_str_lossy(&self) -> Cow<str> {
+        String::from_utf8_lossy(&self.bytes)
+    }
+
+    /// Converts into the string if the bytes are valid UTF-8, returning `self` back otherwise.
+    pub fn into_string(self) -> Result<String, StrBytes> {
+        match self.err {
+            Some(..) => Err(self),
+            None => String::from_utf8(self.bytes).map_err(|err| StrBytes::new(err.into_bytes())),
+        }
+    }
+
+    /// Converts into the original bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Attempts to read a string value from the given reader, returning its data regardless of whether
+/// it is valid UTF-8.
+///
+/// At most `max_len` bytes of string data are accepted, which bounds the amount of memory
+/// allocated for untrusted input.
+///
+/// # Errors
+///
+/// This function will return `DecodeStringError` on any I/O error while reading either the header
+/// or the data.
+///
+/// It returns `DecodeStringError::BufferSizeTooSmall` with the declared length if it exceeds
+/// `max_len`. In this case the string data is left unread, so the reader is positioned right after
+/// the header and the data can be skipped by the caller.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::read_str_bytes;
+///
+/// let buf = [0xa3, 0x61, 0xff, 0x62];
+/// let val = read_str_bytes(&mut &buf[..], 16).unwrap();
+///
+/// assert!(!val.is_valid());
+/// assert_eq!([0x61, 0xff, 0x62], val.as_bytes());
+/// assert_eq!("a\u{fffd}b", val.to_str_lossy());
+/// ```
+pub fn read_str_bytes<R: RmpRead>(rd: &mut R, max_len: u32) -> Result<StrBytes, DecodeStringError<'static>> {
+    let len = read_str_len(rd)?;
+    if len > max_len {
+        return Err(DecodeStringError::BufferSizeTooSmall(len));
+    }
+
+    let mut buf = alloc::vec![0; len as usize];
+    rd.read_exact_buf(&mut buf).map_err(DecodeStringError::InvalidDataRead)?;
+
+    Ok(StrBytes::new(buf))
+}
+
+/// Attempts to read a string value from the given reader and append it to the given `String`,
+/// replacing invalid UTF-8 sequences with `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// Returns whether the string was valid UTF-8, so that records with replaced data can be flagged
+/// instead of dropped. At most `max_len` bytes of string data are accepted.
+///
+/// # Errors
+///
+/// Fails in the same cases as `read_str_bytes` does. Nothing is appended on failure.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::read_str_lossy;
+///
+/// let buf = [0xa2, 0x6f, 0x6b, 0xa3, 0x61, 0xff, 0x62];
+/// let mut rd = &buf[..];
+/// let mut out = String::new();
+///
+/// assert!(read_str_lossy(&mut rd, &mut out, 16).unwrap());
+/// assert!(!read_str_lossy(&mut rd, &mut out, 16).unwrap());
+/// assert_eq!("oka\u{fffd}b", out);
+/// ```
+pub fn read_str_lossy<R>(rd: &mut R, out: &mut String, max_len: u32) ->
+    Result<bool, DecodeStringError<'static>>
+    where R: RmpRead
+{
+    let val = read_str_bytes(rd, max_len)?;
+
+    match val.into_string() {
+        Ok(val) => {
+            if out.is_empty() {
+                *out = val;
+            } else {
+                out.push_str(&val);
+            }
+            Ok(true)
+        }
+        Err(val) => {
+            out.push_str(&val.to_str_lossy());
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,274 @@
+// Strict decoding functions, which accept only the canonical (shortest) form of each value.
+//
+// MessagePack allows most values to be encoded in several ways, for example `300` fits in a
+// `U16`, but can also be written as a `U32` or a `U64`. This is harmless in most cases, but makes
+// byte-for-byte comparison of encoded messages meaningless, which matters when they are hashed,
+// signed or used as keys. The functions below reject such input with a `NonCanonical` error,
+// carrying the offending marker.
+//
+// The canonical form is the one produced by the encoding functions of this crate, i.e.
+// `write_uint`, `write_sint`, `write_str_len`, `write_bin_len`, `write_array_len`,
+// `write_map_len` and `write_ext_meta`. Floats are an exception, see `read_f64_strict`.
+
+use num_traits::cast::FromPrimitive;
+
+use Marker;
+use encode::{uint_len, sint_len, str_header_len, bin_header_len, array_header_len,
+             map_header_len, ext_header_len};
+use super::{read_marker, read_data_u8, read_data_u16, read_data_u32, read_data_u64, read_data_i8,
+            read_data_i16, read_data_i32, read_data_i64, read_data_f32, read_data_f64, ExtMeta,
+            RmpRead, NumValueReadError, ValueReadError};
+
+/// Attempts to read up to 9 bytes from the given reader and to decode them as integral `T` value,
+/// requiring the integer to be encoded in its shortest form.
+///
+/// Non-negative integers must use the smallest of the positive fixnum and unsigned markers, while
+/// negative ones must use the smallest of the negative fixnum and signed markers, exactly as
+/// `write_sint` does.
+///
+/// # Errors
+///
+/// This function will return `NumValueReadError` on any I/O error while reading either the marker
+/// or the data.
+///
+/// It returns `NumValueReadError::NonCanonical` if the integer has a shorter representation, and
+/// `NumValueReadError::OutOfRange` if it does not fit in the given numeric range.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Marker;
+/// use rmp::decode::{read_int_strict, NumValueReadError};
+///
+/// let buf = [0xcd, 0x01, 0x2c];
+/// assert_eq!(300u16, read_int_strict(&mut &buf[..]).unwrap());
+///
+/// // 300 written as U64.
+/// let buf = [0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x2c];
+/// match read_int_strict::<u16, _>(&mut &buf[..]) {
+///     Err(NumValueReadError::NonCanonical(Marker::U64)) => {}
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn read_int_strict<T: FromPrimitive, R: RmpRead>(rd: &mut R) -> Result<T, NumValueReadError> {
+    let marker = read_marker(rd)?;
+    let (val, canonical) = match marker {
+        Marker::FixPos(val) => (T::from_u8(val), true),
+        Marker::FixNeg(val) => (T::from_i8(val), true),
+        Marker::U8 => {
+            let val = read_data_u8(rd)?;
+            (T::from_u8(val), uint_len(val as u64) == 2)
+        }
+        Marker::U16 => {
+            let val = read_data_u16(rd)?;
+            (T::from_u16(val), uint_len(val as u64) == 3)
+        }
+        Marker::U32 => {
+            let val = read_data_u32(rd)?;
+            (T::from_u32(val), uint_len(val as u64) == 5)
+        }
+        Marker::U64 => {
+            let val = read_data_u64(rd)?;
+            (T::from_u64(val), uint_len(val) == 9)
+        }
+        Marker::I8 => {
+            let val = read_data_i8(rd)?;
+            (T::from_i8(val), val < 0 && sint_len(val as i64) == 2)
+        }
+        Marker::I16 => {
+            let val = read_data_i16(rd)?;
+            (T::from_i16(val), val < 0 && sint_len(val as i64) == 3)
+        }
+        Marker::I32 => {
+            let val = read_data_i32(rd)?;
+            (T::from_i32(val), val < 0 && sint_len(val as i64) == 5)
+        }
+        Marker::I64 => {
+            let val = read_data_i64(rd)?;
+            (T::from_i64(val), val < 0 && sint_len(val) == 9)
+        }
+        marker => return Err(NumValueReadError::TypeMismatch(marker)),
+    };
+
+    if !canonical {
+        return Err(NumValueReadError::NonCanonical(marker));
+    }
+
+    val.ok_or(NumValueReadError::OutOfRange)
+}
+
+/// Attempts to read up to 9 bytes from the given reader and to decode them as `f64` value,
+/// requiring the float to be encoded in its shortest form.
+///
+/// Both `F32` and `F64` markers are accepted, but the latter only if the value can't be converted
+/// to `f32` and back without changing its bits. Note that `write_f64` always uses the `F64` marker,
+/// so its output isn't necessarily canonical.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It returns `ValueReadError::NonCanonical` if the value is exactly representable as `f32`, but is
+/// encoded as `F64`.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Marker;
+/// use rmp::decode::{read_f64_strict, ValueReadError};
+///
+/// // 1.5 written as F32.
+/// let buf = [0xca, 0x3f, 0xc0, 0x00, 0x00];
+/// assert_eq!(1.5, read_f64_strict(&mut &buf[..]).unwrap());
+///
+/// // 1.5 written as F64.
+/// let buf = [0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+/// match read_f64_strict(&mut &buf[..]) {
+///     Err(ValueReadError::NonCanonical(Marker::F64)) => {}
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn read_f64_strict<R: RmpRead>(rd: &mut R) -> Result<f64, ValueReadError> {
+    match read_marker(rd)? {
+        Marker::F32 => Ok(read_data_f32(rd)? as f64),
+        Marker::F64 => {
+            let val = read_data_f64(rd)?;
+            if (val as f32 as f64).to_bits() == val.to_bits() {
+                Err(ValueReadError::NonCanonical(Marker::F64))
+            } else {
+                Ok(val)
+            }
+        }
+        marker => Err(ValueReadError::TypeMismatch(marker)),
+    }
+}
+
+/// Attempts to read up to 5 bytes from the given reader and to decode them as a string length,
+/// requiring the header to be encoded in its shortest form.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It returns `ValueReadError::NonCanonical` if the length has a shorter representation, for
+/// example when a 3-byte string is written with `Str32` marker.
+pub fn read_str_len_strict<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
+    let marker = read_marker(rd)?;
+    let (len, nread) = match marker {
+        Marker::FixStr(len) => (len as u32, 1),
+        Marker::Str8 => (read_data_u8(rd)? as u32, 2),
+        Marker::Str16 => (read_data_u16(rd)? as u32, 3),
+        Marker::Str32 => (read_data_u32(rd)?, 5),
+        marker => return Err(ValueReadError::TypeMismatch(marker)),
+    };
+
+    check_len(marker, len, nread, str_header_len(len))
+}
+
+/// Attempts to read up to 5 bytes from the given reader and to decode them as a binary length,
+/// requiring the header to be encoded in its shortest form.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It returns `ValueReadError::NonCanonical` if the length has a shorter representation.
+pub fn read_bin_len_strict<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
+    let marker = read_marker(rd)?;
+    let (len, nread) = match marker {
+        Marker::Bin8 => (read_data_u8(rd)? as u32, 2),
+        Marker::Bin16 => (read_data_u16(rd)? as u32, 3),
+        Marker::Bin32 => (read_data_u32(rd)?, 5),
+        marker => return Err(ValueReadError::TypeMismatch(marker)),
+    };
+
+    check_len(marker, len, nread, bin_header_len(len))
+}
+
+/// Attempts to read up to 5 bytes from the given reader and to decode them as an array length,
+/// requiring the header to be encoded in its shortest form.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It returns `ValueReadError::NonCanonical` if the length has a shorter representation.
+pub fn read_array_len_strict<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
+    let marker = read_marker(rd)?;
+    let (len, nread) = match marker {
+        Marker::FixArray(len) => (len as u32, 1),
+        Marker::Array16 => (read_data_u16(rd)? as u32, 3),
+        Marker::Array32 => (read_data_u32(rd)?, 5),
+        marker => return Err(ValueReadError::TypeMismatch(marker)),
+    };
+
+    check_len(marker, len, nread, array_header_len(len))
+}
+
+/// Attempts to read up to 5 bytes from the given reader and to decode them as a map length,
+/// requiring the header to be encoded in its shortest form.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It returns `ValueReadError::NonCanonical` if the length has a shorter representation.
+pub fn read_map_len_strict<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError> {
+    let marker = read_marker(rd)?;
+    let (len, nread) = match marker {
+        Marker::FixMap(len) => (len as u32, 1),
+        Marker::Map16 => (read_data_u16(rd)? as u32, 3),
+        Marker::Map32 => (read_data_u32(rd)?, 5),
+        marker => return Err(ValueReadError::TypeMismatch(marker)),
+    };
+
+    check_len(marker, len, nread, map_header_len(len))
+}
+
+/// Attempts to read up to 6 bytes from the given reader and to decode them as an extension header,
+/// requiring it to be encoded in its shortest form.
+///
+/// Data of 1, 2, 4, 8 and 16 bytes long must use the corresponding `FixExt` marker.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It returns `ValueReadError::NonCanonical` if the header has a shorter representation.
+pub fn read_ext_meta_strict<R: RmpRead>(rd: &mut R) -> Result<ExtMeta, ValueReadError> {
+    let marker = read_marker(rd)?;
+    // The header sizes include the type id byte.
+    let (size, nread) = match marker {
+        Marker::FixExt1 => (1, 2),
+        Marker::FixExt2 => (2, 2),
+        Marker::FixExt4 => (4, 2),
+        Marker::FixExt8 => (8, 2),
+        Marker::FixExt16 => (16, 2),
+        Marker::Ext8 => (read_data_u8(rd)? as u32, 3),
+        Marker::Ext16 => (read_data_u16(rd)? as u32, 4),
+        Marker::Ext32 => (read_data_u32(rd)?, 6),
+        marker => return Err(ValueReadError::TypeMismatch(marker)),
+    };
+
+    let size = check_len(marker, size, nread, ext_header_len(size))?;
+    let ty = read_data_i8(rd)?;
+
+    Ok(ExtMeta {
+        typeid: ty,
+        size: size,
+    })
+}
+
+fn check_len(marker: Marker, len: u32, nread: usize, canonical: usize) -> Result<u32, ValueReadError> {
+    if nread == canonical {
+        Ok(len)
+    } else {
+        Err(ValueReadError::NonCanonical(marker))
+    }
+}
@@ -0,0 +1,295 @@
+use core::cmp;
+
+use alloc::vec::Vec;
+
+use {Family, Marker, Payload};
+use super::{read_data_u8, read_data_u16, read_data_u32, read_data_u64, read_data_i8, read_data_i16,
+            read_data_i32, read_data_i64, read_data_f32, read_data_f64, RmpRead, ValueReadError};
+use super::read::{is_eof, unexpected_eof};
+
+/// A single event produced by the `Tokenizer`.
+///
+/// Scalar values are yielded as is, while arrays and maps are announced by their start event
+/// carrying the number of elements (or key-value pairs) that follow. Strings are yielded as raw
+/// bytes, leaving UTF-8 validation up to the caller.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token<'a> {
+    /// Nil.
+    Nil,
+    /// Boolean.
+    Bool(bool),
+    /// Non-negative integer of any width.
+    UInt(u64),
+    /// Negative integer of any width.
+    Int(i64),
+    /// 32-bit floating point number.
+    F32(f32),
+    /// 64-bit floating point number.
+    F64(f64),
+    /// Raw string bytes.
+    Str(&'a [u8]),
+    /// Binary data.
+    Bin(&'a [u8]),
+    /// Extension with its type id and data.
+    Ext(i8, &'a [u8]),
+    /// Start of an array with the given number of elements.
+    ArrayStart(u32),
+    /// Start of a map with the given number of key-value pairs.
+    MapStart(u32),
+}
+
+// Payloads are read in chunks of this size, so that a bogus length can't make the tokenizer
+// allocate a huge buffer upfront.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+enum Head {
+    Token(Token<'static>),
+    Str(u32),
+    Bin(u32),
+    Ext(i8, u32),
+}
+
+/// An event-based pull parser over a stream of MessagePack values.
+///
+/// The tokenizer reads markers one by one, yielding a `Token` for each of them without building
+/// any intermediate value. It also keeps track of the current nesting depth and the number of bytes
+/// consumed, which makes it a convenient building block for streaming filters and converters.
+///
+/// When constructed from a reader, string, binary and extension payloads are read into an internal
+/// buffer that is reused between calls to `next_token`. When constructed from a slice, the
+/// tokenizer also acts as an iterator yielding tokens borrowed directly from the slice.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{Token, Tokenizer};
+///
+/// // {"id": [42, nil]}
+/// let buf = [0x81, 0xa2, 0x69, 0x64, 0x92, 0x2a, 0xc0];
+///
+/// let mut tokenizer = Tokenizer::from_slice(&buf[..]);
+///
+/// assert_eq!(Token::MapStart(1), tokenizer.next_token().unwrap().unwrap());
+/// assert_eq!(Token::Str(b"id"), tokenizer.next_token().unwrap().unwrap());
+/// assert_eq!(Token::ArrayStart(2), tokenizer.next_token().unwrap().unwrap());
+/// assert_eq!(2, tokenizer.depth());
+/// assert_eq!(Token::UInt(42), tokenizer.next_token().unwrap().unwrap());
+/// assert_eq!(Token::Nil, tokenizer.next_token().unwrap().unwrap());
+/// assert_eq!(0, tokenizer.depth());
+/// assert_eq!(7, tokenizer.offset());
+/// assert_eq!(None, tokenizer.next_token().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct Tokenizer<R> {
+    rd: R,
+    buf: Vec<u8>,
+    offset: u64,
+    // Number of items left in each of the currently open containers, outermost first.
+    remaining: Vec<u64>,
+}
+
+impl<R: RmpRead> Tokenizer<R> {
+    /// Constructs a new tokenizer reading from the given reader.
+    pub fn new(rd: R) -> Tokenizer<R> {
+        Tokenizer {
+            rd,
+            buf: Vec::new(),
+            offset: 0,
+            remaining: Vec::new(),
+        }
+    }
+
+    /// Returns the number of bytes consumed so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the number of arrays and maps the next token is nested in.
+    pub fn depth(&self) -> usize {
+        self.remaining.len()
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.rd
+    }
+
+    /// Unwraps this tokenizer, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.rd
+    }
+
+    /// Attempts to read the next token.
+    ///
+    /// Returns `None` when the reader is exhausted at a value boundary, i.e. there are no open
+    /// arrays or maps left.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `ValueReadError` on any I/O error while reading either the marker
+    /// or the data, including unexpected EOF in the middle of a value.
+    ///
+    /// It also returns `ValueReadError::TypeMismatch` when encountering the reserved `0xc1` marker.
+    pub fn next_token<'a>(&'a mut self) -> Result<Option<Token<'a>>, ValueReadError> {
+        let head = match self.read_head()? {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+        let len = match head {
+            Head::Token(token) => return Ok(Some(token)),
+            Head::Str(len) | Head::Bin(len) | Head::Ext(_, len) => len,
+        };
+
+        // Read the payload in chunks instead of allocating the claimed length upfront.
+        let len = len as usize;
+        self.buf.clear();
+        while self.buf.len() < len {
+            let pos = self.buf.len();
+            let chunk = cmp::min(len - pos, CHUNK_SIZE);
+            self.buf.resize(pos + chunk, 0);
+            if let Err(err) = self.rd.read_exact_buf(&mut self.buf[pos..]) {
+                self.offset += pos as u64;
+                return Err(ValueReadError::InvalidDataRead(err));
+            }
+        }
+        self.offset += len as u64;
+
+        Ok(Some(payload_token(head, &self.buf)))
+    }
+
+    fn read_head(&mut self) -> Result<Option<Head>, ValueReadError> {
+        let mut byte = [0];
+        if let Err(err) = self.rd.read_exact_buf(&mut byte) {
+            if self.remaining.is_empty() && is_eof(&err) {
+                return Ok(None);
+            }
+            return Err(ValueReadError::InvalidMarkerRead(err));
+        }
+        self.offset += 1;
+
+        let marker = Marker::from_u8(byte[0]);
+        let mut header = [0; 5];
+        let header = &mut header[..marker.header_len()];
+        self.rd.read_exact_buf(header).map_err(ValueReadError::InvalidDataRead)?;
+
+        let payload = match marker.payload(header) {
+            Some(payload) => payload,
+            None => return Err(ValueReadError::TypeMismatch(marker)),
+        };
+
+        let (head, nread, count) = match (marker.family(), payload) {
+            (Family::Str, Payload::Bytes(len)) => (Head::Str(len), 0, 0),
+            (Family::Bin, Payload::Bytes(len)) => (Head::Bin(len), 0, 0),
+            // The type id of extensions is the last byte of the header.
+            (Family::Ext, Payload::Bytes(len)) => (Head::Ext(header[header.len() - 1] as i8, len), 0, 0),
+            (Family::Array, Payload::Values(count)) => {
+                (Head::Token(Token::ArrayStart(count as u32)), 0, count)
+            }
+            (Family::Map, Payload::Values(count)) => {
+                (Head::Token(Token::MapStart((count / 2) as u32)), 0, count)
+            }
+            (_, Payload::Bytes(len)) => (Head::Token(read_scalar(&mut self.rd, marker)?), len as u64, 0),
+            (_, Payload::Values(..)) => return Err(ValueReadError::TypeMismatch(marker)),
+        };
+        self.offset += header.len() as u64 + nread;
+
+        // The token just read is an item of the innermost open container, if any.
+        if let Some(last) = self.remaining.last_mut() {
+            *last -= 1;
+        }
+        if count > 0 {
+            self.remaining.push(count);
+        }
+        while self.remaining.last() == Some(&0) {
+            self.remaining.pop();
+        }
+
+        Ok(Some(head))
+    }
+}
+
+impl<'a> Tokenizer<&'a [u8]> {
+    /// Constructs a new tokenizer over the given slice.
+    ///
+    /// Tokens yielded by the `Iterator` implementation borrow payloads directly from the slice.
+    pub fn from_slice(buf: &'a [u8]) -> Tokenizer<&'a [u8]> {
+        Tokenizer::new(buf)
+    }
+
+    fn next_borrowed_token(&mut self) -> Result<Option<Token<'a>>, ValueReadError> {
+        let head = match self.read_head()? {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+        let len = match head {
+            Head::Token(token) => return Ok(Some(token)),
+            Head::Str(len) | Head::Bin(len) | Head::Ext(_, len) => len as usize,
+        };
+
+        let rd = self.rd;
+        if rd.len() < len {
+            self.offset += rd.len() as u64;
+            self.rd = &rd[rd.len()..];
+            return Err(ValueReadError::InvalidDataRead(unexpected_eof()));
+        }
+
+        let (data, tail) = rd.split_at(len);
+        self.offset += len as u64;
+        self.rd = tail;
+
+        Ok(Some(payload_token(head, data)))
+    }
+}
+
+impl<'a> Iterator for Tokenizer<&'a [u8]> {
+    type Item = Result<Token<'a>, ValueReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_borrowed_token() {
+            Ok(Some(token)) => Some(Ok(token)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+// Decodes the data of a nil, boolean, integer or float value with the given marker.
+fn read_scalar<R: RmpRead>(rd: &mut R, marker: Marker) -> Result<Token<'static>, ValueReadError> {
+    let token = match marker {
+        Marker::Null => Token::Nil,
+        Marker::True => Token::Bool(true),
+        Marker::False => Token::Bool(false),
+        Marker::FixPos(val) => Token::UInt(val as u64),
+        Marker::FixNeg(val) => Token::Int(val as i64),
+        Marker::U8 => Token::UInt(read_data_u8(rd)? as u64),
+        Marker::U16 => Token::UInt(read_data_u16(rd)? as u64),
+        Marker::U32 => Token::UInt(read_data_u32(rd)? as u64),
+        Marker::U64 => Token::UInt(read_data_u64(rd)?),
+        Marker::I8 => int(read_data_i8(rd)? as i64),
+        Marker::I16 => int(read_data_i16(rd)? as i64),
+        Marker::I32 => int(read_data_i32(rd)? as i64),
+        Marker::I64 => int(read_data_i64(rd)?),
+        Marker::F32 => Token::F32(read_data_f32(rd)?),
+        Marker::F64 => Token::F64(read_data_f64(rd)?),
+        marker => return Err(ValueReadError::TypeMismatch(marker)),
+    };
+
+    Ok(token)
+}
+
+fn int(val: i64) -> Token<'static> {
+    if val < 0 {
+        Token::Int(val)
+    } else {
+        Token::UInt(val as u64)
+    }
+}
+
+fn payload_token<'a>(head: Head, data: &'a [u8]) -> Token<'a> {
+    match head {
+        Head::Token(token) => token,
+        Head::Str(..) => Token::Str(data),
+        Head::Bin(..) => Token::Bin(data),
+        Head::Ext(ty, ..) => Token::Ext(ty, data),
+    }
+}
@@ -0,0 +1,255 @@
+#[cfg(feature = "std")]
+use std::error;
+use core::cmp;
+use core::fmt::{self, Display, Formatter};
+
+use alloc::vec::Vec;
+
+use {Marker, TypedArray};
+use super::{read_marker, read_data_i8, read_data_i16, read_data_i32, read_data_i64, read_data_u8,
+            read_data_u16, read_data_u32, read_data_u64, read_ext_meta, RmpRead, Error,
+            MarkerReadError, ValueReadError};
+
+/// An error which can occur when attempting to read a 128-bit integer or a typed array from the
+/// reader.
+#[derive(Debug)]
+pub enum TypedReadError {
+    /// Failed to read the marker.
+    InvalidMarkerRead(Error),
+    /// Failed to read the data.
+    InvalidDataRead(Error),
+    /// The type decoded is neither an extension nor, for integers, a native integer.
+    TypeMismatch(Marker),
+    /// The extension header isn't encoded in its shortest form.
+    NonCanonical(Marker),
+    /// The extension type id isn't the expected one.
+    ExtTypeMismatch(i8),
+    /// The extension data size doesn't match the type.
+    InvalidDataLength(u32),
+    /// The typed array tag names an unknown element type.
+    UnknownElemType(u8),
+    /// The integer doesn't fit in the expected type.
+    OutOfRange,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for TypedReadError {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            TypedReadError::InvalidMarkerRead(ref err) |
+            TypedReadError::InvalidDataRead(ref err) => Some(err),
+            TypedReadError::TypeMismatch(..) |
+            TypedReadError::NonCanonical(..) |
+            TypedReadError::ExtTypeMismatch(..) |
+            TypedReadError::InvalidDataLength(..) |
+            TypedReadError::UnknownElemType(..) |
+            TypedReadError::OutOfRange => None,
+        }
+    }
+}
+
+impl Display for TypedReadError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            TypedReadError::InvalidMarkerRead(..) => "failed to read MessagePack marker".fmt(f),
+            TypedReadError::InvalidDataRead(..) => "failed to read MessagePack data".fmt(f),
+            TypedReadError::TypeMismatch(..) => {
+                "the type decoded isn't match with the expected one".fmt(f)
+            }
+            TypedReadError::NonCanonical(marker) => {
+                write!(f, "the extension header isn't encoded in its shortest form, found {:?}",
+                       marker)
+            }
+            TypedReadError::ExtTypeMismatch(ty) => {
+                write!(f, "unexpected extension type {}", ty)
+            }
+            TypedReadError::InvalidDataLength(len) => {
+                write!(f, "invalid extension data length: {}", len)
+            }
+            TypedReadError::UnknownElemType(tag) => {
+                write!(f, "unknown typed array element type: {:#04x}", tag)
+            }
+            TypedReadError::OutOfRange => "out of range integral type conversion attempted".fmt(f),
+        }
+    }
+}
+
+impl From<MarkerReadError> for TypedReadError {
+    fn from(err: MarkerReadError) -> TypedReadError {
+        TypedReadError::InvalidMarkerRead(err.0)
+    }
+}
+
+impl From<ValueReadError> for TypedReadError {
+    fn from(err: ValueReadError) -> TypedReadError {
+        match err {
+            ValueReadError::InvalidMarkerRead(err) => TypedReadError::InvalidMarkerRead(err),
+            ValueReadError::InvalidDataRead(err) => TypedReadError::InvalidDataRead(err),
+            ValueReadError::TypeMismatch(marker) => TypedReadError::TypeMismatch(marker),
+            ValueReadError::NonCanonical(marker) => TypedReadError::NonCanonical(marker),
+        }
+    }
+}
+
+/// Attempts to read a 128-bit signed integer, written by `write_i128` with the given extension
+/// type, from the given reader.
+///
+/// All native MessagePack integers are accepted as well, so the reader keeps working if the writer
+/// switches to a narrower type.
+///
+/// # Errors
+///
+/// This function will return `TypedReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It also returns `TypedReadError::TypeMismatch` if the actual type is neither an integer nor a
+/// `fixext16` and `TypedReadError::ExtTypeMismatch` if the extension type isn't the given one.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::DEFAULT_I128_EXT_TYPE;
+///
+/// let mut buf = Vec::new();
+/// rmp::encode::write_i128(&mut buf, i128::min_value(), DEFAULT_I128_EXT_TYPE).unwrap();
+/// rmp::encode::write_sint(&mut buf, -42).unwrap();
+///
+/// let mut rd = &buf[..];
+/// assert_eq!(i128::min_value(), rmp::decode::read_i128(&mut rd, DEFAULT_I128_EXT_TYPE).unwrap());
+/// assert_eq!(-42, rmp::decode::read_i128(&mut rd, DEFAULT_I128_EXT_TYPE).unwrap());
+/// ```
+pub fn read_i128<R: RmpRead>(rd: &mut R, ty: i8) -> Result<i128, TypedReadError> {
+    match read_wide_int(rd, ty)? {
+        WideInt::Native(val) => Ok(val),
+        WideInt::Ext(buf) => Ok(i128::from_be_bytes(buf)),
+    }
+}
+
+/// Attempts to read a 128-bit unsigned integer, written by `write_u128` with the given extension
+/// type, from the given reader.
+///
+/// All native MessagePack integers are accepted as well, see `read_i128`.
+///
+/// # Errors
+///
+/// This function will return `TypedReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It also returns `TypedReadError::TypeMismatch` if the actual type is neither an integer nor a
+/// `fixext16`, `TypedReadError::ExtTypeMismatch` if the extension type isn't the given one and
+/// `TypedReadError::OutOfRange` if a native integer is negative.
+pub fn read_u128<R: RmpRead>(rd: &mut R, ty: i8) -> Result<u128, TypedReadError> {
+    match read_wide_int(rd, ty)? {
+        WideInt::Native(val) if val >= 0 => Ok(val as u128),
+        WideInt::Native(..) => Err(TypedReadError::OutOfRange),
+        WideInt::Ext(buf) => Ok(u128::from_be_bytes(buf)),
+    }
+}
+
+enum WideInt {
+    // Any native integer fits in `i128`.
+    Native(i128),
+    Ext([u8; 16]),
+}
+
+fn read_wide_int<R: RmpRead>(rd: &mut R, ext_ty: i8) -> Result<WideInt, TypedReadError> {
+    let val = match read_marker(rd)? {
+        Marker::FixPos(val) => val as i128,
+        Marker::FixNeg(val) => val as i128,
+        Marker::U8 => read_data_u8(rd)? as i128,
+        Marker::U16 => read_data_u16(rd)? as i128,
+        Marker::U32 => read_data_u32(rd)? as i128,
+        Marker::U64 => read_data_u64(rd)? as i128,
+        Marker::I8 => read_data_i8(rd)? as i128,
+        Marker::I16 => read_data_i16(rd)? as i128,
+        Marker::I32 => read_data_i32(rd)? as i128,
+        Marker::I64 => read_data_i64(rd)? as i128,
+        Marker::FixExt16 => {
+            let ty = read_data_i8(rd)?;
+            if ty != ext_ty {
+                return Err(TypedReadError::ExtTypeMismatch(ty));
+            }
+
+            let mut buf = [0; 16];
+            rd.read_exact_buf(&mut buf).map_err(TypedReadError::InvalidDataRead)?;
+            return Ok(WideInt::Ext(buf));
+        }
+        marker => return Err(TypedReadError::TypeMismatch(marker)),
+    };
+
+    Ok(WideInt::Native(val))
+}
+
+/// Attempts to read a typed array extension of the given type from the given reader.
+///
+/// # Errors
+///
+/// This function will return `TypedReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It also returns `TypedReadError::TypeMismatch` if the actual type is not an extension,
+/// `TypedReadError::ExtTypeMismatch` if the extension type id is not the given one and
+/// the errors of `read_typed_array_data` if the payload is malformed.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::{Endian, TypedArray};
+///
+/// let array = TypedArray::from_f64(&[0.5, 1.5], Endian::Little);
+///
+/// let mut buf = Vec::new();
+/// rmp::encode::write_typed_array(&mut buf, &array, TypedArray::DEFAULT_EXT_TYPE).unwrap();
+///
+/// let mut rd = &buf[..];
+/// assert_eq!(array, rmp::decode::read_typed_array(&mut rd, TypedArray::DEFAULT_EXT_TYPE).unwrap());
+/// ```
+pub fn read_typed_array<R: RmpRead>(rd: &mut R, ty: i8) -> Result<TypedArray, TypedReadError> {
+    let meta = read_ext_meta(rd)?;
+
+    if meta.typeid != ty {
+        return Err(TypedReadError::ExtTypeMismatch(meta.typeid));
+    }
+
+    read_typed_array_data(rd, meta.size)
+}
+
+/// Attempts to read the data part of a typed array extension, which is `len` bytes length, from
+/// the given reader.
+///
+/// This function is useful when the extension metadata has already been read, for example using
+/// `read_ext_meta`, or when the array is stored as a raw extension payload.
+///
+/// # Errors
+///
+/// This function will return `TypedReadError` on any I/O error while reading the data.
+///
+/// It also returns `TypedReadError::UnknownElemType` if the tag names an unknown element type and
+/// `TypedReadError::InvalidDataLength` if `len` doesn't fit the tag and a whole number of elements.
+pub fn read_typed_array_data<R: RmpRead>(rd: &mut R, len: u32) -> Result<TypedArray, TypedReadError> {
+    if len == 0 {
+        return Err(TypedReadError::InvalidDataLength(len));
+    }
+
+    let tag = read_data_u8(rd)?;
+    let (elem, endian) = match TypedArray::parse_tag(tag) {
+        Some(val) => val,
+        None => return Err(TypedReadError::UnknownElemType(tag)),
+    };
+
+    let size = (len - 1) as usize;
+    if size % elem.size() != 0 {
+        return Err(TypedReadError::InvalidDataLength(len));
+    }
+
+    // The length comes from the wire, so the buffer grows as the data actually arrives.
+    let mut data = Vec::with_capacity(cmp::min(size, 4096));
+    let mut chunk = [0; 256];
+    while data.len() < size {
+        let chunk = &mut chunk[..cmp::min(256, size - data.len())];
+        rd.read_exact_buf(chunk).map_err(TypedReadError::InvalidDataRead)?;
+        data.extend_from_slice(chunk);
+    }
+
+    Ok(TypedArray::from_bytes(elem, endian, data).expect("length must be checked"))
+}
@@ -0,0 +1,197 @@
+use alloc::vec::Vec;
+
+use {Family, Marker, Payload};
+use super::{DecodeLimits, ValidateError};
+
+/// A push-style decoder, which accepts the input in chunks of arbitrary size and splits it into
+/// complete MessagePack messages.
+///
+/// This is useful with non-blocking I/O, where a message can arrive in several reads, and a single
+/// read can contain several messages. Feed each chunk received with `feed`, then call
+/// `next_message` until it returns `None`, which means that the rest of the buffered data is an
+/// incomplete message.
+///
+/// The scan is resumable: markers, length prefixes and payloads split between chunks are never
+/// re-parsed from the beginning of the message, so each byte is examined once regardless of how
+/// the input is chunked.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_int, Unpacker};
+///
+/// // [1, 2], 300
+/// let mut unpacker = Unpacker::new();
+///
+/// unpacker.feed(&[0x92, 0x01]);
+/// assert_eq!(None, unpacker.next_message().unwrap());
+///
+/// unpacker.feed(&[0x02, 0xcd, 0x01]);
+/// assert_eq!(Some(&[0x92, 0x01, 0x02][..]), unpacker.next_message().unwrap());
+/// assert_eq!(None, unpacker.next_message().unwrap());
+///
+/// unpacker.feed(&[0x2c]);
+/// let mut msg = unpacker.next_message().unwrap().unwrap();
+/// assert_eq!(300, read_int::<u16, _>(&mut msg).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct Unpacker {
+    buf: Vec<u8>,
+    /// Offset of the current message in the buffer. Everything before it has been returned.
+    start: usize,
+    /// Offset of the next marker to scan. May exceed the buffer length while waiting for the rest
+    /// of a payload.
+    pos: u64,
+    /// Number of items left in each of the currently open containers of the current message.
+    remaining: Vec<u64>,
+    limits: DecodeLimits,
+    error: Option<ValidateError>,
+}
+
+impl Unpacker {
+    /// Constructs a new `Unpacker` with the default limits.
+    pub fn new() -> Unpacker {
+        Unpacker::with_limits(DecodeLimits::default())
+    }
+
+    /// Constructs a new `Unpacker`, which rejects messages exceeding the given limits.
+    ///
+    /// Declared lengths are checked as soon as their prefix arrives, so the `max_data_len` and
+    /// `max_total_len` limits also bound the amount of data buffered for a single message.
+    pub fn with_limits(limits: DecodeLimits) -> Unpacker {
+        Unpacker {
+            buf: Vec::new(),
+            start: 0,
+            pos: 0,
+            remaining: Vec::new(),
+            limits: limits,
+            error: None,
+        }
+    }
+
+    /// Returns the limits this unpacker checks messages against.
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    /// Appends the given chunk of data to the internal buffer.
+    ///
+    /// The space taken by messages already returned by `next_message` is reclaimed here.
+    pub fn feed(&mut self, data: &[u8]) {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.pos -= self.start as u64;
+            self.start = 0;
+        }
+
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Returns the number of buffered bytes, which have not been returned as part of a message yet.
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len() - self.start
+    }
+
+    /// Attempts to extract the next complete message from the buffered data, returning the bytes
+    /// it is encoded with.
+    ///
+    /// Returns `Ok(None)` if there is no complete message buffered, in which case more data should
+    /// be fed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValidateError` if the message contains the reserved marker or exceeds the limits,
+    /// with the offset relative to the start of the message. The stream can't be resynchronized
+    /// after that, so the same error is returned by all subsequent calls.
+    pub fn next_message(&mut self) -> Result<Option<&[u8]>, ValidateError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        match self.scan() {
+            Ok(true) => {
+                let start = self.start;
+                self.start = self.pos as usize;
+                Ok(Some(&self.buf[start..self.start]))
+            }
+            Ok(false) => Ok(None),
+            Err(err) => {
+                self.error = Some(err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Advances the scan position as far as the buffered data allows, returning whether the current
+    /// message is complete.
+    fn scan(&mut self) -> Result<bool, ValidateError> {
+        loop {
+            let len = self.buf.len() as u64;
+            if self.pos > len {
+                // Waiting for the rest of a payload.
+                return Ok(false);
+            }
+            if self.pos > self.start as u64 && self.remaining.is_empty() {
+                return Ok(true);
+            }
+            if self.pos == len {
+                return Ok(false);
+            }
+
+            // The position is within the buffer at this point.
+            let pos = self.pos as usize;
+            let offset = pos - self.start;
+            let marker = Marker::from_u8(self.buf[pos]);
+
+            let header = marker.header_len();
+            let payload = match marker.payload(&self.buf[pos + 1..]) {
+                Some(payload) => payload,
+                None if marker == Marker::Reserved => {
+                    return Err(ValidateError::ReservedMarker(offset));
+                }
+                None => return Ok(false),
+            };
+
+            // Only the declared lengths are limited, not the size of scalars.
+            let (size, payload, count) = match (marker.family(), payload) {
+                (Family::Map, Payload::Values(count)) => (count / 2, 0, count),
+                (_, Payload::Values(count)) => (count, 0, count),
+                (Family::Str, Payload::Bytes(len)) |
+                (Family::Bin, Payload::Bytes(len)) |
+                (Family::Ext, Payload::Bytes(len)) => (len as u64, len as u64, 0),
+                (_, Payload::Bytes(len)) => (0, len as u64, 0),
+            };
+
+            if !self.limits.allows_len(marker, size as u32) {
+                return Err(ValidateError::LengthLimitExceeded(offset));
+            }
+            if offset as u64 + 1 + header as u64 + payload > self.limits.max_total_len {
+                return Err(ValidateError::LengthLimitExceeded(offset));
+            }
+
+            if let Some(last) = self.remaining.last_mut() {
+                *last -= 1;
+            }
+            if marker.family() == Family::Array || marker.family() == Family::Map {
+                // Empty containers count towards the depth as well.
+                if self.remaining.len() >= self.limits.max_depth {
+                    return Err(ValidateError::DepthLimitExceeded(offset));
+                }
+                if count > 0 {
+                    self.remaining.push(count);
+                }
+            }
+            while self.remaining.last() == Some(&0) {
+                self.remaining.pop();
+            }
+
+            self.pos += 1 + header as u64 + payload;
+        }
+    }
+}
+
+impl Default for Unpacker {
+    fn default() -> Unpacker {
+        Unpacker::new()
+    }
+}
@@ -0,0 +1,195 @@
+#[cfg(feature = "std")]
+use std::error;
+use core::fmt::{self, Display, Formatter};
+
+use alloc::vec::Vec;
+
+use {Family, Marker, Payload};
+use super::DecodeLimits;
+
+/// An error which can occur when validating a buffer of MessagePack data.
+///
+/// Each variant holds the byte offset in the buffer where the problem was detected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidateError {
+    /// The buffer ended in the middle of a marker or its length prefix, or before the expected
+    /// number of messages was found.
+    Truncated(usize),
+    /// The reserved `0xc1` marker was found.
+    ReservedMarker(usize),
+    /// Arrays and maps are nested deeper than allowed.
+    DepthLimitExceeded(usize),
+    /// A string, binary, extension, array or map is longer than allowed, or the message grows
+    /// larger than allowed with the value at the offset.
+    LengthLimitExceeded(usize),
+    /// A declared length doesn't fit in the rest of the buffer.
+    LengthOverflow(usize),
+    /// The buffer contains more data after the expected number of messages.
+    TrailingBytes(usize),
+}
+
+impl ValidateError {
+    /// Returns the byte offset where the problem was detected.
+    pub fn offset(&self) -> usize {
+        match *self {
+            ValidateError::Truncated(offset) |
+            ValidateError::ReservedMarker(offset) |
+            ValidateError::DepthLimitExceeded(offset) |
+            ValidateError::LengthLimitExceeded(offset) |
+            ValidateError::LengthOverflow(offset) |
+            ValidateError::TrailingBytes(offset) => offset,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ValidateError {}
+
+impl Display for ValidateError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let reason = match *self {
+            ValidateError::Truncated(..) => "unexpected end of data",
+            ValidateError::ReservedMarker(..) => "reserved marker",
+            ValidateError::DepthLimitExceeded(..) => "depth limit exceeded",
+            ValidateError::LengthLimitExceeded(..) => "length limit exceeded",
+            ValidateError::LengthOverflow(..) => "declared length exceeds the remaining data",
+            ValidateError::TrailingBytes(..) => "trailing bytes after the last message",
+        };
+
+        write!(f, "{} at byte {}", reason, self.offset())
+    }
+}
+
+/// Checks that the given buffer holds exactly one well-formed MessagePack value, returning its
+/// length in bytes.
+///
+/// The buffer is only scanned, so it's safe to call this function on untrusted input before
+/// handing it to a decoder. Declared lengths are checked against both the given limits and the
+/// size of the buffer before anything else is done with them.
+///
+/// # Errors
+///
+/// Returns `ValidateError` describing the first problem found along with its offset. If the buffer
+/// contains a well-formed value followed by more data, `ValidateError::TrailingBytes` holds the
+/// offset where the value ends.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{validate, DecodeLimits, ValidateError};
+///
+/// let limits = DecodeLimits::default();
+///
+/// assert_eq!(Ok(3), validate(&[0x92, 0x01, 0x02], &limits));
+/// assert_eq!(Err(ValidateError::Truncated(2)), validate(&[0xdc, 0x00], &limits));
+/// assert_eq!(Err(ValidateError::TrailingBytes(1)), validate(&[0xc0, 0xc0], &limits));
+/// ```
+pub fn validate(buf: &[u8], limits: &DecodeLimits) -> Result<usize, ValidateError> {
+    validate_messages(buf, 1, limits)
+}
+
+/// Checks that the given buffer holds exactly `count` well-formed MessagePack values one after
+/// another, returning the length in bytes of the first one.
+///
+/// # Errors
+///
+/// Fails in the same cases as `validate` does.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{validate_messages, DecodeLimits};
+///
+/// let buf = [0x92, 0x01, 0x02, 0xa1, 0x61];
+///
+/// assert_eq!(Ok(3), validate_messages(&buf, 2, &DecodeLimits::default()));
+/// ```
+pub fn validate_messages(buf: &[u8], count: usize, limits: &DecodeLimits) ->
+    Result<usize, ValidateError>
+{
+    let mut first = 0;
+    let mut pos = 0;
+
+    for idx in 0..count {
+        pos = validate_value(buf, pos, limits)?;
+        if idx == 0 {
+            first = pos;
+        }
+    }
+
+    if pos == buf.len() {
+        Ok(first)
+    } else {
+        Err(ValidateError::TrailingBytes(pos))
+    }
+}
+
+/// Validates a single value starting at `pos`, returning the offset right after it.
+fn validate_value(buf: &[u8], mut pos: usize, limits: &DecodeLimits) ->
+    Result<usize, ValidateError>
+{
+    let start = pos;
+    // Number of items left in each of the currently open containers, bounded by the depth limit.
+    let mut remaining: Vec<u64> = Vec::new();
+
+    loop {
+        let offset = pos;
+        let marker = match buf.get(pos) {
+            Some(&byte) => Marker::from_u8(byte),
+            None => return Err(ValidateError::Truncated(pos)),
+        };
+        pos += 1;
+
+        let payload = match marker.payload(&buf[pos..]) {
+            Some(payload) => payload,
+            None if marker == Marker::Reserved => return Err(ValidateError::ReservedMarker(offset)),
+            None => return Err(ValidateError::Truncated(buf.len())),
+        };
+        pos += marker.header_len();
+
+        // Only the declared lengths are limited, not the size of scalars.
+        let (len, payload, count) = match (marker.family(), payload) {
+            (Family::Map, Payload::Values(count)) => ((count / 2) as u32, 0, count),
+            (_, Payload::Values(count)) => (count as u32, 0, count),
+            (Family::Str, Payload::Bytes(len)) |
+            (Family::Bin, Payload::Bytes(len)) |
+            (Family::Ext, Payload::Bytes(len)) => (len, len as u64, 0),
+            (_, Payload::Bytes(len)) => (0, len as u64, 0),
+        };
+
+        if !limits.allows_len(marker, len) {
+            return Err(ValidateError::LengthLimitExceeded(offset));
+        }
+
+        // Every item of a container takes at least one byte.
+        let left = (buf.len() - pos) as u64;
+        if payload > left || count > left {
+            return Err(ValidateError::LengthOverflow(offset));
+        }
+        pos += payload as usize;
+
+        if (pos - start) as u64 > limits.max_total_len {
+            return Err(ValidateError::LengthLimitExceeded(offset));
+        }
+
+        if let Some(last) = remaining.last_mut() {
+            *last -= 1;
+        }
+        if marker.family() == Family::Array || marker.family() == Family::Map {
+            // Empty containers count towards the depth as well.
+            if remaining.len() >= limits.max_depth {
+                return Err(ValidateError::DepthLimitExceeded(offset));
+            }
+            if count > 0 {
+                remaining.push(count);
+            }
+        }
+        while remaining.last() == Some(&0) {
+            remaining.pop();
+        }
+
+        if remaining.is_empty() {
+            return Ok(pos);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Asynchronous counterparts of the encoding functions, writing to `futures_io::AsyncWrite`.
+//!
+//! The functions have the same names, arguments and errors as their blocking versions, but return
+//! futures, which never block the executor thread while the writer is not ready. The header is
+//! encoded in advance into a small buffer on the stack and written together with the data once the
+//! future is polled. Interrupted writes are retried.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate futures_executor;
+//! extern crate rmp;
+//!
+//! use futures_executor::block_on;
+//! use rmp::encode::asynchronous::{write_array_len, write_str};
+//!
+//! # fn main() {
+//! let mut buf = Vec::new();
+//!
+//! block_on(write_array_len(&mut buf, 1)).unwrap();
+//! block_on(write_str(&mut buf, "le message")).unwrap();
+//!
+//! assert_eq!([0x91, 0xaa], buf[..2]);
+//! # }
+//! ```
+
+use std::io;
+use std::pin::Pin;
+use std::future::Future;
+use std::task::{Context, Poll};
+
+use futures_io::AsyncWrite;
+use futures_util::FutureExt;
+
+use Marker;
+use encode;
+use super::ValueWriteError;
+
+/// Encodes and attempts to write a nil value into the given write.
+///
+/// See `encode::write_nil` for details.
+pub fn write_nil<'a, W>(wr: &'a mut W) -> impl Future<Output = Result<(), io::Error>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    WriteAll::new(wr, head(|wr| encode::write_nil(wr)), &[][..]).map(|res| res.map_err(into_io))
+}
+
+/// Encodes and attempts to write a bool value into the given write.
+///
+/// See `encode::write_bool` for details.
+pub fn write_bool<'a, W>(wr: &'a mut W, val: bool) -> impl Future<Output = Result<(), io::Error>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    WriteAll::new(wr, head(|wr| encode::write_bool(wr, val)), &[][..]).map(|res| res.map_err(into_io))
+}
+
+/// Encodes and attempts to write an unsigned integer value using the most efficient
+/// representation, returning the marker used.
+///
+/// See `encode::write_uint` for details.
+pub fn write_uint<'a, W>(wr: &'a mut W, val: u64) -> impl Future<Output = Result<Marker, ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    write_head(wr, head(|wr| encode::write_uint(wr, val)))
+}
+
+/// Encodes and attempts to write a signed integer value using the most efficient representation,
+/// returning the marker used.
+///
+/// See `encode::write_sint` for details.
+pub fn write_sint<'a, W>(wr: &'a mut W, val: i64) -> impl Future<Output = Result<Marker, ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    write_head(wr, head(|wr| encode::write_sint(wr, val)))
+}
+
+/// Encodes and attempts to write an `f32` value.
+///
+/// See `encode::write_f32` for details.
+pub fn write_f32<'a, W>(wr: &'a mut W, val: f32) -> impl Future<Output = Result<(), ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    WriteAll::new(wr, head(|wr| encode::write_f32(wr, val)), &[][..])
+}
+
+/// Encodes and attempts to write an `f64` value.
+///
+/// See `encode::write_f64` for details.
+pub fn write_f64<'a, W>(wr: &'a mut W, val: f64) -> impl Future<Output = Result<(), ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    WriteAll::new(wr, head(|wr| encode::write_f64(wr, val)), &[][..])
+}
+
+/// Encodes and attempts to write the most efficient string length implementation, returning the
+/// marker used.
+///
+/// See `encode::write_str_len` for details.
+pub fn write_str_len<'a, W>(wr: &'a mut W, len: u32) -> impl Future<Output = Result<Marker, ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    write_head(wr, head(|wr| encode::write_str_len(wr, len)))
+}
+
+/// Encodes and attempts to write the most efficient string representation.
+///
+/// See `encode::write_str` for details.
+pub fn write_str<'a, W>(wr: &'a mut W, data: &'a str) -> impl Future<Output = Result<(), ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    WriteAll::new(wr, head(|wr| encode::write_str_len(wr, data.len() as u32)), data.as_bytes())
+}
+
+/// Encodes and attempts to write the most efficient binary array length implementation, returning
+/// the marker used.
+///
+/// See `encode::write_bin_len` for details.
+pub fn write_bin_len<'a, W>(wr: &'a mut W, len: u32) -> impl Future<Output = Result<Marker, ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    write_head(wr, head(|wr| encode::write_bin_len(wr, len)))
+}
+
+/// Encodes and attempts to write the most efficient binary implementation.
+///
+/// See `encode::write_bin` for details.
+pub fn write_bin<'a, W>(wr: &'a mut W, data: &'a [u8]) -> impl Future<Output = Result<(), ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    WriteAll::new(wr, head(|wr| encode::write_bin_len(wr, data.len() as u32)), data)
+}
+
+/// Encodes and attempts to write the most efficient array length implementation, returning the
+/// marker used.
+///
+/// See `encode::write_array_len` for details.
+pub fn write_array_len<'a, W>(wr: &'a mut W, len: u32) -> impl Future<Output = Result<Marker, ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    write_head(wr, head(|wr| encode::write_array_len(wr, len)))
+}
+
+/// Encodes and attempts to write the most efficient map length implementation, returning the
+/// marker used.
+///
+/// See `encode::write_map_len` for details.
+pub fn write_map_len<'a, W>(wr: &'a mut W, len: u32) -> impl Future<Output = Result<Marker, ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    write_head(wr, head(|wr| encode::write_map_len(wr, len)))
+}
+
+/// Encodes and attempts to write the most efficient ext metadata implementation, returning the
+/// marker used.
+///
+/// See `encode::write_ext_meta` for details.
+pub fn write_ext_meta<'a, W>(wr: &'a mut W, len: u32, ty: i8) -> impl Future<Output = Result<Marker, ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    write_head(wr, head(|wr| encode::write_ext_meta(wr, len, ty)))
+}
+
+/// Attempts to write the given buffer with already encoded data entirely, taking ownership of it.
+///
+/// This is useful for sending values encoded using the blocking functions or any higher-level
+/// library into a memory buffer.
+pub fn write_all<'a, W, B>(wr: &'a mut W, buf: B) -> impl Future<Output = Result<(), io::Error>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized,
+          B: AsRef<[u8]> + Unpin + 'a
+{
+    WriteAll::new(wr, ([0; 9], 0), buf).map(|res| res.map_err(into_io))
+}
+
+// Encodes a header into a buffer on the stack, returning it along with the number of bytes used.
+fn head<F, T, E>(f: F) -> ([u8; 9], usize)
+    where F: FnOnce(&mut &mut [u8]) -> Result<T, E>
+{
+    let mut buf = [0; 9];
+    let len = {
+        let mut wr = &mut buf[..];
+        match f(&mut wr) {
+            Ok(..) => 9 - wr.len(),
+            Err(..) => unreachable!("header must fit in 9 bytes"),
+        }
+    };
+
+    (buf, len)
+}
+
+fn write_head<'a, W>(wr: &'a mut W, head: ([u8; 9], usize)) -> impl Future<Output = Result<Marker, ValueWriteError>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    let marker = Marker::from_u8(head.0[0]);
+    WriteAll::new(wr, head, &[][..]).map(move |res| res.map(|()| marker))
+}
+
+fn into_io(err: ValueWriteError) -> io::Error {
+    match err {
+        ValueWriteError::InvalidMarkerWrite(err) |
+        ValueWriteError::InvalidDataWrite(err) => err,
+        ValueWriteError::ReservedExtType(..) => unreachable!("only raw bytes are written"),
+    }
+}
+
+// Writes an encoded header followed by the data.
+struct WriteAll<'a, W: ?Sized + 'a, B> {
+    wr: &'a mut W,
+    head: [u8; 9],
+    head_len: usize,
+    data: B,
+    pos: usize,
+}
+
+impl<'a, W: ?Sized + 'a, B> WriteAll<'a, W, B> {
+    fn new(wr: &'a mut W, head: ([u8; 9], usize), data: B) -> Self {
+        WriteAll {
+            wr: wr,
+            head: head.0,
+            head_len: head.1,
+            data: data,
+            pos: 0,
+        }
+    }
+}
+
+impl<'a, W, B> Future for WriteAll<'a, W, B>
+    where W: AsyncWrite + Unpin + ?Sized,
+          B: AsRef<[u8]> + Unpin
+{
+    type Output = Result<(), ValueWriteError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            let buf = if this.pos < this.head_len {
+                &this.head[this.pos..this.head_len]
+            } else {
+                &this.data.as_ref()[this.pos - this.head_len..]
+            };
+
+            if buf.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let res = match Pin::new(&mut *this.wr).poll_write(cx, buf) {
+                Poll::Ready(Ok(0)) => Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer")),
+                Poll::Ready(Ok(n)) => Ok(n),
+                Poll::Ready(Err(ref err)) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Poll::Ready(Err(err)) => Err(err),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match res {
+                Ok(n) => this.pos += n,
+                Err(err) if this.pos == 0 && this.head_len > 0 => {
+                    return Poll::Ready(Err(ValueWriteError::InvalidMarkerWrite(err)));
+                }
+                Err(err) => return Poll::Ready(Err(ValueWriteError::InvalidDataWrite(err))),
+            }
+        }
+    }
+}
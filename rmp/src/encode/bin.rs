@@ -1,7 +1,5 @@
-use std::io::Write;
-
 use Marker;
-use encode::{write_marker, ValueWriteError};
+use encode::{write_marker, RmpWrite, ValueWriteError};
 use super::{write_data_u8, write_data_u16, write_data_u32};
 
 /// Encodes and attempts to write the most efficient binary array length implementation to the given
@@ -14,7 +12,7 @@ use super::{write_data_u8, write_data_u16, write_data_u32};
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_bin_len<W: Write>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
+pub fn write_bin_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
     if len < 256 {
         try!(write_marker(wr, Marker::Bin8));
         try!(write_data_u8(wr, len as u8));
@@ -37,7 +35,7 @@ pub fn write_bin_len<W: Write>(wr: &mut W, len: u32) -> Result<Marker, ValueWrit
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
 // TODO: Docs, range check, example, visibility.
-pub fn write_bin<W: Write>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError> {
+pub fn write_bin<W: RmpWrite>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError> {
     try!(write_bin_len(wr, data.len() as u32));
-    wr.write_all(data).map_err(ValueWriteError::InvalidDataWrite)
+    wr.write_bytes(data).map_err(ValueWriteError::InvalidDataWrite)
 }
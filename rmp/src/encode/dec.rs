@@ -1,7 +1,5 @@
-use std::io::Write;
-
 use Marker;
-use encode::ValueWriteError;
+use encode::{RmpWrite, ValueWriteError};
 use super::{write_marker, write_data_f32, write_data_f64};
 
 /// Encodes and attempts to write an `f32` value as a 5-byte sequence into the given write.
@@ -12,7 +10,7 @@ use super::{write_marker, write_data_f32, write_data_f64};
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_f32<W: Write>(wr: &mut W, val: f32) -> Result<(), ValueWriteError> {
+pub fn write_f32<W: RmpWrite>(wr: &mut W, val: f32) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::F32));
     try!(write_data_f32(wr, val));
     Ok(())
@@ -26,7 +24,7 @@ pub fn write_f32<W: Write>(wr: &mut W, val: f32) -> Result<(), ValueWriteError>
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_f64<W: Write>(wr: &mut W, val: f64) -> Result<(), ValueWriteError> {
+pub fn write_f64<W: RmpWrite>(wr: &mut W, val: f64) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::F64));
     try!(write_data_f64(wr, val));
     Ok(())
@@ -0,0 +1,58 @@
+use {Marker, Timestamp};
+use encode::{RmpWrite, ValueWriteError};
+use super::{write_ext_meta, write_data_u32, write_data_u64, write_data_i64};
+
+/// Encodes and attempts to write the given timestamp into the given write using the most compact
+/// timestamp format, returning the marker used.
+///
+/// According to the MessagePack specification, a timestamp is an extension with `-1` type id,
+/// which is stored as:
+///
+/// - timestamp 32 (`fixext4`) if the nanoseconds part is zero and the seconds fit in `u32`.
+/// - timestamp 64 (`fixext8`) if the seconds fit in 34-bit unsigned integer.
+/// - timestamp 96 (`ext8` with 12 bytes of data) otherwise.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::{Marker, Timestamp};
+///
+/// let mut buf = Vec::new();
+/// let marker = rmp::encode::write_timestamp(&mut buf, Timestamp::from_secs(1514862245)).unwrap();
+///
+/// assert_eq!(Marker::FixExt4, marker);
+/// assert_eq!([0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5], buf[..]);
+/// ```
+pub fn write_timestamp<W: RmpWrite>(wr: &mut W, ts: Timestamp) -> Result<Marker, ValueWriteError> {
+    let marker = write_ext_meta(wr, ts.data_len(), Timestamp::EXT_TYPE)?;
+    write_timestamp_data(wr, ts)?;
+
+    Ok(marker)
+}
+
+/// Encodes and attempts to write the data part of the given timestamp into the given write
+/// without any extension metadata.
+///
+/// The number of bytes written is equal to `Timestamp::data_len()`. This function is useful when
+/// the timestamp needs to be stored as a raw extension payload, for example in a `Value`.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing the data.
+pub fn write_timestamp_data<W: RmpWrite>(wr: &mut W, ts: Timestamp) -> Result<(), ValueWriteError> {
+    match ts.data_len() {
+        4 => write_data_u32(wr, ts.secs() as u32)?,
+        8 => write_data_u64(wr, (ts.nsecs() as u64) << 34 | ts.secs() as u64)?,
+        _ => {
+            write_data_u32(wr, ts.nsecs())?;
+            write_data_i64(wr, ts.secs())?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use Timestamp;
+
+/// Returns the number of bytes `write_uint` uses to encode the given value.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::uint_len;
+///
+/// assert_eq!(1, uint_len(42));
+/// assert_eq!(3, uint_len(300));
+/// assert_eq!(9, uint_len(u64::MAX));
+/// ```
+pub fn uint_len(val: u64) -> usize {
+    if val < 128 {
+        1
+    } else if val < 256 {
+        2
+    } else if val < 65536 {
+        3
+    } else if val < 4294967296 {
+        5
+    } else {
+        9
+    }
+}
+
+/// Returns the number of bytes `write_sint` uses to encode the given value.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::sint_len;
+///
+/// assert_eq!(1, sint_len(-32));
+/// assert_eq!(2, sint_len(-33));
+/// assert_eq!(3, sint_len(300));
+/// ```
+pub fn sint_len(val: i64) -> usize {
+    if val >= 0 {
+        uint_len(val as u64)
+    } else if val >= -32 {
+        1
+    } else if val >= -128 {
+        2
+    } else if val >= -32768 {
+        3
+    } else if val >= -2147483648 {
+        5
+    } else {
+        9
+    }
+}
+
+/// Returns the number of bytes `write_str_len` uses to encode a string header of the given length.
+pub fn str_header_len(len: u32) -> usize {
+    if len < 32 {
+        1
+    } else if len < 256 {
+        2
+    } else if len < 65536 {
+        3
+    } else {
+        5
+    }
+}
+
+/// Returns the number of bytes `write_str` uses to encode the given string, including its header.
+///
+/// The result is meaningless for strings longer than `u32::MAX` bytes, which can't be encoded at
+/// all.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::str_len;
+///
+/// assert_eq!(11, str_len("le message"));
+/// ```
+pub fn str_len(data: &str) -> usize {
+    str_header_len(data.len() as u32) + data.len()
+}
+
+/// Returns the number of bytes `write_bin_len` uses to encode a binary header of the given length.
+pub fn bin_header_len(len: u32) -> usize {
+    if len < 256 {
+        2
+    } else if len < 65536 {
+        3
+    } else {
+        5
+    }
+}
+
+/// Returns the number of bytes `write_bin` uses to encode the given data, including its header.
+///
+/// The result is meaningless for data longer than `u32::MAX` bytes, which can't be encoded at all.
+pub fn bin_len(data: &[u8]) -> usize {
+    bin_header_len(data.len() as u32) + data.len()
+}
+
+/// Returns the number of bytes `write_array_len` uses to encode an array header of the given
+/// length.
+pub fn array_header_len(len: u32) -> usize {
+    if len < 16 {
+        1
+    } else if len < 65536 {
+        3
+    } else {
+        5
+    }
+}
+
+/// Returns the number of bytes `write_map_len` uses to encode a map header of the given length.
+pub fn map_header_len(len: u32) -> usize {
+    array_header_len(len)
+}
+
+/// Returns the number of bytes `write_ext_meta` uses to encode an extension header of the given
+/// data length, including the type id.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::ext_header_len;
+///
+/// assert_eq!(2, ext_header_len(4));
+/// assert_eq!(3, ext_header_len(5));
+/// ```
+pub fn ext_header_len(len: u32) -> usize {
+    match len {
+        1 | 2 | 4 | 8 | 16 => 2,
+        len if len < 256 => 3,
+        len if len < 65536 => 4,
+        _ => 6,
+    }
+}
+
+/// Returns the number of bytes `write_timestamp` uses to encode the given timestamp, including its
+/// extension header.
+pub fn timestamp_len(ts: Timestamp) -> usize {
+    ext_header_len(ts.data_len()) + ts.data_len() as usize
+}
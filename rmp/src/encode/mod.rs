@@ -1,5 +1,6 @@
 //! Provides various functions and structs for MessagePack encoding.
 
+mod write;
 mod sint;
 mod uint;
 mod dec;
@@ -8,24 +9,57 @@ mod bin;
 mod vec;
 mod map;
 mod ext;
-
+mod len;
+mod slice;
+mod patch;
+mod typed;
+#[cfg(feature = "std")]
+mod vectored;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+
+pub use self::write::RmpWrite;
+#[cfg(not(feature = "std"))]
+pub use self::write::BytesWriteError;
 pub use self::sint::{write_nfix, write_i8, write_i16, write_i32, write_i64, write_sint};
 pub use self::uint::{write_pfix, write_u8, write_u16, write_u32, write_u64, write_uint};
 pub use self::dec::{write_f32, write_f64};
-pub use self::str::{write_str_len, write_str};
+pub use self::str::{write_str_len, write_str, write_raw_len, write_raw};
 pub use self::bin::{write_bin_len, write_bin};
-
+pub use self::ext::{write_timestamp, write_timestamp_data};
+pub use self::typed::{write_i128, write_u128, write_typed_array, write_typed_array_data};
+pub use self::len::{uint_len, sint_len, str_header_len, str_len, bin_header_len, bin_len,
+                    array_header_len, map_header_len, ext_header_len, timestamp_len};
+pub use self::slice::{write_nil_to_slice, write_bool_to_slice, write_uint_to_slice,
+                      write_sint_to_slice, write_f32_to_slice, write_f64_to_slice,
+                      write_str_to_slice, write_bin_to_slice, write_array_len_to_slice,
+                      write_map_len_to_slice, write_ext_meta_to_slice, write_timestamp_to_slice,
+                      SliceWriteError};
+pub use self::patch::{LenPlaceholder, push_array_len_placeholder, push_map_len_placeholder};
+#[cfg(feature = "std")]
+pub use self::patch::{write_array_len_placeholder, write_map_len_placeholder};
+#[cfg(feature = "std")]
+pub use self::vectored::{write_bin_vectored, write_str_vectored, write_raw_vectored};
+
+#[cfg(feature = "std")]
 use std::error;
-use std::fmt::{self, Display, Formatter};
-use std::io::Write;
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::io::ErrorKind;
 
-use byteorder::{self, WriteBytesExt};
+use byteorder::{BigEndian, ByteOrder};
 
 use Marker;
+use decode::{ext_type_kind, ExtTypeKind};
 
 /// The error type for I/O operations of the `Write` and associated traits.
+#[cfg(feature = "std")]
 pub type Error = ::std::io::Error;
 
+/// The error type for I/O operations of the `RmpWrite` trait.
+#[cfg(not(feature = "std"))]
+pub type Error = BytesWriteError;
+
 // An error returned from the `write_marker` and `write_fixval` functions.
 struct MarkerWriteError(Error);
 
@@ -44,8 +78,8 @@ impl From<MarkerWriteError> for Error {
 }
 
 /// Attempts to write the given marker into the writer.
-fn write_marker<W: Write>(wr: &mut W, marker: Marker) -> Result<(), MarkerWriteError> {
-    wr.write_u8(marker.to_u8()).map_err(MarkerWriteError)
+fn write_marker<W: RmpWrite>(wr: &mut W, marker: Marker) -> Result<(), MarkerWriteError> {
+    wr.write_bytes(&[marker.to_u8()]).map_err(MarkerWriteError)
 }
 
 /// An error returned from primitive values write functions.
@@ -80,7 +114,7 @@ impl From<DataWriteError> for Error {
 ///
 /// assert_eq!(vec![0xc0], buf);
 /// ```
-pub fn write_nil<W: Write>(wr: &mut W) -> Result<(), Error> {
+pub fn write_nil<W: RmpWrite>(wr: &mut W) -> Result<(), Error> {
     write_marker(wr, Marker::Null).map_err(From::from)
 }
 
@@ -93,7 +127,7 @@ pub fn write_nil<W: Write>(wr: &mut W) -> Result<(), Error> {
 ///
 /// Each call to this function may generate an I/O error indicating that the operation could not be
 /// completed.
-pub fn write_bool<W: Write>(wr: &mut W, val: bool) -> Result<(), Error> {
+pub fn write_bool<W: RmpWrite>(wr: &mut W, val: bool) -> Result<(), Error> {
     let marker = if val {
         Marker::True
     } else {
@@ -103,44 +137,60 @@ pub fn write_bool<W: Write>(wr: &mut W, val: bool) -> Result<(), Error> {
     write_marker(wr, marker).map_err(From::from)
 }
 
-fn write_data_u8<W: Write>(wr: &mut W, val: u8) -> Result<(), DataWriteError> {
-    wr.write_u8(val).map_err(DataWriteError)
+fn write_data_u8<W: RmpWrite>(wr: &mut W, val: u8) -> Result<(), DataWriteError> {
+    wr.write_bytes(&[val]).map_err(DataWriteError)
 }
 
-fn write_data_u16<W: Write>(wr: &mut W, val: u16) -> Result<(), DataWriteError> {
-    wr.write_u16::<byteorder::BigEndian>(val).map_err(DataWriteError)
+fn write_data_u16<W: RmpWrite>(wr: &mut W, val: u16) -> Result<(), DataWriteError> {
+    let mut buf = [0; 2];
+    BigEndian::write_u16(&mut buf, val);
+    wr.write_bytes(&buf).map_err(DataWriteError)
 }
 
-fn write_data_u32<W: Write>(wr: &mut W, val: u32) -> Result<(), DataWriteError> {
-    wr.write_u32::<byteorder::BigEndian>(val).map_err(DataWriteError)
+fn write_data_u32<W: RmpWrite>(wr: &mut W, val: u32) -> Result<(), DataWriteError> {
+    let mut buf = [0; 4];
+    BigEndian::write_u32(&mut buf, val);
+    wr.write_bytes(&buf).map_err(DataWriteError)
 }
 
-fn write_data_u64<W: Write>(wr: &mut W, val: u64) -> Result<(), DataWriteError> {
-    wr.write_u64::<byteorder::BigEndian>(val).map_err(DataWriteError)
+fn write_data_u64<W: RmpWrite>(wr: &mut W, val: u64) -> Result<(), DataWriteError> {
+    let mut buf = [0; 8];
+    BigEndian::write_u64(&mut buf, val);
+    wr.write_bytes(&buf).map_err(DataWriteError)
 }
 
-fn write_data_i8<W: Write>(wr: &mut W, val: i8) -> Result<(), DataWriteError> {
-    wr.write_i8(val).map_err(DataWriteError)
+fn write_data_i8<W: RmpWrite>(wr: &mut W, val: i8) -> Result<(), DataWriteError> {
+    write_data_u8(wr, val as u8)
 }
 
-fn write_data_i16<W: Write>(wr: &mut W, val: i16) -> Result<(), DataWriteError> {
-    wr.write_i16::<byteorder::BigEndian>(val).map_err(DataWriteError)
+fn write_data_i16<W: RmpWrite>(wr: &mut W, val: i16) -> Result<(), DataWriteError> {
+    let mut buf = [0; 2];
+    BigEndian::write_i16(&mut buf, val);
+    wr.write_bytes(&buf).map_err(DataWriteError)
 }
 
-fn write_data_i32<W: Write>(wr: &mut W, val: i32) -> Result<(), DataWriteError> {
-    wr.write_i32::<byteorder::BigEndian>(val).map_err(DataWriteError)
+fn write_data_i32<W: RmpWrite>(wr: &mut W, val: i32) -> Result<(), DataWriteError> {
+    let mut buf = [0; 4];
+    BigEndian::write_i32(&mut buf, val);
+    wr.write_bytes(&buf).map_err(DataWriteError)
 }
 
-fn write_data_i64<W: Write>(wr: &mut W, val: i64) -> Result<(), DataWriteError> {
-    wr.write_i64::<byteorder::BigEndian>(val).map_err(DataWriteError)
+fn write_data_i64<W: RmpWrite>(wr: &mut W, val: i64) -> Result<(), DataWriteError> {
+    let mut buf = [0; 8];
+    BigEndian::write_i64(&mut buf, val);
+    wr.write_bytes(&buf).map_err(DataWriteError)
 }
 
-fn write_data_f32<W: Write>(wr: &mut W, val: f32) -> Result<(), DataWriteError> {
-    wr.write_f32::<byteorder::BigEndian>(val).map_err(DataWriteError)
+fn write_data_f32<W: RmpWrite>(wr: &mut W, val: f32) -> Result<(), DataWriteError> {
+    let mut buf = [0; 4];
+    BigEndian::write_f32(&mut buf, val);
+    wr.write_bytes(&buf).map_err(DataWriteError)
 }
 
-fn write_data_f64<W: Write>(wr: &mut W, val: f64) -> Result<(), DataWriteError> {
-    wr.write_f64::<byteorder::BigEndian>(val).map_err(DataWriteError)
+fn write_data_f64<W: RmpWrite>(wr: &mut W, val: f64) -> Result<(), DataWriteError> {
+    let mut buf = [0; 8];
+    BigEndian::write_f64(&mut buf, val);
+    wr.write_bytes(&buf).map_err(DataWriteError)
 }
 
 /// An error that can occur when attempting to write multi-byte MessagePack value.
@@ -150,6 +200,9 @@ pub enum ValueWriteError {
     InvalidMarkerWrite(Error),
     /// I/O error while writing data.
     InvalidDataWrite(Error),
+    /// The extension type id is reserved by the MessagePack specification and has no defined
+    /// meaning.
+    ReservedExtType(i8),
 }
 
 impl From<MarkerWriteError> for ValueWriteError {
@@ -168,15 +221,20 @@ impl From<DataWriteError> for ValueWriteError {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<ValueWriteError> for Error {
     fn from(err: ValueWriteError) -> Error {
         match err {
             ValueWriteError::InvalidMarkerWrite(err) |
             ValueWriteError::InvalidDataWrite(err) => err,
+            ValueWriteError::ReservedExtType(ty) => {
+                Error::new(ErrorKind::InvalidInput, format!("reserved extension type {}", ty))
+            }
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for ValueWriteError {
     fn description(&self) -> &str {
         "error while writing multi-byte MessagePack value"
@@ -186,13 +244,17 @@ impl error::Error for ValueWriteError {
         match *self {
             ValueWriteError::InvalidMarkerWrite(ref err) |
             ValueWriteError::InvalidDataWrite(ref err) => Some(err),
+            ValueWriteError::ReservedExtType(..) => None,
         }
     }
 }
 
 impl Display for ValueWriteError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        error::Error::description(self).fmt(f)
+        match *self {
+            ValueWriteError::ReservedExtType(ty) => write!(f, "reserved extension type {}", ty),
+            _ => "error while writing multi-byte MessagePack value".fmt(f),
+        }
     }
 }
 
@@ -203,7 +265,7 @@ impl Display for ValueWriteError {
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_array_len<W: Write>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
+pub fn write_array_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
     let marker = if len < 16 {
         try!(write_marker(wr, Marker::FixArray(len as u8)));
         Marker::FixArray(len as u8)
@@ -227,7 +289,7 @@ pub fn write_array_len<W: Write>(wr: &mut W, len: u32) -> Result<Marker, ValueWr
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_map_len<W: Write>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
+pub fn write_map_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
     let marker = if len < 16 {
         try!(write_marker(wr, Marker::FixMap(len as u8)));
         Marker::FixMap(len as u8)
@@ -247,18 +309,15 @@ pub fn write_map_len<W: Write>(wr: &mut W, len: u32) -> Result<Marker, ValueWrit
 /// Encodes and attempts to write the most efficient ext metadata implementation to the given
 /// write, returning the marker used.
 ///
+/// The whole `i8` range is accepted for the type id, including negative values reserved by the
+/// MessagePack specification, like `-1` used for timestamps. Use `write_ext_meta_strict` to
+/// reject reserved types that have no defined meaning.
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-///
-/// # Panics
-///
-/// Panics if `ty` is negative, because it is reserved for future MessagePack extension including
-/// 2-byte type information.
-pub fn write_ext_meta<W: Write>(wr: &mut W, len: u32, ty: i8) -> Result<Marker, ValueWriteError> {
-    assert!(ty >= 0);
-
+pub fn write_ext_meta<W: RmpWrite>(wr: &mut W, len: u32, ty: i8) -> Result<Marker, ValueWriteError> {
     let marker = match len {
         1 => {
             try!(write_marker(wr, Marker::FixExt1));
@@ -301,3 +360,39 @@ pub fn write_ext_meta<W: Write>(wr: &mut W, len: u32, ty: i8) -> Result<Marker,
 
     Ok(marker)
 }
+
+/// Encodes and attempts to write the most efficient ext metadata implementation to the given
+/// write, returning the marker used.
+///
+/// Unlike `write_ext_meta` this function accepts only application-specific types and reserved
+/// types whose meaning is defined by the MessagePack specification.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// It also returns `ValueWriteError::ReservedExtType` without writing anything if `ty` is reserved
+/// for future MessagePack extensions.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Marker;
+/// use rmp::encode::{write_ext_meta_strict, ValueWriteError};
+///
+/// let mut buf = Vec::new();
+///
+/// assert_eq!(Marker::FixExt4, write_ext_meta_strict(&mut buf, 4, -1).unwrap());
+///
+/// match write_ext_meta_strict(&mut buf, 4, -2) {
+///     Err(ValueWriteError::ReservedExtType(-2)) => (),
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn write_ext_meta_strict<W: RmpWrite>(wr: &mut W, len: u32, ty: i8) -> Result<Marker, ValueWriteError> {
+    match ext_type_kind(ty) {
+        ExtTypeKind::Application | ExtTypeKind::Predefined => write_ext_meta(wr, len, ty),
+        ExtTypeKind::Reserved => Err(ValueWriteError::ReservedExtType(ty)),
+    }
+}
@@ -0,0 +1,177 @@
+#[cfg(feature = "std")]
+use std::io::{Seek, SeekFrom, Write};
+
+use alloc::vec::Vec;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use Marker;
+use super::{write_array_len_to_slice, write_map_len_to_slice, ValueWriteError};
+
+/// An array or map header written in advance with a 32-bit zero length, which is patched once
+/// the number of elements is known.
+///
+/// This allows to stream elements of containers, whose size is unknown until they are exhausted,
+/// for example ones produced by iterators. The header always takes 5 bytes at first, which can be
+/// compacted to its shortest form afterwards when writing into a `Vec<u8>`.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Marker;
+/// use rmp::encode::{push_array_len_placeholder, write_uint};
+///
+/// let mut buf = Vec::new();
+/// let header = push_array_len_placeholder(&mut buf);
+///
+/// let mut len = 0;
+/// for val in (1..4).map(|val| val * 100) {
+///     write_uint(&mut buf, val).unwrap();
+///     len += 1;
+/// }
+///
+/// assert_eq!(Marker::FixArray(3), header.patch_vec(&mut buf, len, true));
+/// assert_eq!(vec![0x93, 0x64, 0xcc, 0xc8, 0xcd, 0x01, 0x2c], buf);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LenPlaceholder {
+    marker: Marker,
+    offset: u64,
+}
+
+impl LenPlaceholder {
+    /// Returns the marker of the placeholder, which is either `Array32` or `Map32`.
+    pub fn marker(&self) -> Marker {
+        self.marker
+    }
+
+    /// Returns the offset of the placeholder's marker in the stream or vector it was written to.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Patches the placeholder with the given length, keeping its 32-bit form.
+    ///
+    /// The writer is positioned back to where it was when this function was called, so further
+    /// values can be written right away. For maps the length is the number of key-value pairs.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `ValueWriteError` on any I/O error occurred while seeking or
+    /// writing the length.
+    #[cfg(feature = "std")]
+    pub fn patch<W: Write + Seek>(self, wr: &mut W, len: u32) -> Result<(), ValueWriteError> {
+        let mut buf = [0; 4];
+        BigEndian::write_u32(&mut buf, len);
+
+        let end = wr.stream_position().map_err(ValueWriteError::InvalidDataWrite)?;
+        wr.seek(SeekFrom::Start(self.offset + 1)).map_err(ValueWriteError::InvalidDataWrite)?;
+        wr.write_all(&buf).map_err(ValueWriteError::InvalidDataWrite)?;
+        wr.seek(SeekFrom::Start(end)).map_err(ValueWriteError::InvalidDataWrite)?;
+
+        Ok(())
+    }
+
+    /// Patches the placeholder written into the given vector with the given length, returning the
+    /// marker of the final header.
+    ///
+    /// If `compact` is set, the header is rewritten in its shortest form, i.e. exactly as
+    /// `write_array_len` or `write_map_len` would have written it, and the elements are moved to
+    /// follow it. Otherwise the 32-bit form is kept, which is a valid encoding as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is too short to contain the placeholder.
+    pub fn patch_vec(self, buf: &mut Vec<u8>, len: u32, compact: bool) -> Marker {
+        let offset = self.offset as usize;
+
+        if compact {
+            let mut head = [0; 5];
+            let res = match self.marker {
+                Marker::Map32 => write_map_len_to_slice(&mut head, len),
+                _ => write_array_len_to_slice(&mut head, len),
+            };
+            let head_len = res.expect("header must fit in 5 bytes");
+
+            buf.splice(offset..offset + 5, head[..head_len].iter().cloned());
+            Marker::from_u8(head[0])
+        } else {
+            BigEndian::write_u32(&mut buf[offset + 1..offset + 5], len);
+            self.marker
+        }
+    }
+}
+
+/// Writes an `Array32` header with a zero length into the given write, returning the placeholder
+/// to be patched with the actual length later.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while querying the
+/// position or writing the header.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use rmp::encode::{write_array_len_placeholder, write_nil};
+///
+/// let mut cur = Cursor::new(Vec::new());
+/// let header = write_array_len_placeholder(&mut cur).unwrap();
+///
+/// write_nil(&mut cur).unwrap();
+/// header.patch(&mut cur, 1).unwrap();
+///
+/// assert_eq!(vec![0xdd, 0x00, 0x00, 0x00, 0x01, 0xc0], cur.into_inner());
+/// ```
+#[cfg(feature = "std")]
+pub fn write_array_len_placeholder<W: Write + Seek>(wr: &mut W) -> Result<LenPlaceholder, ValueWriteError> {
+    write_placeholder(wr, Marker::Array32)
+}
+
+/// Writes a `Map32` header with a zero length into the given write, returning the placeholder to
+/// be patched with the actual number of key-value pairs later.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while querying the
+/// position or writing the header.
+#[cfg(feature = "std")]
+pub fn write_map_len_placeholder<W: Write + Seek>(wr: &mut W) -> Result<LenPlaceholder, ValueWriteError> {
+    write_placeholder(wr, Marker::Map32)
+}
+
+#[cfg(feature = "std")]
+fn write_placeholder<W: Write + Seek>(wr: &mut W, marker: Marker) -> Result<LenPlaceholder, ValueWriteError> {
+    let offset = wr.stream_position().map_err(ValueWriteError::InvalidMarkerWrite)?;
+    wr.write_all(&[marker.to_u8()]).map_err(ValueWriteError::InvalidMarkerWrite)?;
+    wr.write_all(&[0; 4]).map_err(ValueWriteError::InvalidDataWrite)?;
+
+    Ok(LenPlaceholder {
+        marker: marker,
+        offset: offset,
+    })
+}
+
+/// Appends an `Array32` header with a zero length to the given vector, returning the placeholder
+/// to be patched with the actual length later.
+pub fn push_array_len_placeholder(buf: &mut Vec<u8>) -> LenPlaceholder {
+    push_placeholder(buf, Marker::Array32)
+}
+
+/// Appends a `Map32` header with a zero length to the given vector, returning the placeholder to
+/// be patched with the actual number of key-value pairs later.
+pub fn push_map_len_placeholder(buf: &mut Vec<u8>) -> LenPlaceholder {
+    push_placeholder(buf, Marker::Map32)
+}
+
+fn push_placeholder(buf: &mut Vec<u8>, marker: Marker) -> LenPlaceholder {
+    let offset = buf.len() as u64;
+    buf.extend_from_slice(&[marker.to_u8(), 0, 0, 0, 0]);
+
+    LenPlaceholder {
+        marker: marker,
+        offset: offset,
+    }
+}
@@ -1,7 +1,6 @@
-use std::io::Write;
-
 use Marker;
-use encode::{write_pfix, write_u8, write_u16, write_u32, write_u64, Error, ValueWriteError};
+use encode::{write_pfix, write_u8, write_u16, write_u32, write_u64, RmpWrite, Error,
+             ValueWriteError};
 use super::{write_data_i8, write_data_i16, write_data_i32, write_data_i64, write_marker};
 
 /// Encodes and attempts to write a negative small integer value as a negative fixnum into the
@@ -24,7 +23,7 @@ use super::{write_data_i8, write_data_i16, write_data_i32, write_data_i64, write
 /// # Panics
 ///
 /// Panics if `val` does not fit in `[-32; 0)` range.
-pub fn write_nfix<W: Write>(wr: &mut W, val: i8) -> Result<(), Error> {
+pub fn write_nfix<W: RmpWrite>(wr: &mut W, val: i8) -> Result<(), Error> {
     assert!(-32 <= val && val < 0);
     try!(write_marker(wr, Marker::FixNeg(val)));
     Ok(())
@@ -58,7 +57,7 @@ pub fn write_nfix<W: Write>(wr: &mut W, val: i8) -> Result<(), Error> {
 /// rmp::encode::write_i8(&mut &mut buf[..], -18).ok().unwrap();
 /// assert_eq!([0xd0, 0xee], buf);
 /// ```
-pub fn write_i8<W: Write>(wr: &mut W, val: i8) -> Result<(), ValueWriteError> {
+pub fn write_i8<W: RmpWrite>(wr: &mut W, val: i8) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::I8));
     try!(write_data_i8(wr, val));
     Ok(())
@@ -79,7 +78,7 @@ pub fn write_i8<W: Write>(wr: &mut W, val: i8) -> Result<(), ValueWriteError> {
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_i16<W: Write>(wr: &mut W, val: i16) -> Result<(), ValueWriteError> {
+pub fn write_i16<W: RmpWrite>(wr: &mut W, val: i16) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::I16));
     try!(write_data_i16(wr, val));
     Ok(())
@@ -100,7 +99,7 @@ pub fn write_i16<W: Write>(wr: &mut W, val: i16) -> Result<(), ValueWriteError>
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_i32<W: Write>(wr: &mut W, val: i32) -> Result<(), ValueWriteError> {
+pub fn write_i32<W: RmpWrite>(wr: &mut W, val: i32) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::I32));
     try!(write_data_i32(wr, val));
     Ok(())
@@ -121,7 +120,7 @@ pub fn write_i32<W: Write>(wr: &mut W, val: i32) -> Result<(), ValueWriteError>
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_i64<W: Write>(wr: &mut W, val: i64) -> Result<(), ValueWriteError> {
+pub fn write_i64<W: RmpWrite>(wr: &mut W, val: i64) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::I64));
     try!(write_data_i64(wr, val));
     Ok(())
@@ -144,7 +143,7 @@ pub fn write_i64<W: Write>(wr: &mut W, val: i64) -> Result<(), ValueWriteError>
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_sint<W: Write>(wr: &mut W, val: i64) -> Result<Marker, ValueWriteError> {
+pub fn write_sint<W: RmpWrite>(wr: &mut W, val: i64) -> Result<Marker, ValueWriteError> {
     match val {
         val if -32 <= val && val < 0 => {
             write_nfix(wr, val as i8)
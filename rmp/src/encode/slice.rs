@@ -0,0 +1,215 @@
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error;
+
+use Timestamp;
+use super::{write_nil, write_bool, write_uint, write_sint, write_f32, write_f64, write_str,
+            write_bin, write_array_len, write_map_len, write_ext_meta, write_timestamp};
+use super::{uint_len, sint_len, str_len, bin_len, array_header_len, map_header_len,
+            ext_header_len, timestamp_len};
+
+/// An error that can occur when encoding a value into a slice.
+///
+/// Nothing is written into the slice in this case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliceWriteError {
+    /// The slice is too small to hold the encoded value.
+    BufferTooSmall {
+        /// Number of bytes required to encode the value.
+        required: usize,
+        /// Number of bytes available in the slice.
+        available: usize,
+    },
+    /// The string or binary is longer than the `u32::MAX` bytes a MessagePack header can describe.
+    LengthOverflow(usize),
+}
+
+#[cfg(feature = "std")]
+impl error::Error for SliceWriteError {}
+
+impl Display for SliceWriteError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            SliceWriteError::BufferTooSmall { required, available } => {
+                write!(f, "buffer is too small: {} bytes required, but only {} available",
+                       required, available)
+            }
+            SliceWriteError::LengthOverflow(len) => {
+                write!(f, "length {} doesn't fit in a MessagePack header", len)
+            }
+        }
+    }
+}
+
+// Checks that the slice can hold exactly `len` bytes, then writes them using the given function.
+fn write_exact<F, T, E>(buf: &mut [u8], len: usize, f: F) -> Result<usize, SliceWriteError>
+    where F: FnOnce(&mut &mut [u8]) -> Result<T, E>
+{
+    if buf.len() < len {
+        return Err(SliceWriteError::BufferTooSmall { required: len, available: buf.len() });
+    }
+
+    let mut wr = &mut buf[..len];
+    match f(&mut wr) {
+        Ok(..) if wr.is_empty() => Ok(len),
+        // The encoded length can only differ from the precomputed one if the header can't
+        // describe the data, which the callers reject upfront.
+        _ => Err(SliceWriteError::LengthOverflow(len)),
+    }
+}
+
+// Rejects data, which is too long to be described by a MessagePack header.
+fn check_data_len(len: usize) -> Result<(), SliceWriteError> {
+    if len > u32::MAX as usize {
+        Err(SliceWriteError::LengthOverflow(len))
+    } else {
+        Ok(())
+    }
+}
+
+/// Encodes a nil value into the beginning of the given slice, returning the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is empty.
+pub fn write_nil_to_slice(buf: &mut [u8]) -> Result<usize, SliceWriteError> {
+    write_exact(buf, 1, |wr| write_nil(wr))
+}
+
+/// Encodes a bool value into the beginning of the given slice, returning the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is empty.
+pub fn write_bool_to_slice(buf: &mut [u8], val: bool) -> Result<usize, SliceWriteError> {
+    write_exact(buf, 1, |wr| write_bool(wr, val))
+}
+
+/// Encodes an unsigned integer into the beginning of the given slice using the most efficient
+/// representation, returning the number of bytes written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than `uint_len(val)`.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::write_uint_to_slice;
+///
+/// let mut buf = [0; 16];
+///
+/// assert_eq!(3, write_uint_to_slice(&mut buf, 300).unwrap());
+/// assert_eq!([0xcd, 0x01, 0x2c], buf[..3]);
+/// ```
+pub fn write_uint_to_slice(buf: &mut [u8], val: u64) -> Result<usize, SliceWriteError> {
+    write_exact(buf, uint_len(val), |wr| write_uint(wr, val))
+}
+
+/// Encodes a signed integer into the beginning of the given slice using the most efficient
+/// representation, returning the number of bytes written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than `sint_len(val)`.
+pub fn write_sint_to_slice(buf: &mut [u8], val: i64) -> Result<usize, SliceWriteError> {
+    write_exact(buf, sint_len(val), |wr| write_sint(wr, val))
+}
+
+/// Encodes an `f32` value into the beginning of the given slice, returning the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than 5 bytes.
+pub fn write_f32_to_slice(buf: &mut [u8], val: f32) -> Result<usize, SliceWriteError> {
+    write_exact(buf, 5, |wr| write_f32(wr, val))
+}
+
+/// Encodes an `f64` value into the beginning of the given slice, returning the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than 9 bytes.
+pub fn write_f64_to_slice(buf: &mut [u8], val: f64) -> Result<usize, SliceWriteError> {
+    write_exact(buf, 9, |wr| write_f64(wr, val))
+}
+
+/// Encodes a string, including its header, into the beginning of the given slice, returning the
+/// number of bytes written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than `str_len(data)` and
+/// `SliceWriteError::LengthOverflow` if the string is longer than `u32::MAX` bytes.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::{write_str_to_slice, SliceWriteError};
+///
+/// let mut buf = [0; 8];
+///
+/// assert_eq!(4, write_str_to_slice(&mut buf, "key").unwrap());
+/// assert_eq!(Err(SliceWriteError::BufferTooSmall { required: 11, available: 8 }),
+///            write_str_to_slice(&mut buf, "le message"));
+/// ```
+pub fn write_str_to_slice(buf: &mut [u8], data: &str) -> Result<usize, SliceWriteError> {
+    check_data_len(data.len())?;
+    write_exact(buf, str_len(data), |wr| write_str(wr, data))
+}
+
+/// Encodes a binary array, including its header, into the beginning of the given slice, returning
+/// the number of bytes written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than `bin_len(data)` and
+/// `SliceWriteError::LengthOverflow` if the data is longer than `u32::MAX` bytes.
+pub fn write_bin_to_slice(buf: &mut [u8], data: &[u8]) -> Result<usize, SliceWriteError> {
+    check_data_len(data.len())?;
+    write_exact(buf, bin_len(data), |wr| write_bin(wr, data))
+}
+
+/// Encodes an array header into the beginning of the given slice, returning the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than `array_header_len(len)`.
+pub fn write_array_len_to_slice(buf: &mut [u8], len: u32) -> Result<usize, SliceWriteError> {
+    write_exact(buf, array_header_len(len), |wr| write_array_len(wr, len))
+}
+
+/// Encodes a map header into the beginning of the given slice, returning the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than `map_header_len(len)`.
+pub fn write_map_len_to_slice(buf: &mut [u8], len: u32) -> Result<usize, SliceWriteError> {
+    write_exact(buf, map_header_len(len), |wr| write_map_len(wr, len))
+}
+
+/// Encodes an extension header into the beginning of the given slice, returning the number of
+/// bytes written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than `ext_header_len(len)`.
+pub fn write_ext_meta_to_slice(buf: &mut [u8], len: u32, ty: i8) -> Result<usize, SliceWriteError> {
+    write_exact(buf, ext_header_len(len), |wr| write_ext_meta(wr, len, ty))
+}
+
+/// Encodes a timestamp into the beginning of the given slice, returning the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// Returns `SliceWriteError::BufferTooSmall` if the slice is shorter than `timestamp_len(ts)`.
+pub fn write_timestamp_to_slice(buf: &mut [u8], ts: Timestamp) -> Result<usize, SliceWriteError> {
+    write_exact(buf, timestamp_len(ts), |wr| write_timestamp(wr, ts))
+}
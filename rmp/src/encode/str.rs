@@ -1,7 +1,5 @@
-use std::io::Write;
-
 use Marker;
-use encode::ValueWriteError;
+use encode::{RmpWrite, ValueWriteError};
 use super::{write_marker, write_data_u8, write_data_u16, write_data_u32};
 
 /// Encodes and attempts to write the most efficient string length implementation to the given
@@ -11,7 +9,7 @@ use super::{write_marker, write_data_u8, write_data_u16, write_data_u32};
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_str_len<W: Write>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
+pub fn write_str_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
     if len < 32 {
         try!(write_marker(wr, Marker::FixStr(len as u8)));
         Ok(Marker::FixStr(len as u8))
@@ -38,7 +36,59 @@ pub fn write_str_len<W: Write>(wr: &mut W, len: u32) -> Result<Marker, ValueWrit
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
 // TODO: Docs, range check, example, visibility.
-pub fn write_str<W: Write>(wr: &mut W, data: &str) -> Result<(), ValueWriteError> {
+pub fn write_str<W: RmpWrite>(wr: &mut W, data: &str) -> Result<(), ValueWriteError> {
     try!(write_str_len(wr, data.len() as u32));
-    wr.write_all(data.as_bytes()).map_err(ValueWriteError::InvalidDataWrite)
+    wr.write_bytes(data.as_bytes()).map_err(ValueWriteError::InvalidDataWrite)
+}
+
+/// Encodes and attempts to write the most efficient raw length implementation to the given write,
+/// returning the marker used.
+///
+/// Raw is the single type the old MessagePack specification (before 2013) had for both strings and
+/// binary data. It shares its markers with the string family, but lacks `Str8`, so lengths from 32
+/// to 255 are written with `Str16` marker. Use this function instead of `write_str_len` and
+/// `write_bin_len` when the data is going to be read by an implementation that doesn't support the
+/// current specification.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Marker;
+/// use rmp::encode::write_raw_len;
+///
+/// let mut buf = Vec::new();
+///
+/// assert_eq!(Marker::Str16, write_raw_len(&mut buf, 42).unwrap());
+/// assert_eq!(vec![0xda, 0x00, 0x2a], buf);
+/// ```
+pub fn write_raw_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueWriteError> {
+    if len < 32 {
+        write_marker(wr, Marker::FixStr(len as u8))?;
+        Ok(Marker::FixStr(len as u8))
+    } else if len < 65536 {
+        write_marker(wr, Marker::Str16)?;
+        write_data_u16(wr, len as u16)?;
+        Ok(Marker::Str16)
+    } else {
+        write_marker(wr, Marker::Str32)?;
+        write_data_u32(wr, len)?;
+        Ok(Marker::Str32)
+    }
+}
+
+/// Encodes and attempts to write the given string or binary data as raw, i.e. in a format that the
+/// old MessagePack specification understands.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+pub fn write_raw<W: RmpWrite>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError> {
+    write_raw_len(wr, data.len() as u32)?;
+    wr.write_bytes(data).map_err(ValueWriteError::InvalidDataWrite)
 }
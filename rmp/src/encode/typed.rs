@@ -0,0 +1,87 @@
+use {Marker, TypedArray};
+use encode::{RmpWrite, ValueWriteError};
+use super::write_ext_meta;
+
+/// Encodes and attempts to write a 128-bit signed integer as an extension of the given type into
+/// the given write.
+///
+/// MessagePack has no native 128-bit integers, so the value is always written as a `fixext16`
+/// extension with the value in big-endian two's complement, even if it fits into 64 bits. This way
+/// the wire type doesn't depend on the value. `DEFAULT_I128_EXT_TYPE` is a suggested type id.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// let mut buf = Vec::new();
+///
+/// rmp::encode::write_i128(&mut buf, -2, rmp::DEFAULT_I128_EXT_TYPE).unwrap();
+///
+/// assert_eq!([0xd8, 0x7d, 0xff, 0xff], buf[..4]);
+/// assert_eq!(18, buf.len());
+/// ```
+pub fn write_i128<W: RmpWrite>(wr: &mut W, val: i128, ty: i8) -> Result<(), ValueWriteError> {
+    write_ext_meta(wr, 16, ty)?;
+    wr.write_bytes(&val.to_be_bytes()).map_err(ValueWriteError::InvalidDataWrite)
+}
+
+/// Encodes and attempts to write a 128-bit unsigned integer as an extension of the given type into
+/// the given write.
+///
+/// The value is always written as a `fixext16` extension with the value in big-endian.
+/// `DEFAULT_U128_EXT_TYPE` is a suggested type id.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+pub fn write_u128<W: RmpWrite>(wr: &mut W, val: u128, ty: i8) -> Result<(), ValueWriteError> {
+    write_ext_meta(wr, 16, ty)?;
+    wr.write_bytes(&val.to_be_bytes()).map_err(ValueWriteError::InvalidDataWrite)
+}
+
+/// Encodes and attempts to write the given typed array as an extension of the given type into the
+/// given write, returning the marker used.
+///
+/// See `TypedArray` for the layout. `TypedArray::DEFAULT_EXT_TYPE` is a suggested type id.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::{Endian, Marker, TypedArray};
+///
+/// let mut buf = Vec::new();
+/// let array = TypedArray::from_i32(&[1, 2, 3], Endian::Big);
+///
+/// assert_eq!(Marker::Ext8, rmp::encode::write_typed_array(&mut buf, &array, TypedArray::DEFAULT_EXT_TYPE).unwrap());
+/// assert_eq!([0xc7, 0x0d, 0x7f, 0x05, 0x00, 0x00, 0x00, 0x01], buf[..8]);
+/// ```
+pub fn write_typed_array<W: RmpWrite>(wr: &mut W, array: &TypedArray, ty: i8) -> Result<Marker, ValueWriteError> {
+    let marker = write_ext_meta(wr, array.data_len(), ty)?;
+    write_typed_array_data(wr, array)?;
+
+    Ok(marker)
+}
+
+/// Encodes and attempts to write the data part of the given typed array, i.e. the tag byte
+/// followed by the elements, into the given write without any extension metadata.
+///
+/// The number of bytes written is equal to `TypedArray::data_len()`. This function is useful when
+/// the array needs to be stored as a raw extension payload, for example in a `Value`.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing the data.
+pub fn write_typed_array_data<W: RmpWrite>(wr: &mut W, array: &TypedArray) -> Result<(), ValueWriteError> {
+    wr.write_bytes(&[array.tag()]).map_err(ValueWriteError::InvalidDataWrite)?;
+    wr.write_bytes(array.as_bytes()).map_err(ValueWriteError::InvalidDataWrite)
+}
@@ -1,7 +1,5 @@
-use std::io::Write;
-
 use Marker;
-use encode::{Error, ValueWriteError};
+use encode::{RmpWrite, Error, ValueWriteError};
 use super::{write_data_u8, write_data_u16, write_data_u32, write_data_u64, write_marker};
 
 /// Encodes and attempts to write an unsigned small integer value as a positive fixint into the
@@ -24,7 +22,7 @@ use super::{write_data_u8, write_data_u16, write_data_u32, write_data_u64, write
 /// # Panics
 ///
 /// Panics if `val` is greater than 127.
-pub fn write_pfix<W: Write>(wr: &mut W, val: u8) -> Result<(), Error> {
+pub fn write_pfix<W: RmpWrite>(wr: &mut W, val: u8) -> Result<(), Error> {
     assert!(val < 128);
     try!(write_marker(wr, Marker::FixPos(val)));
     Ok(())
@@ -56,7 +54,7 @@ pub fn write_pfix<W: Write>(wr: &mut W, val: u8) -> Result<(), Error> {
 /// rmp::encode::write_u8(&mut &mut buf[..], 42).ok().unwrap();
 /// assert_eq!([0xcc, 0x2a], buf);
 /// ```
-pub fn write_u8<W: Write>(wr: &mut W, val: u8) -> Result<(), ValueWriteError> {
+pub fn write_u8<W: RmpWrite>(wr: &mut W, val: u8) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::U8));
     try!(write_data_u8(wr, val));
     Ok(())
@@ -76,7 +74,7 @@ pub fn write_u8<W: Write>(wr: &mut W, val: u8) -> Result<(), ValueWriteError> {
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_u16<W: Write>(wr: &mut W, val: u16) -> Result<(), ValueWriteError> {
+pub fn write_u16<W: RmpWrite>(wr: &mut W, val: u16) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::U16));
     try!(write_data_u16(wr, val));
     Ok(())
@@ -96,7 +94,7 @@ pub fn write_u16<W: Write>(wr: &mut W, val: u16) -> Result<(), ValueWriteError>
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_u32<W: Write>(wr: &mut W, val: u32) -> Result<(), ValueWriteError> {
+pub fn write_u32<W: RmpWrite>(wr: &mut W, val: u32) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::U32));
     try!(write_data_u32(wr, val));
     Ok(())
@@ -116,7 +114,7 @@ pub fn write_u32<W: Write>(wr: &mut W, val: u32) -> Result<(), ValueWriteError>
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_u64<W: Write>(wr: &mut W, val: u64) -> Result<(), ValueWriteError> {
+pub fn write_u64<W: RmpWrite>(wr: &mut W, val: u64) -> Result<(), ValueWriteError> {
     try!(write_marker(wr, Marker::U64));
     try!(write_data_u64(wr, val));
     Ok(())
@@ -135,7 +133,7 @@ pub fn write_u64<W: Write>(wr: &mut W, val: u64) -> Result<(), ValueWriteError>
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
 /// marker or the data.
-pub fn write_uint<W: Write>(wr: &mut W, val: u64) -> Result<Marker, ValueWriteError> {
+pub fn write_uint<W: RmpWrite>(wr: &mut W, val: u64) -> Result<Marker, ValueWriteError> {
     if val < 128 {
         write_pfix(wr, val as u8)
             .and(Ok(Marker::FixPos(val as u8)))
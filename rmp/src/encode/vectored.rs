@@ -0,0 +1,131 @@
+use std::io::{self, IoSlice, Write};
+
+use super::{write_bin_len, write_str_len, write_raw_len, ValueWriteError};
+
+/// Encodes and attempts to write the most efficient binary implementation to the given `Write`,
+/// passing the header and the payload to the writer together.
+///
+/// Unlike `write_bin`, which issues separate writes for the marker, the length and the data, this
+/// function uses `Write::write_vectored`, so unbuffered writers like sockets get the whole value
+/// in a single system call if they support vectored I/O, and in at most two otherwise.
+///
+/// The other encoding functions still write every marker and length separately. To batch them,
+/// wrap the writer into `std::io::BufWriter`, which accumulates small writes, like headers, and
+/// passes payloads exceeding its capacity straight to the underlying writer.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// let mut buf = Vec::new();
+///
+/// rmp::encode::write_bin_vectored(&mut buf, &[1, 2, 3]).unwrap();
+///
+/// assert_eq!(vec![0xc4, 0x03, 0x01, 0x02, 0x03], buf);
+/// ```
+///
+/// Batching the headers of other values with `BufWriter`:
+///
+/// ```
+/// use std::io::{BufWriter, Write};
+///
+/// use rmp::encode::{write_array_len, write_bin_vectored, write_uint};
+///
+/// let mut wr = BufWriter::with_capacity(16, Vec::new());
+///
+/// write_array_len(&mut wr, 2).unwrap();
+/// write_uint(&mut wr, 42).unwrap();
+/// // Nothing has been written to the vector yet.
+/// assert!(wr.get_ref().is_empty());
+///
+/// write_bin_vectored(&mut wr, &[0; 32]).unwrap();
+/// assert_eq!(2 + 2 + 32, wr.get_ref().len());
+///
+/// wr.flush().unwrap();
+/// ```
+pub fn write_bin_vectored<W: Write + ?Sized>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError> {
+    let mut head = [0; 5];
+    let head_len = {
+        let mut buf = &mut head[..];
+        write_bin_len(&mut buf, data.len() as u32)?;
+        5 - buf.len()
+    };
+
+    write_head_and_data(wr, &head[..head_len], data)
+}
+
+/// Encodes and attempts to write the most efficient string implementation to the given `Write`,
+/// passing the header and the payload to the writer together.
+///
+/// See `write_bin_vectored` for details.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+pub fn write_str_vectored<W: Write + ?Sized>(wr: &mut W, data: &str) -> Result<(), ValueWriteError> {
+    let mut head = [0; 5];
+    let head_len = {
+        let mut buf = &mut head[..];
+        write_str_len(&mut buf, data.len() as u32)?;
+        5 - buf.len()
+    };
+
+    write_head_and_data(wr, &head[..head_len], data.as_bytes())
+}
+
+/// Encodes and attempts to write the given data as raw of the old MessagePack specification to the
+/// given `Write`, passing the header and the payload to the writer together.
+///
+/// See `write_raw_len` and `write_bin_vectored` for details.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+pub fn write_raw_vectored<W: Write + ?Sized>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError> {
+    let mut head = [0; 5];
+    let head_len = {
+        let mut buf = &mut head[..];
+        write_raw_len(&mut buf, data.len() as u32)?;
+        5 - buf.len()
+    };
+
+    write_head_and_data(wr, &head[..head_len], data)
+}
+
+fn write_head_and_data<W: Write + ?Sized>(wr: &mut W, head: &[u8], data: &[u8]) -> Result<(), ValueWriteError> {
+    let mut pos = 0;
+
+    while pos < head.len() + data.len() {
+        let res = if pos < head.len() {
+            wr.write_vectored(&[IoSlice::new(&head[pos..]), IoSlice::new(data)])
+        } else {
+            wr.write(&data[pos - head.len()..])
+        };
+
+        match res {
+            Ok(0) => {
+                let err = io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer");
+                return Err(write_error(pos, err));
+            }
+            Ok(n) => pos += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(write_error(pos, err)),
+        }
+    }
+
+    Ok(())
+}
+
+fn write_error(pos: usize, err: io::Error) -> ValueWriteError {
+    if pos == 0 {
+        ValueWriteError::InvalidMarkerWrite(err)
+    } else {
+        ValueWriteError::InvalidDataWrite(err)
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(not(feature = "std"))]
+use core::cmp;
+#[cfg(not(feature = "std"))]
+use core::fmt::{self, Display, Formatter};
+#[cfg(not(feature = "std"))]
+use core::mem;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::Error;
+
+/// A minimal sink of bytes used by the encoding functions.
+///
+/// With the `std` feature enabled (the default) this trait is implemented for every type that
+/// implements `std::io::Write`, so you should never need to implement it yourself.
+///
+/// Without the standard library it is implemented for `Vec<u8>`, which grows as needed, and for
+/// mutable byte slices, which are advanced as the data is written, exactly like `std::io::Write`
+/// does for them.
+pub trait RmpWrite {
+    /// Writes the entire buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sink is unable to accept all the bytes. Some of them may have
+    /// been written in this case.
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Error>;
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write + ?Sized> RmpWrite for W {
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Error> {
+        self.write_all(buf)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl RmpWrite for Vec<u8> {
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Error> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl RmpWrite for &mut [u8] {
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let len = cmp::min(self.len(), buf.len());
+        let (head, tail) = mem::take(self).split_at_mut(len);
+        head.copy_from_slice(&buf[..len]);
+        *self = tail;
+
+        if len == buf.len() {
+            Ok(())
+        } else {
+            Err(BytesWriteError)
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: RmpWrite + ?Sized> RmpWrite for &mut W {
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Error> {
+        (**self).write_bytes(buf)
+    }
+}
+
+/// An error returned when a byte slice has no room left for the value being written.
+///
+/// It takes place of `std::io::Error` when the `std` feature is disabled.
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BytesWriteError;
+
+#[cfg(not(feature = "std"))]
+impl Display for BytesWriteError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.write_str("failed to write whole buffer")
+    }
+}
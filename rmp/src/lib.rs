@@ -45,6 +45,18 @@
 //!   This project is developed using TDD and CI, so any found bugs will be fixed without breaking
 //!   existing functionality.
 //!
+//! - **`no_std` support**
+//!
+//!   The standard library is required only by the `std` feature, which is enabled by default.
+//!   Without it the crate depends on `alloc` only, and all functions work with byte slices and
+//!   vectors through the `RmpRead` and `RmpWrite` traits instead of `std::io::Read` and `Write`.
+//!
+//! - **Asynchronous I/O**
+//!
+//!   The optional `async` feature adds the `decode::asynchronous` and `encode::asynchronous`
+//!   modules with counterparts of the basic functions, which work with `futures_io::AsyncRead` and
+//!   `AsyncWrite` without blocking the executor thread.
+//!
 //! ## Detailed
 //!
 //! This crate represents the very basic functionality needed to work with MessagePack format.
@@ -148,14 +160,30 @@
 //!
 //! [read_int]: decode/fn.read_int.html
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate core;
+extern crate alloc;
+
 extern crate byteorder;
 extern crate num_traits;
+#[cfg(feature = "async")]
+extern crate futures_io;
+#[cfg(feature = "async")]
+extern crate futures_util;
+#[cfg(feature = "half")]
+extern crate half;
 
 mod marker;
+mod timestamp;
+mod typed;
 pub mod encode;
 pub mod decode;
 
-pub use marker::Marker;
+pub use marker::{Marker, Family, Payload};
+pub use timestamp::Timestamp;
+pub use typed::{TypedArray, ElemType, Endian, DEFAULT_I128_EXT_TYPE, DEFAULT_U128_EXT_TYPE};
 
 /// Version of the MessagePack [spec](http://github.com/msgpack/msgpack/blob/master/spec.md).
 pub const MSGPACK_VERSION: u32 = 5;
@@ -145,6 +145,161 @@ impl Marker {
     }
 }
 
+/// The type family of a marker, as defined by the MessagePack specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Family {
+    /// The `nil` value.
+    Nil,
+    /// Boolean values.
+    Bool,
+    /// Signed and unsigned integers, including fixnums.
+    Int,
+    /// 32 and 64-bit floats.
+    Float,
+    /// UTF-8 strings.
+    Str,
+    /// Binary data.
+    Bin,
+    /// Arrays.
+    Array,
+    /// Maps.
+    Map,
+    /// Extension types, including the fixed-size ones.
+    Ext,
+    /// The reserved `0xc1` marker, which is never used.
+    Reserved,
+}
+
+/// What follows the header of a value, as returned by `Marker::payload`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// The given number of data bytes, which is the value itself for scalars, or the data of
+    /// strings, binaries and extensions, excluding the type id.
+    Bytes(u32),
+    /// The given number of child values, i.e. elements of an array, or keys and values of a map
+    /// together.
+    Values(u64),
+}
+
+impl Marker {
+    /// Returns the type family of this marker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::{Family, Marker};
+    ///
+    /// assert_eq!(Family::Int, Marker::FixPos(42).family());
+    /// assert_eq!(Family::Str, Marker::Str8.family());
+    /// assert_eq!(Family::Ext, Marker::FixExt4.family());
+    /// ```
+    pub fn family(&self) -> Family {
+        match *self {
+            Marker::Null => Family::Nil,
+            Marker::True | Marker::False => Family::Bool,
+            Marker::FixPos(..) | Marker::FixNeg(..) |
+            Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64 |
+            Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64 => Family::Int,
+            Marker::F32 | Marker::F64 => Family::Float,
+            Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => Family::Str,
+            Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => Family::Bin,
+            Marker::FixArray(..) | Marker::Array16 | Marker::Array32 => Family::Array,
+            Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => Family::Map,
+            Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 |
+            Marker::FixExt16 | Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => Family::Ext,
+            Marker::Reserved => Family::Reserved,
+        }
+    }
+
+    /// Returns the number of header bytes following this marker, i.e. the length prefix of
+    /// strings, binaries, arrays, maps and extensions, plus the type id of extensions.
+    ///
+    /// Scalars have no header, their data is described by `payload` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::Marker;
+    ///
+    /// assert_eq!(0, Marker::U16.header_len());
+    /// assert_eq!(2, Marker::Array16.header_len());
+    /// assert_eq!(1, Marker::FixExt4.header_len());
+    /// assert_eq!(1 + 4, Marker::Ext32.header_len());
+    /// ```
+    pub fn header_len(&self) -> usize {
+        match *self {
+            Marker::Str8 | Marker::Bin8 => 1,
+            Marker::Str16 | Marker::Bin16 | Marker::Array16 | Marker::Map16 => 2,
+            Marker::Str32 | Marker::Bin32 | Marker::Array32 | Marker::Map32 => 4,
+            Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 |
+            Marker::FixExt16 => 1,
+            Marker::Ext8 => 1 + 1,
+            Marker::Ext16 => 2 + 1,
+            Marker::Ext32 => 4 + 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns what follows the header of a value with this marker, given the header bytes.
+    ///
+    /// Extra bytes after the first `header_len()` ones are ignored, so the slice may as well
+    /// continue with the payload.
+    ///
+    /// Returns `None` if the header is shorter than `header_len()`, or if the marker is reserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::{Marker, Payload};
+    ///
+    /// assert_eq!(Some(Payload::Bytes(2)), Marker::U16.payload(&[]));
+    /// assert_eq!(Some(Payload::Bytes(42)), Marker::Str8.payload(&[0x2a]));
+    /// assert_eq!(Some(Payload::Values(2 * 256)), Marker::Map16.payload(&[0x01, 0x00]));
+    /// assert_eq!(None, Marker::Map16.payload(&[0x01]));
+    /// ```
+    pub fn payload(&self, header: &[u8]) -> Option<Payload> {
+        if header.len() < self.header_len() {
+            return None;
+        }
+
+        let payload = match *self {
+            Marker::FixPos(..) | Marker::FixNeg(..) |
+            Marker::Null | Marker::True | Marker::False => Payload::Bytes(0),
+            Marker::U8 | Marker::I8 => Payload::Bytes(1),
+            Marker::U16 | Marker::I16 => Payload::Bytes(2),
+            Marker::U32 | Marker::I32 | Marker::F32 => Payload::Bytes(4),
+            Marker::U64 | Marker::I64 | Marker::F64 => Payload::Bytes(8),
+            Marker::FixStr(len) => Payload::Bytes(len as u32),
+            Marker::Str8 | Marker::Bin8 | Marker::Ext8 |
+            Marker::Str16 | Marker::Bin16 | Marker::Ext16 |
+            Marker::Str32 | Marker::Bin32 | Marker::Ext32 => Payload::Bytes(read_len(*self, header)),
+            Marker::FixArray(len) => Payload::Values(len as u64),
+            Marker::Array16 | Marker::Array32 => Payload::Values(read_len(*self, header) as u64),
+            Marker::FixMap(len) => Payload::Values(2 * len as u64),
+            Marker::Map16 | Marker::Map32 => Payload::Values(2 * read_len(*self, header) as u64),
+            Marker::FixExt1 => Payload::Bytes(1),
+            Marker::FixExt2 => Payload::Bytes(2),
+            Marker::FixExt4 => Payload::Bytes(4),
+            Marker::FixExt8 => Payload::Bytes(8),
+            Marker::FixExt16 => Payload::Bytes(16),
+            Marker::Reserved => return None,
+        };
+
+        Some(payload)
+    }
+}
+
+// Decodes the big-endian length prefix at the start of the header. For extensions it's followed
+// by the type id, which is excluded.
+fn read_len(marker: Marker, header: &[u8]) -> u32 {
+    let width = match marker.family() {
+        Family::Ext => marker.header_len() - 1,
+        _ => marker.header_len(),
+    };
+
+    header[..width].iter().fold(0, |acc, &byte| acc << 8 | byte as u32)
+}
+
 impl From<u8> for Marker {
     fn from(val: u8) -> Marker {
         Marker::from_u8(val)
@@ -0,0 +1,82 @@
+/// A MessagePack timestamp.
+///
+/// Represents an instant in time as a number of seconds elapsed since 1970-01-01 00:00:00 UTC
+/// and an additional nanoseconds adjustment, the same way the MessagePack specification does.
+///
+/// The timestamp is encoded as an extension with `-1` type id, using one of the three layouts:
+/// timestamp 32, timestamp 64 or timestamp 96. The encoder always picks the smallest one able to
+/// represent the value.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Timestamp;
+///
+/// let ts = Timestamp::new(1514862245, 678901234).unwrap();
+///
+/// assert_eq!(1514862245, ts.secs());
+/// assert_eq!(678901234, ts.nsecs());
+///
+/// // Nanoseconds must be less than a second.
+/// assert_eq!(None, Timestamp::new(1514862245, 1000000000));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    secs: i64,
+    nsecs: u32,
+}
+
+impl Timestamp {
+    /// The extension type id reserved by the MessagePack specification for timestamps.
+    pub const EXT_TYPE: i8 = -1;
+
+    /// Constructs a new timestamp from the given seconds and nanoseconds.
+    ///
+    /// Returns `None` if `nsecs` is not in `[0; 999999999]` range.
+    pub fn new(secs: i64, nsecs: u32) -> Option<Timestamp> {
+        if nsecs < 1_000_000_000 {
+            Some(Timestamp { secs, nsecs })
+        } else {
+            None
+        }
+    }
+
+    /// Constructs a new timestamp from the given seconds with no nanoseconds adjustment.
+    pub fn from_secs(secs: i64) -> Timestamp {
+        Timestamp { secs, nsecs: 0 }
+    }
+
+    /// Returns the number of seconds elapsed since 1970-01-01 00:00:00 UTC.
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+
+    /// Returns the nanoseconds adjustment, which is always less than a second.
+    pub fn nsecs(&self) -> u32 {
+        self.nsecs
+    }
+
+    /// Returns the size of the extension data required to encode this timestamp, which is either
+    /// 4, 8 or 12 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::Timestamp;
+    ///
+    /// assert_eq!(4, Timestamp::from_secs(1514862245).data_len());
+    /// assert_eq!(8, Timestamp::new(1514862245, 678901234).unwrap().data_len());
+    /// assert_eq!(12, Timestamp::from_secs(-1).data_len());
+    /// ```
+    pub fn data_len(&self) -> u32 {
+        if self.secs >> 34 == 0 {
+            if self.nsecs == 0 && self.secs >> 32 == 0 {
+                4
+            } else {
+                8
+            }
+        } else {
+            12
+        }
+    }
+}
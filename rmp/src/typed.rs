@@ -0,0 +1,343 @@
+use alloc::vec::Vec;
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+#[cfg(feature = "half")]
+use half::{bf16, f16};
+
+/// The suggested extension type id for 128-bit signed integers.
+///
+/// MessagePack has no native integers wider than 64 bits, so `write_i128` stores them as a
+/// `fixext16` extension with the value in big-endian two's complement. Extension type ids belong
+/// to the application, which is why `write_i128` and `read_i128` take the id as a parameter. This
+/// one is taken from the top of the range, since application types are usually numbered from
+/// zero.
+pub const DEFAULT_I128_EXT_TYPE: i8 = 125;
+
+/// The suggested extension type id for 128-bit unsigned integers.
+///
+/// See `DEFAULT_I128_EXT_TYPE`.
+pub const DEFAULT_U128_EXT_TYPE: i8 = 126;
+
+/// The byte order of the elements of a `TypedArray`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// Big-endian, the byte order MessagePack uses for its own numbers.
+    Big,
+    /// Little-endian, the native byte order of most hardware, which allows to use the data in
+    /// place.
+    Little,
+}
+
+/// The type of the elements of a `TypedArray`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ElemType {
+    /// IEEE 754 half-precision floating point number.
+    F16,
+    /// Brain floating point number, i.e. the upper half of an `f32`.
+    BF16,
+    /// IEEE 754 single-precision floating point number.
+    F32,
+    /// IEEE 754 double-precision floating point number.
+    F64,
+    /// Signed 32-bit integer.
+    I32,
+}
+
+impl ElemType {
+    /// Returns the size of a single element in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            ElemType::F16 | ElemType::BF16 => 2,
+            ElemType::F32 | ElemType::I32 => 4,
+            ElemType::F64 => 8,
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            ElemType::F16 => 1,
+            ElemType::BF16 => 2,
+            ElemType::F32 => 3,
+            ElemType::F64 => 4,
+            ElemType::I32 => 5,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<ElemType> {
+        match code {
+            1 => Some(ElemType::F16),
+            2 => Some(ElemType::BF16),
+            3 => Some(ElemType::F32),
+            4 => Some(ElemType::F64),
+            5 => Some(ElemType::I32),
+            _ => None,
+        }
+    }
+}
+
+/// A packed array of numbers of the same type.
+///
+/// Encoding large numeric arrays as MessagePack arrays wastes a marker per element and, worse,
+/// has no room for half-precision floats at all. A typed array is stored instead as an extension
+/// of an application chosen type, whose data is a single tag byte followed by the elements
+/// packed without padding. The lower 7 bits of the tag identify the element type (`1` for `f16`,
+/// `2` for `bf16`, `3` for `f32`, `4` for `f64` and `5` for `i32`), the high bit is set for
+/// little-endian elements.
+///
+/// Conversions from and to `half::f16` and `half::bf16` are available with the `half` feature.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::{Endian, ElemType, TypedArray};
+///
+/// let array = TypedArray::from_f32(&[1.0, -0.5], Endian::Little);
+///
+/// assert_eq!(ElemType::F32, array.elem_type());
+/// assert_eq!(2, array.len());
+/// assert_eq!([0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0xbf], array.as_bytes());
+/// assert_eq!(Some(vec![1.0, -0.5]), array.to_f32_vec());
+/// assert_eq!(None, array.to_f64_vec());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TypedArray {
+    elem: ElemType,
+    endian: Endian,
+    data: Vec<u8>,
+}
+
+impl TypedArray {
+    /// The suggested extension type id for typed arrays.
+    ///
+    /// See `DEFAULT_I128_EXT_TYPE`.
+    pub const DEFAULT_EXT_TYPE: i8 = 127;
+
+    /// Constructs a typed array from the elements already encoded in the given byte order.
+    ///
+    /// Returns `None` if the data length is not a multiple of the element size or if the array
+    /// doesn't fit into an extension.
+    pub fn from_bytes(elem: ElemType, endian: Endian, data: Vec<u8>) -> Option<TypedArray> {
+        if data.len() % elem.size() != 0 || data.len() as u64 >= u32::max_value() as u64 {
+            return None;
+        }
+
+        Some(TypedArray {
+            elem: elem,
+            endian: endian,
+            data: data,
+        })
+    }
+
+    /// Constructs a typed array of half-precision floats, given as their raw bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array doesn't fit into an extension, i.e. takes 4 GiB or more.
+    pub fn from_f16_bits(data: &[u16], endian: Endian) -> TypedArray {
+        TypedArray::encode(ElemType::F16, endian, data, |bo, buf, val| bo.write_u16(buf, val))
+    }
+
+    /// Constructs a typed array of brain floats, given as their raw bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array doesn't fit into an extension, i.e. takes 4 GiB or more.
+    pub fn from_bf16_bits(data: &[u16], endian: Endian) -> TypedArray {
+        TypedArray::encode(ElemType::BF16, endian, data, |bo, buf, val| bo.write_u16(buf, val))
+    }
+
+    /// Constructs a typed array of half-precision floats.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array doesn't fit into an extension, i.e. takes 4 GiB or more.
+    #[cfg(feature = "half")]
+    pub fn from_f16(data: &[f16], endian: Endian) -> TypedArray {
+        TypedArray::encode(ElemType::F16, endian, data, |bo, buf, val| bo.write_u16(buf, val.to_bits()))
+    }
+
+    /// Constructs a typed array of brain floats.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array doesn't fit into an extension, i.e. takes 4 GiB or more.
+    #[cfg(feature = "half")]
+    pub fn from_bf16(data: &[bf16], endian: Endian) -> TypedArray {
+        TypedArray::encode(ElemType::BF16, endian, data, |bo, buf, val| bo.write_u16(buf, val.to_bits()))
+    }
+
+    /// Constructs a typed array of single-precision floats.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array doesn't fit into an extension, i.e. takes 4 GiB or more.
+    pub fn from_f32(data: &[f32], endian: Endian) -> TypedArray {
+        TypedArray::encode(ElemType::F32, endian, data, |bo, buf, val| bo.write_f32(buf, val))
+    }
+
+    /// Constructs a typed array of double-precision floats.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array doesn't fit into an extension, i.e. takes 4 GiB or more.
+    pub fn from_f64(data: &[f64], endian: Endian) -> TypedArray {
+        TypedArray::encode(ElemType::F64, endian, data, |bo, buf, val| bo.write_f64(buf, val))
+    }
+
+    /// Constructs a typed array of 32-bit signed integers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array doesn't fit into an extension, i.e. takes 4 GiB or more.
+    pub fn from_i32(data: &[i32], endian: Endian) -> TypedArray {
+        TypedArray::encode(ElemType::I32, endian, data, |bo, buf, val| bo.write_i32(buf, val))
+    }
+
+    /// Returns the type of the elements.
+    pub fn elem_type(&self) -> ElemType {
+        self.elem
+    }
+
+    /// Returns the byte order of the elements.
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.data.len() / self.elem.size()
+    }
+
+    /// Returns `true` if the array has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the packed elements.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes the array, returning the packed elements.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Returns the number of bytes the array takes as an extension payload, including the tag.
+    pub fn data_len(&self) -> u32 {
+        1 + self.data.len() as u32
+    }
+
+    /// Returns the raw bits of the elements if they are half-precision floats, or else `None`.
+    pub fn to_f16_bits_vec(&self) -> Option<Vec<u16>> {
+        self.decode(ElemType::F16, |bo, buf| bo.read_u16(buf))
+    }
+
+    /// Returns the raw bits of the elements if they are brain floats, or else `None`.
+    pub fn to_bf16_bits_vec(&self) -> Option<Vec<u16>> {
+        self.decode(ElemType::BF16, |bo, buf| bo.read_u16(buf))
+    }
+
+    /// Returns the elements if they are half-precision floats, or else `None`.
+    #[cfg(feature = "half")]
+    pub fn to_f16_vec(&self) -> Option<Vec<f16>> {
+        self.decode(ElemType::F16, |bo, buf| f16::from_bits(bo.read_u16(buf)))
+    }
+
+    /// Returns the elements if they are brain floats, or else `None`.
+    #[cfg(feature = "half")]
+    pub fn to_bf16_vec(&self) -> Option<Vec<bf16>> {
+        self.decode(ElemType::BF16, |bo, buf| bf16::from_bits(bo.read_u16(buf)))
+    }
+
+    /// Returns the elements if they are single-precision floats, or else `None`.
+    pub fn to_f32_vec(&self) -> Option<Vec<f32>> {
+        self.decode(ElemType::F32, |bo, buf| bo.read_f32(buf))
+    }
+
+    /// Returns the elements if they are double-precision floats, or else `None`.
+    pub fn to_f64_vec(&self) -> Option<Vec<f64>> {
+        self.decode(ElemType::F64, |bo, buf| bo.read_f64(buf))
+    }
+
+    /// Returns the elements if they are 32-bit signed integers, or else `None`.
+    pub fn to_i32_vec(&self) -> Option<Vec<i32>> {
+        self.decode(ElemType::I32, |bo, buf| bo.read_i32(buf))
+    }
+
+    /// Returns the tag byte, which precedes the elements in the extension payload.
+    pub(crate) fn tag(&self) -> u8 {
+        match self.endian {
+            Endian::Big => self.elem.code(),
+            Endian::Little => self.elem.code() | 0x80,
+        }
+    }
+
+    /// Parses the tag byte, returning `None` if the element type is unknown.
+    pub(crate) fn parse_tag(tag: u8) -> Option<(ElemType, Endian)> {
+        let endian = if tag & 0x80 == 0 {
+            Endian::Big
+        } else {
+            Endian::Little
+        };
+
+        ElemType::from_code(tag & 0x7f).map(|elem| (elem, endian))
+    }
+
+    fn encode<T, F>(elem: ElemType, endian: Endian, data: &[T], f: F) -> TypedArray
+        where T: Copy,
+              F: Fn(Order, &mut [u8], T)
+    {
+        let size = elem.size();
+        let mut buf = alloc::vec![0; data.len() * size];
+        for (chunk, &val) in buf.chunks_mut(size).zip(data) {
+            f(Order(endian), chunk, val);
+        }
+
+        TypedArray::from_bytes(elem, endian, buf).expect("typed array must fit into an extension")
+    }
+
+    fn decode<T, F>(&self, elem: ElemType, f: F) -> Option<Vec<T>>
+        where F: Fn(Order, &[u8]) -> T
+    {
+        if self.elem != elem {
+            return None;
+        }
+
+        let vec = self.data.chunks(elem.size()).map(|chunk| f(Order(self.endian), chunk)).collect();
+        Some(vec)
+    }
+}
+
+// Dispatches to `byteorder` with the byte order chosen at runtime.
+#[derive(Clone, Copy)]
+struct Order(Endian);
+
+macro_rules! order_fns {
+    ($($read:ident, $write:ident, $ty:ty;)*) => {
+        impl Order {
+            $(
+                fn $read(self, buf: &[u8]) -> $ty {
+                    match self.0 {
+                        Endian::Big => BigEndian::$read(buf),
+                        Endian::Little => LittleEndian::$read(buf),
+                    }
+                }
+
+                fn $write(self, buf: &mut [u8], val: $ty) {
+                    match self.0 {
+                        Endian::Big => BigEndian::$write(buf, val),
+                        Endian::Little => LittleEndian::$write(buf, val),
+                    }
+                }
+            )*
+        }
+    }
+}
+
+order_fns! {
+    read_u16, write_u16, u16;
+    read_i32, write_i32, i32;
+    read_f32, write_f32, f32;
+    read_f64, write_f64, f64;
+}
@@ -0,0 +1,257 @@
+#![cfg(feature = "async")]
+
+extern crate futures_executor;
+extern crate futures_io;
+extern crate rmp;
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_executor::block_on;
+use futures_io::{AsyncRead, AsyncWrite};
+
+use rmp::Marker;
+use rmp::decode::asynchronous as decode;
+use rmp::encode::asynchronous as encode;
+use rmp::decode::{DecodeLimits, DecodeStringError, LimitError, ValueReadError};
+
+/// Delivers the data one byte at a time, returning `Pending` before each byte.
+struct Trickle<'a> {
+    buf: &'a [u8],
+    ready: bool,
+}
+
+impl<'a> Trickle<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Trickle { buf: buf, ready: false }
+    }
+}
+
+impl<'a> AsyncRead for Trickle<'a> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        self.ready = false;
+        if self.buf.is_empty() || buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        buf[0] = self.buf[0];
+        self.buf = &self.buf[1..];
+        Poll::Ready(Ok(1))
+    }
+}
+
+/// Accepts at most one byte per call, returning `Pending` before each byte.
+struct TrickleWriter {
+    buf: Vec<u8>,
+    ready: bool,
+}
+
+impl AsyncWrite for TrickleWriter {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        self.ready = false;
+        self.buf.push(buf[0]);
+        Poll::Ready(Ok(1))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn from_u16_trickle() {
+    let buf = [0xcd, 0x01, 0x2c];
+    let mut rd = Trickle::new(&buf);
+
+    assert_eq!(300u16, block_on(decode::read_int(&mut rd)).unwrap());
+    assert!(rd.buf.is_empty());
+}
+
+#[test]
+fn from_u16_into_u8_out_of_range() {
+    let buf = [0xcd, 0x01, 0x2c];
+    let mut rd = &buf[..];
+
+    assert!(block_on(decode::read_int::<u8, _>(&mut rd)).is_err());
+}
+
+#[test]
+fn from_str_type_mismatch_leaves_payload() {
+    let buf = [0x92, 0x01, 0x02];
+    let mut rd = &buf[..];
+
+    match block_on(decode::read_str_len(&mut rd)) {
+        Err(ValueReadError::TypeMismatch(Marker::FixArray(2))) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!([0x01, 0x02], rd);
+}
+
+#[test]
+fn from_eof_in_the_middle() {
+    let buf = [0xce, 0x00, 0x01];
+    let mut rd = Trickle::new(&buf);
+
+    match block_on(decode::read_int::<u32, _>(&mut rd)) {
+        Err(rmp::decode::NumValueReadError::InvalidDataRead(ref err)) => {
+            assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_str_trickle() {
+    let buf = [0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65];
+    let mut rd = Trickle::new(&buf);
+    let mut out = [0u8; 16];
+
+    assert_eq!("le message", block_on(decode::read_str(&mut rd, &mut out)).unwrap());
+}
+
+#[test]
+fn from_str_buffer_too_small() {
+    let buf = [0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65];
+    let mut rd = &buf[..];
+    let mut out = [0u8; 4];
+
+    match block_on(decode::read_str(&mut rd, &mut out)) {
+        Err(DecodeStringError::BufferSizeTooSmall(10)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_ext_meta() {
+    let buf = [0xc8, 0x00, 0x2a, 0x01];
+    let mut rd = Trickle::new(&buf);
+
+    let meta = block_on(decode::read_ext_meta(&mut rd)).unwrap();
+    assert_eq!((1, 42), (meta.typeid, meta.size));
+}
+
+#[test]
+fn from_value_bytes_nested_trickle() {
+    // [1, {"a": [nil, true]}, bin(3), ext(-1, 4 bytes), 0.5f32]
+    let buf = [
+        0x95, 0x01, 0x81, 0xa1, 0x61, 0x92, 0xc0, 0xc3, 0xc4, 0x03, 0x01, 0x02, 0x03, 0xd6, 0xff,
+        0x00, 0x00, 0x00, 0x00, 0xca, 0x3f, 0x00, 0x00, 0x00, 0xc0,
+    ];
+    let mut rd = Trickle::new(&buf);
+
+    let value = block_on(decode::read_value_bytes(&mut rd)).unwrap();
+    assert_eq!(&buf[..buf.len() - 1], &value[..]);
+    assert_eq!([0xc0], rd.buf);
+}
+
+fn limit_error(res: Result<Vec<u8>, ValueReadError>) -> LimitError {
+    match res {
+        Err(ValueReadError::InvalidDataRead(err)) => {
+            assert_eq!(io::ErrorKind::InvalidData, err.kind());
+            *err.into_inner().unwrap().downcast::<LimitError>().unwrap()
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_value_bytes_with_limits_depth() {
+    let limits = DecodeLimits { max_depth: 2, ..DecodeLimits::default() };
+
+    // [[], {1: 2}, 3]
+    let buf = [0x93, 0x90, 0x81, 0x01, 0x02, 0x03];
+    let mut rd = Trickle::new(&buf);
+    assert_eq!(&buf[..], &block_on(decode::read_value_bytes_with_limits(&mut rd, &limits)).unwrap()[..]);
+
+    // [[[]]]
+    let buf = [0x91, 0x91, 0x90];
+    let mut rd = Trickle::new(&buf);
+    let res = block_on(decode::read_value_bytes_with_limits(&mut rd, &limits));
+    assert_eq!(LimitError::DepthLimitExceeded, limit_error(res));
+}
+
+#[test]
+fn from_value_bytes_with_limits_len_before_data() {
+    let limits = DecodeLimits { max_data_len: 16, max_container_len: 2, ..DecodeLimits::default() };
+
+    // A binary of 4 GiB, of which nothing is sent.
+    let buf = [0xc6, 0xff, 0xff, 0xff, 0xff];
+    let mut rd = &buf[..];
+    let res = block_on(decode::read_value_bytes_with_limits(&mut rd, &limits));
+    assert_eq!(LimitError::LengthLimitExceeded(Marker::Bin32), limit_error(res));
+
+    // A map of 3 pairs.
+    let buf = [0x83];
+    let mut rd = &buf[..];
+    let res = block_on(decode::read_value_bytes_with_limits(&mut rd, &limits));
+    assert_eq!(LimitError::LengthLimitExceeded(Marker::FixMap(3)), limit_error(res));
+
+    // A map of 2 pairs is within the limits.
+    let buf = [0x82, 0x01, 0x02, 0x03, 0x04];
+    let mut rd = &buf[..];
+    assert_eq!(&buf[..], &block_on(decode::read_value_bytes_with_limits(&mut rd, &limits)).unwrap()[..]);
+}
+
+#[test]
+fn from_value_bytes_with_limits_total() {
+    let limits = DecodeLimits { max_total_len: 4, ..DecodeLimits::default() };
+
+    let buf = [0x92, 0xcd, 0x01, 0x2c, 0x01];
+    let mut rd = &buf[..];
+    let res = block_on(decode::read_value_bytes_with_limits(&mut rd, &limits));
+    assert_eq!(LimitError::TotalLimitExceeded, limit_error(res));
+}
+
+#[test]
+fn pass_roundtrip_trickle() {
+    let mut wr = TrickleWriter { buf: Vec::new(), ready: false };
+
+    block_on(encode::write_nil(&mut wr)).unwrap();
+    block_on(encode::write_bool(&mut wr, true)).unwrap();
+    assert_eq!(Marker::U16, block_on(encode::write_uint(&mut wr, 300)).unwrap());
+    assert_eq!(Marker::I8, block_on(encode::write_sint(&mut wr, -100)).unwrap());
+    block_on(encode::write_f32(&mut wr, 1.5)).unwrap();
+    block_on(encode::write_f64(&mut wr, 0.1)).unwrap();
+    assert_eq!(Marker::FixArray(2), block_on(encode::write_array_len(&mut wr, 2)).unwrap());
+    block_on(encode::write_str(&mut wr, "le message")).unwrap();
+    block_on(encode::write_bin(&mut wr, &[1, 2, 3])).unwrap();
+    assert_eq!(Marker::Map16, block_on(encode::write_map_len(&mut wr, 16)).unwrap());
+    assert_eq!(Marker::FixExt4, block_on(encode::write_ext_meta(&mut wr, 4, 42)).unwrap());
+    block_on(encode::write_all(&mut wr, vec![0xc0])).unwrap();
+
+    let mut rd = &wr.buf[..];
+    let mut out = [0u8; 16];
+
+    block_on(decode::read_nil(&mut rd)).unwrap();
+    assert!(block_on(decode::read_bool(&mut rd)).unwrap());
+    assert_eq!(300u16, block_on(decode::read_int(&mut rd)).unwrap());
+    assert_eq!(-100i8, block_on(decode::read_int(&mut rd)).unwrap());
+    assert_eq!(1.5, block_on(decode::read_f32(&mut rd)).unwrap());
+    assert_eq!(0.1, block_on(decode::read_f64(&mut rd)).unwrap());
+    assert_eq!(2, block_on(decode::read_array_len(&mut rd)).unwrap());
+    assert_eq!("le message", block_on(decode::read_str(&mut rd, &mut out)).unwrap());
+    assert_eq!(3, block_on(decode::read_bin_len(&mut rd)).unwrap());
+    rd = &rd[3..];
+    assert_eq!(16, block_on(decode::read_map_len(&mut rd)).unwrap());
+    let meta = block_on(decode::read_ext_meta(&mut rd)).unwrap();
+    assert_eq!((42, 4), (meta.typeid, meta.size));
+    assert_eq!(Marker::Null, block_on(decode::read_marker(&mut rd)).unwrap());
+    assert!(rd.is_empty());
+}
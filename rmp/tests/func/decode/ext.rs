@@ -1,5 +1,6 @@
 use std::io::Cursor;
 
+use msgpack::{Marker, Timestamp};
 use msgpack::decode::*;
 
 #[test]
@@ -126,3 +127,99 @@ fn from_ext32_read_ext_meta() {
     assert_eq!(ExtMeta { typeid: 1, size: 4294967295 }, read_ext_meta(&mut cur).unwrap());
     assert_eq!(6, cur.position());
 }
+
+#[test]
+fn from_fixext1_read_ext_meta_reserved_type() {
+    let buf: &[u8] = &[0xd4, 0x80, 0x00];
+    let mut cur = Cursor::new(buf);
+
+    let meta = read_ext_meta(&mut cur).unwrap();
+    assert_eq!(ExtMeta { typeid: -128, size: 1 }, meta);
+    assert_eq!(ExtTypeKind::Reserved, meta.kind());
+    assert!(meta.is_reserved());
+    assert!(!meta.is_application());
+    assert_eq!(2, cur.position());
+}
+
+#[test]
+fn ext_type_kind_ranges() {
+    assert_eq!(ExtTypeKind::Application, ext_type_kind(0));
+    assert_eq!(ExtTypeKind::Application, ext_type_kind(127));
+    assert_eq!(ExtTypeKind::Predefined, ext_type_kind(-1));
+    assert_eq!(ExtTypeKind::Reserved, ext_type_kind(-2));
+    assert_eq!(ExtTypeKind::Reserved, ext_type_kind(-128));
+}
+
+#[test]
+fn from_fixext4_read_timestamp() {
+    let buf: &[u8] = &[0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(Timestamp::from_secs(1514862245), read_timestamp(&mut cur).unwrap());
+    assert_eq!(6, cur.position());
+}
+
+#[test]
+fn from_fixext8_read_timestamp() {
+    let buf: &[u8] = &[0xd7, 0xff, 0xa1, 0xdc, 0xd7, 0xc8, 0x5a, 0x4a, 0xf6, 0xa5];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(Timestamp::new(1514862245, 678901234).unwrap(), read_timestamp(&mut cur).unwrap());
+    assert_eq!(10, cur.position());
+}
+
+#[test]
+fn from_ext8_read_timestamp() {
+    let buf: &[u8] = &[0xc7, 0x0c, 0xff,
+                       0x3b, 0x9a, 0xc9, 0xff,
+                       0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(Timestamp::new(-1, 999999999).unwrap(), read_timestamp(&mut cur).unwrap());
+    assert_eq!(15, cur.position());
+}
+
+#[test]
+fn from_fixext8_read_timestamp_with_invalid_nsecs() {
+    // Nanoseconds field is set to 1000000000.
+    let buf: &[u8] = &[0xd7, 0xff, 0xee, 0x6b, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(TimestampReadError::InvalidNanoseconds(1000000000)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_fixext4_read_timestamp_with_non_timestamp_type() {
+    let buf: &[u8] = &[0xd6, 0x01, 0x5a, 0x4a, 0xf6, 0xa5];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(TimestampReadError::ExtTypeMismatch(1)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_fixext2_read_timestamp_with_invalid_length() {
+    let buf: &[u8] = &[0xd5, 0xff, 0x00, 0x00];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(TimestampReadError::InvalidDataLength(2)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_nil_read_timestamp() {
+    let buf: &[u8] = &[0xc0];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(TimestampReadError::TypeMismatch(Marker::Null)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
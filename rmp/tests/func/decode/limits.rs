@@ -0,0 +1,92 @@
+use msgpack::Marker;
+use msgpack::decode::*;
+
+#[test]
+fn limiter_accounts_fixed_size_values() {
+    let mut limiter = Limiter::new(DecodeLimits::default());
+
+    limiter.check_marker(Marker::U64).unwrap();
+    limiter.check_marker(Marker::FixStr(3)).unwrap();
+    limiter.check_len(Marker::FixStr(3), 3).unwrap();
+    limiter.check_marker(Marker::FixExt4).unwrap();
+
+    assert_eq!(9 + 4 + 6, limiter.total_len());
+}
+
+#[test]
+fn limiter_accounts_length_prefixed_values() {
+    let mut limiter = Limiter::new(DecodeLimits::default());
+
+    limiter.check_marker(Marker::Str8).unwrap();
+    limiter.check_len(Marker::Str8, 100).unwrap();
+    limiter.check_marker(Marker::Ext16).unwrap();
+    limiter.check_len(Marker::Ext16, 1000).unwrap();
+    limiter.check_marker(Marker::Array32).unwrap();
+    limiter.check_len(Marker::Array32, 1000).unwrap();
+
+    assert_eq!(2 + 100 + 4 + 1000 + 5, limiter.total_len());
+}
+
+#[test]
+fn limiter_data_and_container_limits_are_separate() {
+    let limits = DecodeLimits { max_data_len: 4, max_container_len: 2, ..DecodeLimits::default() };
+    let mut limiter = Limiter::new(limits);
+
+    limiter.check_len(Marker::Bin8, 4).unwrap();
+    assert_eq!(Err(LimitError::LengthLimitExceeded(Marker::Bin8)), limiter.check_len(Marker::Bin8, 5));
+    assert_eq!(Err(LimitError::LengthLimitExceeded(Marker::FixStr(5))),
+               limiter.check_marker(Marker::FixStr(5)));
+
+    limiter.check_marker(Marker::FixMap(2)).unwrap();
+    assert_eq!(Err(LimitError::LengthLimitExceeded(Marker::FixArray(3))),
+               limiter.check_marker(Marker::FixArray(3)));
+    assert_eq!(Err(LimitError::LengthLimitExceeded(Marker::Map16)),
+               limiter.check_len(Marker::Map16, 3));
+}
+
+#[test]
+fn limiter_total_limit_exceeded_before_payload() {
+    let limits = DecodeLimits { max_total_len: 16, ..DecodeLimits::default() };
+    let mut limiter = Limiter::new(limits);
+
+    limiter.check_marker(Marker::Bin32).unwrap();
+    assert_eq!(Err(LimitError::TotalLimitExceeded), limiter.check_len(Marker::Bin32, u32::max_value()));
+
+    limiter.reset();
+    assert_eq!(0, limiter.total_len());
+    limiter.check_marker(Marker::Bin8).unwrap();
+    limiter.check_len(Marker::Bin8, 14).unwrap();
+}
+
+#[test]
+fn limiter_depth_limit() {
+    let limits = DecodeLimits { max_depth: 2, ..DecodeLimits::default() };
+    let mut limiter = Limiter::new(limits);
+
+    limiter.enter().unwrap();
+    limiter.enter().unwrap();
+    assert_eq!(Err(LimitError::DepthLimitExceeded), limiter.enter());
+    assert_eq!(2, limiter.depth());
+
+    limiter.leave();
+    limiter.enter().unwrap();
+}
+
+#[test]
+fn validate_total_limit() {
+    let limits = DecodeLimits { max_total_len: 4, ..DecodeLimits::default() };
+
+    assert_eq!(Ok(4), validate(&[0x93, 0x01, 0x02, 0x03], &limits));
+    assert_eq!(Err(ValidateError::LengthLimitExceeded(4)), validate(&[0x94, 0x01, 0x02, 0x03, 0x04], &limits));
+    assert_eq!(Err(ValidateError::LengthLimitExceeded(0)), validate(&[0xa4, 0x61, 0x62, 0x63, 0x64], &limits));
+}
+
+#[test]
+fn unpack_total_limit_exceeded_before_payload() {
+    let limits = DecodeLimits { max_total_len: 1024, ..DecodeLimits::default() };
+    let mut unpacker = Unpacker::with_limits(limits);
+
+    unpacker.feed(&[0xc0, 0xc6, 0x00, 0x00, 0x04, 0x00]);
+    assert_eq!(Some(&[0xc0][..]), unpacker.next_message().unwrap());
+    assert_eq!(Err(ValidateError::LengthLimitExceeded(0)), unpacker.next_message());
+}
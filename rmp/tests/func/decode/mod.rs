@@ -8,3 +8,11 @@ mod bin;
 mod array;
 mod map;
 mod ext;
+mod skip;
+mod token;
+mod validate;
+mod limits;
+mod strict;
+mod number;
+mod unpack;
+mod offset;
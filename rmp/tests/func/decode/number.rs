@@ -0,0 +1,93 @@
+use std::io::Cursor;
+use std::f64;
+
+use msgpack::Marker;
+use msgpack::decode::*;
+
+#[test]
+fn pass_read_number() {
+    let cases: &[(&[u8], Number)] = &[
+        (&[0x2a], Number::U64(42)),
+        (&[0xe0], Number::I64(-32)),
+        (&[0xd0, 0x05], Number::U64(5)),
+        (&[0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe], Number::I64(-2)),
+        (&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], Number::U64(u64::MAX)),
+        (&[0xca, 0x3f, 0xc0, 0x00, 0x00], Number::F32(1.5)),
+        (&[0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], Number::F64(1.5)),
+    ];
+
+    for &(buf, num) in cases {
+        let mut cur = Cursor::new(buf);
+        assert_eq!(num, read_number(&mut cur).unwrap());
+        assert_eq!(buf.len() as u64, cur.position());
+    }
+}
+
+#[test]
+fn fail_read_number_type_mismatch() {
+    match read_number(&mut &[0xc0][..]) {
+        Err(ValueReadError::TypeMismatch(Marker::Null)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_read_float_lossless_from_int() {
+    assert_eq!(3.0, read_float::<f64, _>(&mut &[0x03][..], NumConversion::Lossless).unwrap());
+    assert_eq!(-3.0, read_float::<f32, _>(&mut &[0xfd][..], NumConversion::Lossless).unwrap());
+
+    // 2^53 is exactly representable as f64.
+    let buf = [0xcf, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert_eq!(9007199254740992.0,
+               read_float::<f64, _>(&mut &buf[..], NumConversion::Lossless).unwrap());
+}
+
+#[test]
+fn fail_read_float_lossless_from_int() {
+    // 2^53 + 1 isn't representable as f64.
+    let buf = [0xcf, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01];
+
+    match read_float::<f64, _>(&mut &buf[..], NumConversion::Lossless) {
+        Err(NumValueReadError::OutOfRange) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(9007199254740992.0,
+               read_float::<f64, _>(&mut &buf[..], NumConversion::Lossy).unwrap());
+
+    let buf = [0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    assert!(read_float::<f64, _>(&mut &buf[..], NumConversion::Lossless).is_err());
+}
+
+#[test]
+fn pass_read_float_widening() {
+    let buf = [0xca, 0x3d, 0xcc, 0xcc, 0xcd];
+    assert_eq!(0.1f32 as f64,
+               read_float::<f64, _>(&mut &buf[..], NumConversion::Lossless).unwrap());
+
+    let buf = [0xca, 0x7f, 0xc0, 0x00, 0x00];
+    assert!(read_float::<f64, _>(&mut &buf[..], NumConversion::Lossless).unwrap().is_nan());
+}
+
+#[test]
+fn pass_read_float_narrowing() {
+    let buf = [0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert_eq!(1.5, read_float::<f32, _>(&mut &buf[..], NumConversion::Lossless).unwrap());
+
+    let buf = [0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a];
+    match read_float::<f32, _>(&mut &buf[..], NumConversion::Lossless) {
+        Err(NumValueReadError::OutOfRange) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(0.1, read_float::<f32, _>(&mut &buf[..], NumConversion::Lossy).unwrap());
+}
+
+#[test]
+fn pass_number_convert() {
+    assert_eq!(Some(3u8), Number::F64(3.0).convert(NumConversion::Lossless));
+    assert_eq!(None::<u8>, Number::F64(2.5).convert(NumConversion::Lossless));
+    assert_eq!(Some(2u8), Number::F64(2.5).convert(NumConversion::Lossy));
+    assert_eq!(None::<u8>, Number::U64(256).convert(NumConversion::Lossy));
+    assert_eq!(None::<u64>, Number::I64(-1).convert(NumConversion::Lossy));
+    assert_eq!(None::<i64>, Number::F64(f64::NAN).convert(NumConversion::Lossy));
+    assert_eq!(None::<f32>, Number::F64(1e300).convert(NumConversion::Lossless));
+}
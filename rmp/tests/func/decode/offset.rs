@@ -0,0 +1,77 @@
+use msgpack::Marker;
+use msgpack::decode::*;
+
+#[test]
+fn offset_reader_counts_bytes() {
+    let buf = [0xc0, 0xcd, 0x01, 0x2c, 0xa2, 0x6f, 0x6b];
+    let mut rd = OffsetReader::new(&buf[..]);
+
+    rd.decode("nil", read_nil).unwrap();
+    assert_eq!(1, rd.offset());
+    assert_eq!(300, rd.decode("u16", read_int::<u16, _>).unwrap());
+    assert_eq!(4, rd.offset());
+    assert_eq!(2, rd.decode("string", read_str_len).unwrap());
+    assert_eq!(5, rd.offset());
+}
+
+#[test]
+fn offset_reader_with_offset() {
+    let buf = [0xc3, 0xa1, 0x61];
+    let mut rd = OffsetReader::with_offset(&buf[..], 1421);
+
+    assert!(rd.decode("bool", read_bool).unwrap());
+
+    let err = rd.decode("u16", read_int::<u16, _>).unwrap_err();
+    assert_eq!("expected u16 at byte 1422, found FixStr(1)", format!("{}", err));
+}
+
+#[test]
+fn decode_error_type_mismatch() {
+    let buf = [0xd9, 0x01, 0x61];
+    let mut rd = OffsetReader::new(&buf[..]);
+
+    let err = rd.decode("u16", read_int::<u16, _>).unwrap_err();
+    match err.kind {
+        DecodeErrorKind::TypeMismatch(Marker::Str8) => (),
+        ref other => panic!("unexpected kind: {:?}", other),
+    }
+    assert_eq!("u16", err.expected);
+    assert_eq!(0, err.offset);
+    assert_eq!("expected u16 at byte 0, found Str8", format!("{}", err));
+}
+
+#[test]
+fn decode_error_out_of_range() {
+    let buf = [0xcd, 0x01, 0x2c];
+    let mut rd = OffsetReader::new(&buf[..]);
+
+    let err = rd.decode("u8", read_int::<u8, _>).unwrap_err();
+    assert_eq!("expected u8 at byte 0, found a value out of range", format!("{}", err));
+}
+
+#[test]
+fn decode_error_truncated_data_points_to_value_start() {
+    let buf = [0x91, 0xcd, 0x01];
+    let mut rd = OffsetReader::new(&buf[..]);
+
+    assert_eq!(1, rd.decode("array", read_array_len).unwrap());
+
+    let err = rd.decode("u16", read_int::<u16, _>).unwrap_err();
+    match err.kind {
+        DecodeErrorKind::InvalidDataRead(..) => (),
+        ref other => panic!("unexpected kind: {:?}", other),
+    }
+    assert_eq!(1, err.offset);
+    assert!(format!("{}", err).starts_with("expected u16 at byte 1, but failed to read the data"));
+}
+
+#[test]
+fn decode_error_string_buffer_too_small() {
+    let buf = [0xa5, 0x68, 0x65, 0x6c, 0x6c, 0x6f];
+    let mut rd = OffsetReader::new(&buf[..]);
+    let mut out = [0u8; 2];
+
+    let err = rd.decode("string", |rd| read_str(rd, &mut out)).unwrap_err();
+    assert_eq!("expected string at byte 0, found a string of 5 bytes, which doesn't fit the buffer",
+               format!("{}", err));
+}
@@ -0,0 +1,103 @@
+use std::io::{Cursor, ErrorKind};
+
+use msgpack::Marker;
+use msgpack::decode::*;
+
+#[test]
+fn skip_scalars() {
+    let buf: &[u8] = &[
+        0xc0,
+        0x2a,
+        0xcd, 0x01, 0x2c,
+        0xcb, 0x40, 0x09, 0x21, 0xfb, 0x54, 0x44, 0x2d, 0x18,
+        0xa3, 0x6b, 0x65, 0x79,
+        0xc4, 0x02, 0x00, 0x01,
+    ];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(1, skip_value(&mut cur).unwrap());
+    assert_eq!(1, skip_value(&mut cur).unwrap());
+    assert_eq!(3, skip_value(&mut cur).unwrap());
+    assert_eq!(9, skip_value(&mut cur).unwrap());
+    assert_eq!(4, skip_value(&mut cur).unwrap());
+    assert_eq!(4, skip_value(&mut cur).unwrap());
+    assert_eq!(buf.len() as u64, cur.position());
+}
+
+#[test]
+fn skip_nested_containers() {
+    // {"a": [1, [2, 3]], "b": {}}, nil
+    let buf: &[u8] = &[
+        0x82,
+        0xa1, 0x61, 0x92, 0x01, 0x92, 0x02, 0x03,
+        0xa1, 0x62, 0x80,
+        0xc0,
+    ];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(11, skip_value(&mut cur).unwrap());
+    assert_eq!(11, cur.position());
+    read_nil(&mut cur).unwrap();
+}
+
+#[test]
+fn skip_ext() {
+    let buf: &[u8] = &[
+        0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5,
+        0xc7, 0x02, 0x10, 0x01, 0x02,
+    ];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(6, skip_value(&mut cur).unwrap());
+    assert_eq!(5, skip_value(&mut cur).unwrap());
+    assert_eq!(11, cur.position());
+}
+
+#[test]
+fn skip_deeply_nested_array() {
+    let depth = 1_000_000;
+    let mut buf = vec![0x91; depth];
+    buf.push(0xc0);
+
+    assert_eq!(depth as u64 + 1, skip_value(&mut &buf[..]).unwrap());
+}
+
+#[test]
+fn skip_value_from_slice_returns_subslice() {
+    let buf: &[u8] = &[0x92, 0x01, 0xa1, 0x61, 0xc3];
+
+    let (value, tail) = skip_value_from_slice(buf).unwrap();
+
+    assert_eq!([0x92, 0x01, 0xa1, 0x61], value);
+    assert_eq!([0xc3], tail);
+}
+
+#[test]
+fn skip_truncated_payload_fails() {
+    let buf: &[u8] = &[0xa3, 0x6b, 0x65];
+
+    match skip_value_from_slice(buf) {
+        Err(ValueReadError::InvalidDataRead(ref err)) if err.kind() == ErrorKind::UnexpectedEof => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn skip_truncated_array_fails() {
+    let buf: &[u8] = &[0x93, 0x01, 0x02];
+
+    match skip_value(&mut &buf[..]) {
+        Err(ValueReadError::InvalidMarkerRead(ref err)) if err.kind() == ErrorKind::UnexpectedEof => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn skip_reserved_marker_fails() {
+    let buf: &[u8] = &[0x91, 0xc1];
+
+    match skip_value(&mut &buf[..]) {
+        Err(ValueReadError::TypeMismatch(Marker::Reserved)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,160 @@
+use std::io::Cursor;
+
+use msgpack::Marker;
+use msgpack::decode::*;
+
+#[test]
+fn pass_int_strict_minimal() {
+    let cases: &[(&[u8], i64)] = &[
+        (&[0x2a], 42),
+        (&[0xe0], -32),
+        (&[0xcc, 0x80], 128),
+        (&[0xcd, 0x01, 0x2c], 300),
+        (&[0xce, 0x00, 0x01, 0x00, 0x00], 65536),
+        (&[0xd0, 0xdf], -33),
+        (&[0xd1, 0xff, 0x7f], -129),
+        (&[0xd2, 0xff, 0xff, 0x7f, 0xff], -32769),
+        (&[0xd3, 0xff, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff], -2147483649),
+    ];
+
+    for &(buf, val) in cases {
+        let mut cur = Cursor::new(buf);
+        assert_eq!(val, read_int_strict::<i64, _>(&mut cur).unwrap());
+        assert_eq!(buf.len() as u64, cur.position());
+    }
+}
+
+#[test]
+fn fail_int_strict_non_minimal() {
+    let cases: &[(&[u8], Marker)] = &[
+        (&[0xcc, 0x7f], Marker::U8),
+        (&[0xcd, 0x00, 0xff], Marker::U16),
+        (&[0xce, 0x00, 0x00, 0xff, 0xff], Marker::U32),
+        (&[0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x2c], Marker::U64),
+        (&[0xd0, 0xe0], Marker::I8),
+        (&[0xd0, 0x05], Marker::I8),
+        (&[0xd1, 0xff, 0x80], Marker::I16),
+        (&[0xd2, 0xff, 0xff, 0x80, 0x00], Marker::I32),
+        (&[0xd3, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00], Marker::I64),
+    ];
+
+    for &(buf, marker) in cases {
+        let mut cur = Cursor::new(buf);
+        match read_int_strict::<i64, _>(&mut cur) {
+            Err(NumValueReadError::NonCanonical(actual)) => assert_eq!(marker, actual),
+            other => panic!("unexpected result for {:?}: {:?}", buf, other),
+        }
+        assert_eq!(buf.len() as u64, cur.position());
+    }
+}
+
+#[test]
+fn fail_int_strict_out_of_range() {
+    let buf = [0xcd, 0x01, 0x2c];
+    let mut cur = Cursor::new(&buf[..]);
+
+    match read_int_strict::<u8, _>(&mut cur) {
+        Err(NumValueReadError::OutOfRange) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_f64_strict() {
+    let buf = [0xca, 0x3f, 0xc0, 0x00, 0x00];
+    assert_eq!(1.5, read_f64_strict(&mut &buf[..]).unwrap());
+
+    // 0.1 can't be represented as f32 exactly.
+    let buf = [0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a];
+    assert_eq!(0.1, read_f64_strict(&mut &buf[..]).unwrap());
+}
+
+#[test]
+fn fail_f64_strict_non_minimal() {
+    let buf = [0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut cur = Cursor::new(&buf[..]);
+
+    match read_f64_strict(&mut cur) {
+        Err(ValueReadError::NonCanonical(Marker::F64)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(9, cur.position());
+}
+
+#[test]
+fn fail_str_len_strict_non_minimal() {
+    // A 3-byte string with Str32 marker.
+    let buf = [0xdb, 0x00, 0x00, 0x00, 0x03, 0x6b, 0x65, 0x79];
+    let mut cur = Cursor::new(&buf[..]);
+
+    match read_str_len_strict(&mut cur) {
+        Err(ValueReadError::NonCanonical(Marker::Str32)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(5, cur.position());
+
+    let buf = [0xd9, 0x1f];
+    match read_str_len_strict(&mut &buf[..]) {
+        Err(ValueReadError::NonCanonical(Marker::Str8)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let buf = [0xd9, 0x20];
+    assert_eq!(32, read_str_len_strict(&mut &buf[..]).unwrap());
+}
+
+#[test]
+fn pass_lengths_strict_minimal() {
+    assert_eq!(3, read_str_len_strict(&mut &[0xa3][..]).unwrap());
+    assert_eq!(0, read_bin_len_strict(&mut &[0xc4, 0x00][..]).unwrap());
+    assert_eq!(256, read_bin_len_strict(&mut &[0xc5, 0x01, 0x00][..]).unwrap());
+    assert_eq!(15, read_array_len_strict(&mut &[0x9f][..]).unwrap());
+    assert_eq!(16, read_array_len_strict(&mut &[0xdc, 0x00, 0x10][..]).unwrap());
+    assert_eq!(65536, read_map_len_strict(&mut &[0xdf, 0x00, 0x01, 0x00, 0x00][..]).unwrap());
+}
+
+#[test]
+fn fail_lengths_strict_non_minimal() {
+    match read_bin_len_strict(&mut &[0xc5, 0x00, 0xff][..]) {
+        Err(ValueReadError::NonCanonical(Marker::Bin16)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match read_array_len_strict(&mut &[0xdc, 0x00, 0x0f][..]) {
+        Err(ValueReadError::NonCanonical(Marker::Array16)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match read_map_len_strict(&mut &[0xdf, 0x00, 0x00, 0xff, 0xff][..]) {
+        Err(ValueReadError::NonCanonical(Marker::Map32)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_ext_meta_strict() {
+    let meta = read_ext_meta_strict(&mut &[0xd6, 0x01][..]).unwrap();
+    assert_eq!(ExtMeta { typeid: 1, size: 4 }, meta);
+
+    let meta = read_ext_meta_strict(&mut &[0xc7, 0x03, 0x01][..]).unwrap();
+    assert_eq!(ExtMeta { typeid: 1, size: 3 }, meta);
+}
+
+#[test]
+fn fail_ext_meta_strict_non_minimal() {
+    // 4 bytes of data with Ext8 marker instead of FixExt4.
+    match read_ext_meta_strict(&mut &[0xc7, 0x04, 0x01][..]) {
+        Err(ValueReadError::NonCanonical(Marker::Ext8)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match read_ext_meta_strict(&mut &[0xc8, 0x00, 0x03, 0x01][..]) {
+        Err(ValueReadError::NonCanonical(Marker::Ext16)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_strict_type_mismatch() {
+    match read_str_len_strict(&mut &[0xc0][..]) {
+        Err(ValueReadError::TypeMismatch(Marker::Null)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::io::Cursor;
 
 use msgpack::Marker;
@@ -240,3 +241,111 @@ fn example_process_sequence_of_strings() {
 
     assert_eq!(vec!["Unpacking", "multiple", "strings"], chunks);
 }
+
+#[test]
+fn from_str_and_bin_read_raw_len() {
+    let buf: &[u8] = &[0xa3, 0xda, 0x00, 0x20, 0xc4, 0x05, 0xc6, 0x00, 0x01, 0x00, 0x00];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(3, read_raw_len(&mut cur).unwrap());
+    assert_eq!(32, read_raw_len(&mut cur).unwrap());
+    assert_eq!(5, read_raw_len(&mut cur).unwrap());
+    assert_eq!(65536, read_raw_len(&mut cur).unwrap());
+    assert_eq!(buf.len() as u64, cur.position());
+}
+
+#[test]
+fn from_nil_read_raw_len() {
+    let buf: &[u8] = &[0xc0];
+    let mut cur = Cursor::new(buf);
+
+    match read_raw_len(&mut cur) {
+        Err(ValueReadError::TypeMismatch(Marker::Null)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_str_lossy_from_slice_borrows_valid() {
+    let buf: &[u8] = &[0xa2, 0x6f, 0x6b];
+
+    match read_str_lossy_from_slice(buf).unwrap() {
+        (Cow::Borrowed("ok"), tail) => assert!(tail.is_empty()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_str_lossy_from_slice_replaces_invalid() {
+    let buf: &[u8] = &[0xd9, 0x04, 0xc3, 0x28, 0x6f, 0x6b, 0xc0];
+
+    let (val, tail) = read_str_lossy_from_slice(buf).unwrap();
+    assert_eq!("\u{fffd}(ok", val);
+    assert_eq!([0xc0], tail);
+}
+
+#[test]
+fn from_str_lossy_from_slice_truncated() {
+    let buf: &[u8] = &[0xa5, 0x6f, 0x6b];
+
+    match read_str_lossy_from_slice(buf) {
+        Err(DecodeStringError::BufferSizeTooSmall(5)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_str_read_str_bytes_valid() {
+    let buf: &[u8] = &[0xa2, 0x6f, 0x6b];
+
+    let val = read_str_bytes(&mut &buf[..], 2).unwrap();
+    assert!(val.is_valid());
+    assert_eq!(Some("ok"), val.as_str());
+    assert_eq!(Ok("ok".to_string()), val.into_string());
+}
+
+#[test]
+fn from_str_read_str_bytes_invalid() {
+    let buf: &[u8] = &[0xa3, 0x61, 0xff, 0x62];
+
+    let val = read_str_bytes(&mut &buf[..], 16).unwrap();
+    assert_eq!(None, val.as_str());
+    assert_eq!(1, val.utf8_error().unwrap().valid_up_to());
+    assert_eq!(vec![0x61, 0xff, 0x62], val.clone().into_string().unwrap_err().into_bytes());
+}
+
+#[test]
+fn from_str_read_str_bytes_exceeds_max_len() {
+    let buf: &[u8] = &[0xa3, 0x61, 0x62, 0x63];
+    let mut cur = Cursor::new(buf);
+
+    match read_str_bytes(&mut cur, 2) {
+        Err(DecodeStringError::BufferSizeTooSmall(3)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(1, cur.position());
+}
+
+#[test]
+fn from_str_read_str_lossy_appends() {
+    let buf: &[u8] = &[0xa2, 0x6f, 0x6b, 0xa3, 0x61, 0xff, 0x62, 0xa1, 0x21];
+    let mut rd = &buf[..];
+    let mut out = String::new();
+
+    assert!(read_str_lossy(&mut rd, &mut out, 16).unwrap());
+    assert!(!read_str_lossy(&mut rd, &mut out, 16).unwrap());
+    assert!(read_str_lossy(&mut rd, &mut out, 16).unwrap());
+    assert_eq!("oka\u{fffd}b!", out);
+}
+
+#[test]
+fn from_str_read_str_lossy_type_mismatch() {
+    let buf: &[u8] = &[0xc0];
+    let mut out = String::new();
+
+    match read_str_lossy(&mut &buf[..], &mut out, 16) {
+        Err(DecodeStringError::TypeMismatch(Marker::Null)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(out.is_empty());
+}
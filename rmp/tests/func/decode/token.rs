@@ -0,0 +1,107 @@
+use std::io::{Cursor, ErrorKind};
+
+use msgpack::Marker;
+use msgpack::decode::*;
+
+#[test]
+fn tokenize_scalars_from_reader() {
+    let buf: &[u8] = &[
+        0xc0,
+        0xc3,
+        0x2a,
+        0xd0, 0x05,
+        0xd1, 0xff, 0x00,
+        0xca, 0x3f, 0x80, 0x00, 0x00,
+        0xa2, 0x68, 0x69,
+        0xc4, 0x01, 0x00,
+        0xd4, 0x10, 0x01,
+    ];
+    let mut tokenizer = Tokenizer::new(Cursor::new(buf));
+
+    assert_eq!(Some(Token::Nil), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::Bool(true)), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::UInt(42)), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::UInt(5)), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::Int(-256)), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::F32(1.0)), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::Str(b"hi")), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::Bin(&[0x00])), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::Ext(16, &[0x01])), tokenizer.next_token().unwrap());
+    assert_eq!(None, tokenizer.next_token().unwrap());
+    assert_eq!(buf.len() as u64, tokenizer.offset());
+}
+
+#[test]
+fn tokenize_nested_from_slice() {
+    // [{"a": []}, 1]
+    let buf: &[u8] = &[0x92, 0x81, 0xa1, 0x61, 0x90, 0x01];
+    let mut tokenizer = Tokenizer::from_slice(buf);
+    let mut depths = Vec::new();
+    let mut tokens = Vec::new();
+
+    while let Some(token) = tokenizer.next() {
+        tokens.push(token.unwrap());
+        depths.push(tokenizer.depth());
+    }
+
+    assert_eq!(vec![
+        Token::ArrayStart(2),
+        Token::MapStart(1),
+        Token::Str(b"a"),
+        Token::ArrayStart(0),
+        Token::UInt(1),
+    ], tokens);
+    assert_eq!(vec![1, 2, 2, 1, 0], depths);
+    assert_eq!(6, tokenizer.offset());
+}
+
+#[test]
+fn tokenize_borrows_from_slice() {
+    let buf: &[u8] = &[0xa3, 0x6b, 0x65, 0x79];
+
+    let token = Tokenizer::from_slice(buf).next().unwrap().unwrap();
+
+    match token {
+        Token::Str(val) => assert_eq!(buf[1..].as_ptr(), val.as_ptr()),
+        other => panic!("unexpected token: {:?}", other),
+    }
+}
+
+#[test]
+fn tokenize_truncated_container_fails() {
+    let buf: &[u8] = &[0x92, 0x01];
+    let mut tokenizer = Tokenizer::new(buf);
+
+    assert_eq!(Some(Token::ArrayStart(2)), tokenizer.next_token().unwrap());
+    assert_eq!(Some(Token::UInt(1)), tokenizer.next_token().unwrap());
+
+    match tokenizer.next_token() {
+        Err(ValueReadError::InvalidMarkerRead(ref err)) if err.kind() == ErrorKind::UnexpectedEof => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn tokenize_truncated_payload_fails() {
+    let buf: &[u8] = &[0xdb, 0xff, 0xff, 0xff, 0xff, 0x61];
+
+    match Tokenizer::new(buf).next_token() {
+        Err(ValueReadError::InvalidDataRead(ref err)) if err.kind() == ErrorKind::UnexpectedEof => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    match Tokenizer::from_slice(buf).next() {
+        Some(Err(ValueReadError::InvalidDataRead(ref err))) if err.kind() == ErrorKind::UnexpectedEof => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn tokenize_reserved_marker_fails() {
+    let buf: &[u8] = &[0xc1];
+
+    match Tokenizer::from_slice(buf).next() {
+        Some(Err(ValueReadError::TypeMismatch(Marker::Reserved))) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
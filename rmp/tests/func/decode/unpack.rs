@@ -0,0 +1,103 @@
+use msgpack::decode::*;
+
+// [1, {"a": [nil, true]}, bin(3), ext(-1, 4 bytes), str16("hello")], 300
+const MESSAGES: &[u8] = &[
+    0x95, 0x01, 0x81, 0xa1, 0x61, 0x92, 0xc0, 0xc3, 0xc4, 0x03, 0x01, 0x02, 0x03, 0xd6, 0xff, 0x00,
+    0x00, 0x00, 0x00, 0xda, 0x00, 0x05, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0xcd, 0x01, 0x2c,
+];
+
+fn unpack_chunked(buf: &[u8], chunk: usize) -> Vec<Vec<u8>> {
+    let mut unpacker = Unpacker::new();
+    let mut messages = Vec::new();
+
+    for data in buf.chunks(chunk) {
+        unpacker.feed(data);
+        while let Some(msg) = unpacker.next_message().unwrap() {
+            messages.push(msg.to_vec());
+        }
+    }
+
+    assert_eq!(0, unpacker.buffered_len());
+    messages
+}
+
+#[test]
+fn unpack_in_chunks_of_any_size() {
+    let expected = vec![MESSAGES[..27].to_vec(), MESSAGES[27..].to_vec()];
+
+    for chunk in 1..MESSAGES.len() + 1 {
+        assert_eq!(expected, unpack_chunked(MESSAGES, chunk), "chunk size {}", chunk);
+    }
+}
+
+#[test]
+fn unpack_many_messages_from_single_chunk() {
+    let buf = [0x01, 0x02, 0xa1, 0x61, 0x90, 0x80];
+
+    let messages = unpack_chunked(&buf, buf.len());
+
+    assert_eq!(vec![vec![0x01], vec![0x02], vec![0xa1, 0x61], vec![0x90], vec![0x80]], messages);
+}
+
+#[test]
+fn unpack_keeps_partial_message() {
+    let mut unpacker = Unpacker::new();
+
+    unpacker.feed(&[0xc0, 0xdb, 0x00]);
+    assert_eq!(Some(&[0xc0][..]), unpacker.next_message().unwrap());
+    assert_eq!(None, unpacker.next_message().unwrap());
+    assert_eq!(2, unpacker.buffered_len());
+
+    unpacker.feed(&[0x00, 0x00]);
+    assert_eq!(None, unpacker.next_message().unwrap());
+    unpacker.feed(&[0x02, 0x61]);
+    assert_eq!(None, unpacker.next_message().unwrap());
+    unpacker.feed(&[0x62]);
+    assert_eq!(Some(&[0xdb, 0x00, 0x00, 0x00, 0x02, 0x61, 0x62][..]),
+               unpacker.next_message().unwrap());
+    assert_eq!(0, unpacker.buffered_len());
+}
+
+#[test]
+fn unpack_large_payload_in_chunks() {
+    let mut buf = vec![0xc6, 0x00, 0x01, 0x00, 0x00];
+    buf.extend((0..65536).map(|idx| idx as u8));
+
+    let messages = unpack_chunked(&buf, 1000);
+
+    assert_eq!(vec![buf], messages);
+}
+
+#[test]
+fn unpack_reserved_marker_fails() {
+    let mut unpacker = Unpacker::new();
+
+    unpacker.feed(&[0xc0, 0x92, 0x01, 0xc1]);
+    assert_eq!(Some(&[0xc0][..]), unpacker.next_message().unwrap());
+    assert_eq!(Err(ValidateError::ReservedMarker(2)), unpacker.next_message());
+
+    // The stream can't be resynchronized.
+    unpacker.feed(&[0xc0]);
+    assert_eq!(Err(ValidateError::ReservedMarker(2)), unpacker.next_message());
+}
+
+#[test]
+fn unpack_length_limit_exceeded_before_payload() {
+    let limits = DecodeLimits { max_data_len: 16, ..DecodeLimits::default() };
+    let mut unpacker = Unpacker::with_limits(limits);
+
+    unpacker.feed(&[0x91, 0xc5, 0x10]);
+    assert_eq!(None, unpacker.next_message().unwrap());
+    unpacker.feed(&[0x00]);
+    assert_eq!(Err(ValidateError::LengthLimitExceeded(1)), unpacker.next_message());
+}
+
+#[test]
+fn unpack_depth_limit_exceeded() {
+    let limits = DecodeLimits { max_depth: 2, ..DecodeLimits::default() };
+    let mut unpacker = Unpacker::with_limits(limits);
+
+    unpacker.feed(&[0x91, 0x90, 0x91, 0x91, 0x90]);
+    assert_eq!(Some(&[0x91, 0x90][..]), unpacker.next_message().unwrap());
+    assert_eq!(Err(ValidateError::DepthLimitExceeded(2)), unpacker.next_message());
+}
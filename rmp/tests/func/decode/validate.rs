@@ -0,0 +1,97 @@
+use msgpack::decode::*;
+
+#[test]
+fn validate_single_values() {
+    let limits = DecodeLimits::default();
+
+    assert_eq!(Ok(1), validate(&[0xc0], &limits));
+    assert_eq!(Ok(3), validate(&[0xcd, 0x01, 0x2c], &limits));
+    assert_eq!(Ok(4), validate(&[0xa3, 0x6b, 0x65, 0x79], &limits));
+    assert_eq!(Ok(6), validate(&[0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5], &limits));
+    assert_eq!(Ok(7), validate(&[0x82, 0xa1, 0x61, 0x90, 0xa1, 0x62, 0x80], &limits));
+}
+
+#[test]
+fn validate_empty_buffer_fails() {
+    assert_eq!(Err(ValidateError::Truncated(0)), validate(&[], &DecodeLimits::default()));
+}
+
+#[test]
+fn validate_truncated_length_fails() {
+    let buf: &[u8] = &[0x91, 0xdc, 0x00];
+
+    assert_eq!(Err(ValidateError::Truncated(3)), validate(buf, &DecodeLimits::default()));
+}
+
+#[test]
+fn validate_truncated_ext_type_fails() {
+    let limits = DecodeLimits::default();
+
+    // The type id is a part of the header, like the length.
+    assert_eq!(Err(ValidateError::Truncated(1)), validate(&[0xd4], &limits));
+    assert_eq!(Err(ValidateError::Truncated(2)), validate(&[0xc7, 0x01], &limits));
+}
+
+#[test]
+fn validate_reserved_marker_fails() {
+    let buf: &[u8] = &[0x92, 0x01, 0xc1];
+
+    assert_eq!(Err(ValidateError::ReservedMarker(2)), validate(buf, &DecodeLimits::default()));
+}
+
+#[test]
+fn validate_depth_limit() {
+    let limits = DecodeLimits { max_depth: 2, ..DecodeLimits::default() };
+
+    assert_eq!(Ok(3), validate(&[0x91, 0x91, 0xc0], &limits));
+    assert_eq!(Err(ValidateError::DepthLimitExceeded(2)), validate(&[0x91, 0x91, 0x90], &limits));
+    assert_eq!(Err(ValidateError::DepthLimitExceeded(2)), validate(&[0x91, 0x91, 0x91, 0xc0], &limits));
+}
+
+#[test]
+fn validate_deeply_nested_input_does_not_overflow() {
+    let mut buf = vec![0x91; 1_000_000];
+    buf.push(0xc0);
+
+    let limits = DecodeLimits { max_depth: usize::MAX, ..DecodeLimits::default() };
+    assert_eq!(Ok(buf.len()), validate(&buf, &limits));
+    assert_eq!(Err(ValidateError::DepthLimitExceeded(1024)), validate(&buf, &DecodeLimits::default()));
+}
+
+#[test]
+fn validate_length_limit() {
+    let limits = DecodeLimits { max_data_len: 2, max_container_len: 2, ..DecodeLimits::default() };
+
+    assert_eq!(Ok(3), validate(&[0xa2, 0x61, 0x62], &limits));
+    assert_eq!(Err(ValidateError::LengthLimitExceeded(0)), validate(&[0xa3, 0x61, 0x62, 0x63], &limits));
+    assert_eq!(Err(ValidateError::LengthLimitExceeded(1)), validate(&[0x91, 0x93, 0x01, 0x02, 0x03], &limits));
+}
+
+#[test]
+fn validate_length_overflow_fails() {
+    let limits = DecodeLimits::default();
+
+    // Bin32 claiming 4 GiB of data.
+    assert_eq!(Err(ValidateError::LengthOverflow(0)),
+               validate(&[0xc6, 0xff, 0xff, 0xff, 0xff, 0x00], &limits));
+    // Array32 claiming more elements than there are bytes left.
+    assert_eq!(Err(ValidateError::LengthOverflow(0)),
+               validate(&[0xdd, 0xff, 0xff, 0xff, 0xff, 0xc0, 0xc0], &limits));
+}
+
+#[test]
+fn validate_detects_message_boundary() {
+    let buf: &[u8] = &[0x92, 0x01, 0x02, 0xa1, 0x61];
+
+    assert_eq!(Err(ValidateError::TrailingBytes(3)), validate(buf, &DecodeLimits::default()));
+}
+
+#[test]
+fn validate_multiple_messages() {
+    let buf: &[u8] = &[0x92, 0x01, 0x02, 0xa1, 0x61, 0xc0];
+    let limits = DecodeLimits::default();
+
+    assert_eq!(Ok(3), validate_messages(buf, 3, &limits));
+    assert_eq!(Err(ValidateError::TrailingBytes(5)), validate_messages(buf, 2, &limits));
+    assert_eq!(Err(ValidateError::Truncated(6)), validate_messages(buf, 4, &limits));
+}
@@ -1,4 +1,4 @@
-use msgpack::Marker;
+use msgpack::{Marker, Timestamp};
 use msgpack::encode::*;
 
 #[test]
@@ -73,3 +73,75 @@ fn pass_pack_meta_32() {
 
     assert_eq!([0xc9, 0xff, 0xff, 0xff, 0xff, 0x10], buf);
 }
+
+#[test]
+fn pass_pack_meta_reserved_type() {
+    let mut buf = Vec::new();
+
+    assert_eq!(Marker::FixExt1, write_ext_meta(&mut buf, 1, -128).unwrap());
+
+    assert_eq!(vec![0xd4, 0x80], buf);
+}
+
+#[test]
+fn pass_pack_meta_strict_predefined_type() {
+    let mut buf = Vec::new();
+
+    assert_eq!(Marker::FixExt4, write_ext_meta_strict(&mut buf, 4, -1).unwrap());
+
+    assert_eq!(vec![0xd6, 0xff], buf);
+}
+
+#[test]
+fn fail_pack_meta_strict_reserved_type() {
+    let mut buf = Vec::new();
+
+    match write_ext_meta_strict(&mut buf, 4, -2) {
+        Err(err @ ValueWriteError::ReservedExtType(-2)) => {
+            assert_eq!("reserved extension type -2", err.to_string());
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn pass_pack_timestamp32() {
+    let mut buf = Vec::new();
+
+    assert_eq!(Marker::FixExt4, write_timestamp(&mut buf, Timestamp::from_secs(1514862245)).unwrap());
+
+    assert_eq!(vec![0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5], buf);
+}
+
+#[test]
+fn pass_pack_timestamp64() {
+    let mut buf = Vec::new();
+    let ts = Timestamp::new(1514862245, 678901234).unwrap();
+
+    assert_eq!(Marker::FixExt8, write_timestamp(&mut buf, ts).unwrap());
+
+    assert_eq!(vec![0xd7, 0xff, 0xa1, 0xdc, 0xd7, 0xc8, 0x5a, 0x4a, 0xf6, 0xa5], buf);
+}
+
+#[test]
+fn pass_pack_timestamp64_when_secs_exceed_u32() {
+    let mut buf = Vec::new();
+
+    assert_eq!(Marker::FixExt8, write_timestamp(&mut buf, Timestamp::from_secs(4294967296)).unwrap());
+
+    assert_eq!(vec![0xd7, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00], buf);
+}
+
+#[test]
+fn pass_pack_timestamp96() {
+    let mut buf = Vec::new();
+    let ts = Timestamp::new(-1, 999999999).unwrap();
+
+    assert_eq!(Marker::Ext8, write_timestamp(&mut buf, ts).unwrap());
+
+    assert_eq!(vec![0xc7, 0x0c, 0xff,
+                    0x3b, 0x9a, 0xc9, 0xff,
+                    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], buf);
+}
@@ -0,0 +1,46 @@
+use msgpack::Timestamp;
+use msgpack::encode::*;
+
+// Checks the precomputed length against the number of bytes actually written.
+fn written<F: FnOnce(&mut Vec<u8>)>(f: F) -> usize {
+    let mut buf = Vec::new();
+    f(&mut buf);
+    buf.len()
+}
+
+#[test]
+fn uint_len_matches_write_uint() {
+    for &val in &[0, 127, 128, 255, 256, 65535, 65536, 4294967295, 4294967296, u64::MAX] {
+        assert_eq!(written(|buf| { write_uint(buf, val).unwrap(); }), uint_len(val), "{}", val);
+    }
+}
+
+#[test]
+fn sint_len_matches_write_sint() {
+    let vals = [i64::MIN, -2147483649, -2147483648, -32769, -32768, -129, -128, -33, -32, -1, 0,
+                127, 128, 65536, i64::MAX];
+    for &val in &vals {
+        assert_eq!(written(|buf| { write_sint(buf, val).unwrap(); }), sint_len(val), "{}", val);
+    }
+}
+
+#[test]
+fn header_lens_match_writers() {
+    for &len in &[0, 1, 2, 4, 8, 15, 16, 31, 32, 255, 256, 65535, 65536, u32::MAX] {
+        assert_eq!(written(|buf| { write_str_len(buf, len).unwrap(); }), str_header_len(len));
+        assert_eq!(written(|buf| { write_bin_len(buf, len).unwrap(); }), bin_header_len(len));
+        assert_eq!(written(|buf| { write_array_len(buf, len).unwrap(); }), array_header_len(len));
+        assert_eq!(written(|buf| { write_map_len(buf, len).unwrap(); }), map_header_len(len));
+        assert_eq!(written(|buf| { write_ext_meta(buf, len, 1).unwrap(); }), ext_header_len(len));
+    }
+}
+
+#[test]
+fn value_lens_match_writers() {
+    assert_eq!(written(|buf| write_str(buf, "le message").unwrap()), str_len("le message"));
+    assert_eq!(written(|buf| write_bin(buf, &[1, 2, 3]).unwrap()), bin_len(&[1, 2, 3]));
+
+    for &ts in &[Timestamp::from_secs(1), Timestamp::new(1, 1).unwrap(), Timestamp::from_secs(-1)] {
+        assert_eq!(written(|buf| { write_timestamp(buf, ts).unwrap(); }), timestamp_len(ts));
+    }
+}
@@ -7,3 +7,7 @@ mod int;
 mod map;
 mod null;
 mod string;
+mod len;
+mod slice;
+mod patch;
+mod vectored;
@@ -0,0 +1,83 @@
+use std::io::{Cursor, Seek, SeekFrom};
+
+use msgpack::Marker;
+use msgpack::encode::*;
+
+#[test]
+fn pass_patch_array_in_cursor() {
+    let mut cur = Cursor::new(vec![0xc0]);
+    cur.seek(SeekFrom::End(0)).unwrap();
+
+    let header = write_array_len_placeholder(&mut cur).unwrap();
+    assert_eq!(Marker::Array32, header.marker());
+    assert_eq!(1, header.offset());
+
+    write_uint(&mut cur, 42).unwrap();
+    write_nil(&mut cur).unwrap();
+    header.patch(&mut cur, 2).unwrap();
+    write_bool(&mut cur, true).unwrap();
+
+    assert_eq!(vec![0xc0, 0xdd, 0x00, 0x00, 0x00, 0x02, 0x2a, 0xc0, 0xc3], cur.into_inner());
+}
+
+#[test]
+fn pass_patch_nested_maps_in_cursor() {
+    let mut cur = Cursor::new(Vec::new());
+
+    let outer = write_map_len_placeholder(&mut cur).unwrap();
+    write_uint(&mut cur, 1).unwrap();
+    let inner = write_map_len_placeholder(&mut cur).unwrap();
+    inner.patch(&mut cur, 0).unwrap();
+    outer.patch(&mut cur, 1).unwrap();
+
+    assert_eq!(vec![0xdf, 0x00, 0x00, 0x00, 0x01, 0x01, 0xdf, 0x00, 0x00, 0x00, 0x00],
+               cur.into_inner());
+}
+
+#[test]
+fn pass_patch_vec_keeps_32bit_header() {
+    let mut buf = vec![0xc0];
+
+    let header = push_map_len_placeholder(&mut buf);
+    write_uint(&mut buf, 1).unwrap();
+    write_uint(&mut buf, 2).unwrap();
+
+    assert_eq!(Marker::Map32, header.patch_vec(&mut buf, 1, false));
+    assert_eq!(vec![0xc0, 0xdf, 0x00, 0x00, 0x00, 0x01, 0x01, 0x02], buf);
+}
+
+#[test]
+fn pass_patch_vec_compact_fix() {
+    let mut buf = vec![0xc0];
+
+    let header = push_map_len_placeholder(&mut buf);
+    write_uint(&mut buf, 1).unwrap();
+    write_uint(&mut buf, 2).unwrap();
+
+    assert_eq!(Marker::FixMap(1), header.patch_vec(&mut buf, 1, true));
+    assert_eq!(vec![0xc0, 0x81, 0x01, 0x02], buf);
+}
+
+#[test]
+fn pass_patch_vec_compact_u16() {
+    let mut buf = Vec::new();
+
+    let header = push_array_len_placeholder(&mut buf);
+    for _ in 0..300 {
+        write_nil(&mut buf).unwrap();
+    }
+
+    assert_eq!(Marker::Array16, header.patch_vec(&mut buf, 300, true));
+    assert_eq!(303, buf.len());
+    assert_eq!([0xdc, 0x01, 0x2c, 0xc0], buf[..4]);
+}
+
+#[test]
+fn pass_patch_vec_compact_u32() {
+    let mut buf = Vec::new();
+
+    let header = push_array_len_placeholder(&mut buf);
+
+    assert_eq!(Marker::Array32, header.patch_vec(&mut buf, 65536, true));
+    assert_eq!(vec![0xdd, 0x00, 0x01, 0x00, 0x00], buf);
+}
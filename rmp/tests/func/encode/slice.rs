@@ -0,0 +1,54 @@
+use msgpack::Timestamp;
+use msgpack::encode::*;
+
+#[test]
+fn pass_write_uint_to_slice() {
+    let mut buf = [0xff; 4];
+
+    assert_eq!(3, write_uint_to_slice(&mut buf, 300).unwrap());
+    assert_eq!([0xcd, 0x01, 0x2c, 0xff], buf);
+}
+
+#[test]
+fn pass_write_sequence_to_slice() {
+    let mut buf = [0; 32];
+    let mut pos = 0;
+
+    pos += write_map_len_to_slice(&mut buf[pos..], 1).unwrap();
+    pos += write_str_to_slice(&mut buf[pos..], "id").unwrap();
+    pos += write_array_len_to_slice(&mut buf[pos..], 5).unwrap();
+    pos += write_nil_to_slice(&mut buf[pos..]).unwrap();
+    pos += write_bool_to_slice(&mut buf[pos..], true).unwrap();
+    pos += write_sint_to_slice(&mut buf[pos..], -33).unwrap();
+    pos += write_f32_to_slice(&mut buf[pos..], 1.0).unwrap();
+    pos += write_bin_to_slice(&mut buf[pos..], &[0x2a]).unwrap();
+
+    assert_eq!([0x81, 0xa2, 0x69, 0x64, 0x95, 0xc0, 0xc3, 0xd0, 0xdf,
+                0xca, 0x3f, 0x80, 0x00, 0x00, 0xc4, 0x01, 0x2a], buf[..pos]);
+}
+
+#[test]
+fn pass_write_ext_to_slice() {
+    let mut buf = [0; 16];
+
+    assert_eq!(2, write_ext_meta_to_slice(&mut buf, 4, 16).unwrap());
+    assert_eq!([0xd6, 0x10], buf[..2]);
+
+    assert_eq!(6, write_timestamp_to_slice(&mut buf, Timestamp::from_secs(1514862245)).unwrap());
+    assert_eq!([0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5], buf[..6]);
+
+    assert_eq!(9, write_f64_to_slice(&mut buf, 0.0).unwrap());
+}
+
+#[test]
+fn fail_write_to_short_slice_leaves_it_untouched() {
+    let mut buf = [0; 4];
+
+    assert_eq!(Err(SliceWriteError::BufferTooSmall { required: 5, available: 4 }),
+               write_uint_to_slice(&mut buf, 65536));
+    assert_eq!(Err(SliceWriteError::BufferTooSmall { required: 5, available: 4 }),
+               write_bin_to_slice(&mut buf, &[1, 2, 3]));
+    assert_eq!([0; 4], buf);
+
+    assert_eq!(Err(SliceWriteError::BufferTooSmall { required: 1, available: 0 }), write_nil_to_slice(&mut []));
+}
@@ -36,3 +36,23 @@ fn pass_pack_len_u32() {
 
     assert_eq!([0xdb, 0xff, 0xff, 0xff, 0xff], buf);
 }
+
+#[test]
+fn pass_pack_raw_len_skips_str8() {
+    let mut buf = Vec::new();
+
+    assert_eq!(Marker::FixStr(31), write_raw_len(&mut buf, 31).unwrap());
+    assert_eq!(Marker::Str16, write_raw_len(&mut buf, 32).unwrap());
+    assert_eq!(Marker::Str16, write_raw_len(&mut buf, 65535).unwrap());
+    assert_eq!(Marker::Str32, write_raw_len(&mut buf, 65536).unwrap());
+
+    assert_eq!(vec![0xbf, 0xda, 0x00, 0x20, 0xda, 0xff, 0xff, 0xdb, 0x00, 0x01, 0x00, 0x00], buf);
+}
+
+#[test]
+fn pass_pack_raw() {
+    let mut buf = Vec::new();
+    write_raw(&mut buf, &[0xcc, 0x80]).unwrap();
+
+    assert_eq!(vec![0xa2, 0xcc, 0x80], buf);
+}
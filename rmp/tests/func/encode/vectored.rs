@@ -0,0 +1,152 @@
+use std::io::{self, BufWriter, IoSlice, Write};
+
+use msgpack::encode::*;
+
+/// Records each write call, accepting at most `limit` bytes per call.
+struct Calls {
+    calls: Vec<Vec<u8>>,
+    limit: usize,
+    vectored: bool,
+}
+
+impl Calls {
+    fn new(limit: usize, vectored: bool) -> Calls {
+        Calls { calls: Vec::new(), limit: limit, vectored: vectored }
+    }
+
+    fn concat(&self) -> Vec<u8> {
+        self.calls.concat()
+    }
+}
+
+impl Write for Calls {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.limit);
+        self.calls.push(buf[..len].to_vec());
+        Ok(len)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        if !self.vectored {
+            let buf = bufs.iter().find(|buf| !buf.is_empty()).map_or(&[][..], |buf| &buf[..]);
+            return self.write(buf);
+        }
+
+        let mut call = Vec::new();
+        for buf in bufs {
+            call.extend_from_slice(buf);
+        }
+        call.truncate(self.limit);
+        let len = call.len();
+        self.calls.push(call);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Fails with `Interrupted` on every other call.
+struct Interrupting {
+    buf: Vec<u8>,
+    interrupt: bool,
+}
+
+impl Write for Interrupting {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+        }
+        self.buf.push(buf[0]);
+        Ok(1)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn pass_bin_vectored_single_call() {
+    let mut wr = Calls::new(usize::MAX, true);
+
+    write_bin_vectored(&mut wr, &[1, 2, 3]).unwrap();
+
+    assert_eq!(vec![vec![0xc4, 0x03, 0x01, 0x02, 0x03]], wr.calls);
+}
+
+#[test]
+fn pass_bin_vectored_without_vectored_support() {
+    let mut wr = Calls::new(usize::MAX, false);
+
+    write_bin_vectored(&mut wr, &[1, 2, 3]).unwrap();
+
+    assert_eq!(vec![vec![0xc4, 0x03], vec![0x01, 0x02, 0x03]], wr.calls);
+}
+
+#[test]
+fn pass_bin_vectored_partial_writes() {
+    let mut wr = Calls::new(3, true);
+    let data: Vec<u8> = (0..10).collect();
+
+    write_bin_vectored(&mut wr, &data).unwrap();
+
+    let mut expected = vec![0xc4, 0x0a];
+    expected.extend_from_slice(&data);
+    assert_eq!(expected, wr.concat());
+    assert_eq!(4, wr.calls.len());
+}
+
+#[test]
+fn pass_str_and_raw_vectored() {
+    let mut wr = Calls::new(usize::MAX, true);
+    let data = "a".repeat(32);
+
+    write_str_vectored(&mut wr, &data).unwrap();
+    write_raw_vectored(&mut wr, data.as_bytes()).unwrap();
+
+    assert_eq!([0xd9, 0x20, 0x61], wr.calls[0][..3]);
+    assert_eq!([0xda, 0x00, 0x20, 0x61], wr.calls[1][..4]);
+    assert_eq!(2, wr.calls.len());
+}
+
+#[test]
+fn pass_bin_vectored_retries_interrupted() {
+    let mut wr = Interrupting { buf: Vec::new(), interrupt: false };
+
+    write_bin_vectored(&mut wr, &[1, 2]).unwrap();
+
+    assert_eq!(vec![0xc4, 0x02, 0x01, 0x02], wr.buf);
+}
+
+#[test]
+fn fail_bin_vectored_write_zero() {
+    let mut buf = [0u8; 3];
+
+    match write_bin_vectored(&mut &mut buf[..], &[1, 2, 3]) {
+        Err(ValueWriteError::InvalidDataWrite(ref err)) => {
+            assert_eq!(io::ErrorKind::WriteZero, err.kind());
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_buf_writer_batches_headers() {
+    let mut wr = BufWriter::with_capacity(16, Calls::new(usize::MAX, true));
+    let data = [0xff; 64];
+
+    write_array_len(&mut wr, 2).unwrap();
+    write_uint(&mut wr, 300).unwrap();
+    assert!(wr.get_ref().calls.is_empty());
+
+    // The payload exceeds the buffer, so it's passed on directly after the pending headers.
+    write_bin_vectored(&mut wr, &data).unwrap();
+
+    let calls = &wr.get_ref().calls;
+    assert_eq!(2, calls.len());
+    assert_eq!(vec![0x92, 0xcd, 0x01, 0x2c, 0xc4, 0x40], calls[0]);
+    assert_eq!(data.to_vec(), calls[1]);
+}
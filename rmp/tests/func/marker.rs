@@ -0,0 +1,81 @@
+use msgpack::{Family, Marker, Payload};
+use msgpack::decode::skip_value;
+
+#[test]
+fn family_of_every_marker() {
+    let expected = [
+        (0x00, Family::Int), (0x7f, Family::Int), (0x80, Family::Map), (0x90, Family::Array),
+        (0xa0, Family::Str), (0xc0, Family::Nil), (0xc1, Family::Reserved), (0xc2, Family::Bool),
+        (0xc3, Family::Bool), (0xc4, Family::Bin), (0xc6, Family::Bin), (0xc7, Family::Ext),
+        (0xc9, Family::Ext), (0xca, Family::Float), (0xcb, Family::Float), (0xcc, Family::Int),
+        (0xd3, Family::Int), (0xd4, Family::Ext), (0xd8, Family::Ext), (0xd9, Family::Str),
+        (0xdb, Family::Str), (0xdc, Family::Array), (0xdd, Family::Array), (0xde, Family::Map),
+        (0xdf, Family::Map), (0xe0, Family::Int), (0xff, Family::Int),
+    ];
+
+    for &(byte, family) in expected.iter() {
+        assert_eq!(family, Marker::from_u8(byte).family(), "marker {:#x}", byte);
+    }
+}
+
+#[test]
+fn header_len_and_payload_of_scalars() {
+    assert_eq!(0, Marker::Null.header_len());
+    assert_eq!(Some(Payload::Bytes(0)), Marker::FixNeg(-1).payload(&[]));
+    assert_eq!(Some(Payload::Bytes(1)), Marker::I8.payload(&[]));
+    assert_eq!(Some(Payload::Bytes(4)), Marker::F32.payload(&[]));
+    assert_eq!(Some(Payload::Bytes(8)), Marker::U64.payload(&[]));
+}
+
+#[test]
+fn payload_of_length_prefixed_markers() {
+    assert_eq!(Some(Payload::Bytes(31)), Marker::FixStr(31).payload(&[]));
+    assert_eq!(Some(Payload::Bytes(0x0102)), Marker::Bin16.payload(&[0x01, 0x02]));
+    assert_eq!(Some(Payload::Bytes(0x01020304)), Marker::Str32.payload(&[0x01, 0x02, 0x03, 0x04]));
+    assert_eq!(Some(Payload::Values(15)), Marker::FixArray(15).payload(&[]));
+    assert_eq!(Some(Payload::Values(0x0102)), Marker::Array16.payload(&[0x01, 0x02]));
+    assert_eq!(Some(Payload::Values(2 * 0xffffffff)),
+               Marker::Map32.payload(&[0xff, 0xff, 0xff, 0xff]));
+}
+
+#[test]
+fn payload_of_ext_excludes_type_id() {
+    assert_eq!(1, Marker::FixExt2.header_len());
+    assert_eq!(Some(Payload::Bytes(2)), Marker::FixExt2.payload(&[0x2a]));
+    assert_eq!(Some(Payload::Bytes(16)), Marker::FixExt16.payload(&[0x2a]));
+    assert_eq!(Some(Payload::Bytes(3)), Marker::Ext8.payload(&[0x03, 0x2a]));
+    assert_eq!(Some(Payload::Bytes(0x0100)), Marker::Ext16.payload(&[0x01, 0x00, 0x2a]));
+    assert_eq!(None, Marker::Ext32.payload(&[0x00, 0x00, 0x00, 0x01]));
+}
+
+#[test]
+fn payload_of_short_header_or_reserved() {
+    assert_eq!(None, Marker::Str8.payload(&[]));
+    assert_eq!(None, Marker::FixExt1.payload(&[]));
+    assert_eq!(None, Marker::Reserved.payload(&[0x00]));
+}
+
+#[test]
+fn header_and_payload_frame_values() {
+    // [bin8(2), {fixstr(1): u16}], ext8(1)
+    let buf = [0x92, 0xc4, 0x02, 0x01, 0x02, 0x81, 0xa1, 0x61, 0xcd, 0x01, 0x2c, 0xc7, 0x01, 0x2a, 0x00];
+
+    let mut pos = 0;
+    let mut pending = 2u64;
+    while pending > 0 {
+        pending -= 1;
+
+        let marker = Marker::from_u8(buf[pos]);
+        let header = marker.header_len();
+        match marker.payload(&buf[pos + 1..]).unwrap() {
+            Payload::Bytes(len) => pos += 1 + header + len as usize,
+            Payload::Values(count) => {
+                pos += 1 + header;
+                pending += count;
+            }
+        }
+    }
+
+    assert_eq!(buf.len(), pos);
+    assert_eq!(11, skip_value(&mut &buf[..]).unwrap());
+}
@@ -1,4 +1,4 @@
-use msgpack::{decode, encode};
+use msgpack::{decode, encode, Timestamp};
 
 quickcheck! {
     fn mirror_uint(xs: u64) -> bool {
@@ -28,4 +28,12 @@ quickcheck! {
 
         xs == decode::read_f64(&mut &buf[..]).unwrap()
     }
+
+    fn mirror_timestamp(secs: i64, nsecs: u32) -> bool {
+        let ts = Timestamp::new(secs, nsecs % 1000000000).unwrap();
+        let mut buf = Vec::new();
+        encode::write_timestamp(&mut buf, ts).unwrap();
+
+        ts == decode::read_timestamp(&mut &buf[..]).unwrap()
+    }
 }
@@ -1,3 +1,5 @@
 mod encode;
 mod decode;
 mod mirror;
+mod marker;
+mod typed;
@@ -0,0 +1,199 @@
+use std::io::Cursor;
+
+use msgpack::{ElemType, Endian, Marker, TypedArray, DEFAULT_I128_EXT_TYPE, DEFAULT_U128_EXT_TYPE};
+use msgpack::encode::*;
+use msgpack::decode::*;
+
+#[test]
+fn pass_i128_round_trip() {
+    for &val in &[0, 1, -1, i64::min_value() as i128 - 1, i128::min_value(), i128::max_value()] {
+        let mut buf = Vec::new();
+        write_i128(&mut buf, val, DEFAULT_I128_EXT_TYPE).unwrap();
+
+        assert_eq!([0xd8, DEFAULT_I128_EXT_TYPE as u8], buf[..2]);
+        assert_eq!(val, read_i128(&mut &buf[..], DEFAULT_I128_EXT_TYPE).unwrap());
+    }
+}
+
+#[test]
+fn pass_u128_round_trip() {
+    let mut buf = Vec::new();
+    write_u128(&mut buf, u128::max_value(), DEFAULT_U128_EXT_TYPE).unwrap();
+
+    assert_eq!([0xd8, DEFAULT_U128_EXT_TYPE as u8, 0xff], buf[..3]);
+    assert_eq!(u128::max_value(), read_u128(&mut &buf[..], DEFAULT_U128_EXT_TYPE).unwrap());
+}
+
+#[test]
+fn pass_wide_ext_type_is_a_parameter() {
+    let mut buf = Vec::new();
+    write_i128(&mut buf, -2, 7).unwrap();
+    write_u128(&mut buf, 2, 7).unwrap();
+
+    assert_eq!([0xd8, 0x07], buf[..2]);
+
+    let mut rd = &buf[..];
+    assert_eq!(-2, read_i128(&mut rd, 7).unwrap());
+    assert_eq!(2, read_u128(&mut rd, 7).unwrap());
+}
+
+#[test]
+fn pass_read_wide_from_native_ints() {
+    let mut buf = Vec::new();
+    write_uint(&mut buf, u64::max_value()).unwrap();
+    write_sint(&mut buf, -1).unwrap();
+
+    let mut rd = &buf[..];
+    assert_eq!(u64::max_value() as i128, read_i128(&mut rd, DEFAULT_I128_EXT_TYPE).unwrap());
+    assert_eq!(-1, read_i128(&mut rd, DEFAULT_I128_EXT_TYPE).unwrap());
+    assert_eq!(u64::max_value() as u128, read_u128(&mut &buf[..], DEFAULT_U128_EXT_TYPE).unwrap());
+}
+
+#[test]
+fn fail_read_wide_out_of_range() {
+    let mut buf = Vec::new();
+    write_sint(&mut buf, -1).unwrap();
+
+    match read_u128(&mut &buf[..], DEFAULT_U128_EXT_TYPE) {
+        Err(TypedReadError::OutOfRange) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_read_wide_ext_type_mismatch() {
+    let buf = [0xd8, 0x2a, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+    match read_i128(&mut &buf[..], DEFAULT_I128_EXT_TYPE) {
+        Err(TypedReadError::ExtTypeMismatch(42)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut buf = Vec::new();
+    write_u128(&mut buf, 1, DEFAULT_U128_EXT_TYPE).unwrap();
+
+    match read_i128(&mut &buf[..], DEFAULT_I128_EXT_TYPE) {
+        Err(TypedReadError::ExtTypeMismatch(DEFAULT_U128_EXT_TYPE)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_read_wide_type_mismatch() {
+    let buf = [0xc0];
+
+    match read_u128(&mut &buf[..], DEFAULT_U128_EXT_TYPE) {
+        Err(TypedReadError::TypeMismatch(Marker::Null)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_typed_array_layout() {
+    let array = TypedArray::from_f16_bits(&[0x3c00, 0xc000], Endian::Big);
+    let mut buf = Vec::new();
+
+    // Not a fixext, because of the tag byte.
+    assert_eq!(Marker::Ext8, write_typed_array(&mut buf, &array, TypedArray::DEFAULT_EXT_TYPE).unwrap());
+    assert_eq!(vec![0xc7, 0x05, 0x7f, 0x01, 0x3c, 0x00, 0xc0, 0x00], buf);
+
+    let array = TypedArray::from_bf16_bits(&[0x3f80], Endian::Little);
+    let mut buf = Vec::new();
+    write_typed_array(&mut buf, &array, TypedArray::DEFAULT_EXT_TYPE).unwrap();
+
+    assert_eq!(vec![0xc7, 0x03, 0x7f, 0x82, 0x80, 0x3f], buf);
+}
+
+#[test]
+fn pass_typed_array_round_trip() {
+    let arrays = vec![
+        TypedArray::from_f16_bits(&[0x3c00, 0xc000], Endian::Big),
+        TypedArray::from_bf16_bits(&[0x3f80], Endian::Little),
+        TypedArray::from_f32(&[1.0, 2.5, -0.0], Endian::Big),
+        TypedArray::from_f64(&[3.14, -1e300], Endian::Little),
+        TypedArray::from_i32(&[i32::min_value(), 0, 42], Endian::Big),
+        TypedArray::from_i32(&[], Endian::Little),
+        TypedArray::from_f32(&vec![0.5; 1000], Endian::Little),
+    ];
+
+    let mut buf = Vec::new();
+    for array in &arrays {
+        write_typed_array(&mut buf, array, TypedArray::DEFAULT_EXT_TYPE).unwrap();
+    }
+
+    let mut rd = Cursor::new(&buf[..]);
+    for array in &arrays {
+        assert_eq!(*array, read_typed_array(&mut rd, TypedArray::DEFAULT_EXT_TYPE).unwrap());
+    }
+    assert_eq!(buf.len() as u64, rd.position());
+}
+
+#[test]
+fn pass_typed_array_conversions() {
+    let array = TypedArray::from_i32(&[1, -2], Endian::Little);
+
+    assert_eq!(ElemType::I32, array.elem_type());
+    assert_eq!(Endian::Little, array.endian());
+    assert_eq!(2, array.len());
+    assert_eq!(Some(vec![1, -2]), array.to_i32_vec());
+    assert_eq!(None, array.to_f32_vec());
+    assert_eq!(None, array.to_f16_bits_vec());
+
+    let array = TypedArray::from_bytes(ElemType::F64, Endian::Big, vec![0x3f, 0xf0, 0, 0, 0, 0, 0, 0]).unwrap();
+    assert_eq!(Some(vec![1.0]), array.to_f64_vec());
+
+    assert_eq!(None, TypedArray::from_bytes(ElemType::F32, Endian::Big, vec![0; 6]));
+}
+
+#[test]
+fn fail_typed_array_unknown_elem_type() {
+    let buf = [0xd5, 0x7f, 0x06, 0x00];
+
+    match read_typed_array(&mut &buf[..], TypedArray::DEFAULT_EXT_TYPE) {
+        Err(TypedReadError::UnknownElemType(6)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_typed_array_invalid_length() {
+    // A single f32 element is 3 bytes long.
+    let buf = [0xc7, 0x04, 0x7f, 0x03, 0x00, 0x00, 0x00];
+
+    match read_typed_array(&mut &buf[..], TypedArray::DEFAULT_EXT_TYPE) {
+        Err(TypedReadError::InvalidDataLength(4)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match read_typed_array_data(&mut &buf[3..], 0) {
+        Err(TypedReadError::InvalidDataLength(0)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_typed_array_truncated() {
+    // Declares 1 MiB of f64, but ends right after the tag.
+    let buf = [0xc9, 0x00, 0x10, 0x00, 0x01, 0x7f, 0x04];
+
+    match read_typed_array(&mut &buf[..], TypedArray::DEFAULT_EXT_TYPE) {
+        Err(TypedReadError::InvalidDataRead(..)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn pass_typed_array_half() {
+    use half::{bf16, f16};
+
+    let data = [f16::from_f32(1.0), f16::from_f32(-2.0)];
+    let array = TypedArray::from_f16(&data, Endian::Big);
+    assert_eq!(Some(vec![0x3c00, 0xc000]), array.to_f16_bits_vec());
+    assert_eq!(Some(data.to_vec()), array.to_f16_vec());
+    assert_eq!(None, array.to_bf16_vec());
+
+    let data = [bf16::from_f32(1.0)];
+    let array = TypedArray::from_bf16(&data, Endian::Little);
+    assert_eq!([0x80, 0x3f], array.as_bytes());
+    assert_eq!(Some(data.to_vec()), array.to_bf16_vec());
+}
@@ -1,4 +1,9 @@
+// These tests rely on `std::io` readers and writers, like `Cursor`.
+#![cfg(feature = "std")]
+
 extern crate rmp as msgpack;
+#[cfg(feature = "half")]
+extern crate half;
 
 #[cfg(test)]
 #[macro_use]
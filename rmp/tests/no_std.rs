@@ -0,0 +1,54 @@
+// Checks that slices and vectors can be used for encoding and decoding without the `std` feature.
+#![cfg(not(feature = "std"))]
+
+extern crate rmp;
+
+use rmp::Marker;
+use rmp::decode::{self, BytesReadError, ValueReadError};
+use rmp::encode::{self, BytesWriteError, ValueWriteError};
+
+#[test]
+fn pass_round_trip_through_vec() {
+    let mut buf = Vec::new();
+    encode::write_array_len(&mut buf, 3).unwrap();
+    encode::write_sint(&mut buf, -42).unwrap();
+    encode::write_str(&mut buf, "le message").unwrap();
+    encode::write_ext_meta(&mut buf, 1, 16).unwrap();
+    buf.push(0xff);
+
+    let mut rd = &buf[..];
+    assert_eq!(3, decode::read_array_len(&mut rd).unwrap());
+    assert_eq!(-42, decode::read_int::<i64, _>(&mut rd).unwrap());
+    let mut out = [0; 16];
+    assert_eq!("le message", decode::read_str(&mut rd, &mut out).unwrap());
+    assert_eq!((16, 0xff), decode::read_fixext1(&mut rd).unwrap());
+    assert!(rd.is_empty());
+}
+
+#[test]
+fn pass_write_into_slice() {
+    let mut buf = [0; 3];
+
+    assert_eq!(Marker::U16, encode::write_uint(&mut &mut buf[..], 300).unwrap());
+    assert_eq!([0xcd, 0x01, 0x2c], buf);
+}
+
+#[test]
+fn fail_write_into_short_slice() {
+    let mut buf = [0; 2];
+
+    match encode::write_u32(&mut &mut buf[..], 42) {
+        Err(ValueWriteError::InvalidDataWrite(BytesWriteError)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn fail_read_from_short_slice() {
+    let buf: &[u8] = &[0xcd, 0x01];
+
+    match decode::read_u16(&mut &buf[..]) {
+        Err(ValueReadError::InvalidDataRead(BytesReadError)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -256,3 +256,12 @@ fn pass_from_slice() {
     assert_eq!(ValueRef::Array(vec![ValueRef::from("John"), ValueRef::from("Smith"), ValueRef::from(42)]),
         rmps::from_slice(&buf[..]).unwrap());
 }
+
+#[test]
+fn pass_ext_from_slice() {
+    let buf = [0x92, 0xd4, 0x7d, 0x01, 0x2a];
+
+    assert_eq!(ValueRef::Array(vec![ValueRef::Ext(125, &[0x01]), ValueRef::from(42)]),
+        rmps::from_slice(&buf[..]).unwrap());
+    assert_eq!(rmps::ExtValue(125, vec![0x01]), deserialize_from(ValueRef::Ext(125, &[0x01])).unwrap());
+}
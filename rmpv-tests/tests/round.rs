@@ -112,3 +112,21 @@ fn pass_newtype_struct() {
 
     test_round(Newtype("John".into()), Value::from("John"));
 }
+
+#[test]
+fn pass_ext() {
+    test_round(rmps::ExtValue(125, vec![0; 16]), Value::Ext(125, vec![0; 16]));
+}
+
+#[test]
+fn pass_ext_untagged() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Field {
+        Int(u64),
+        Ext(rmps::ExtValue),
+    }
+
+    test_round(vec![Field::Int(1), Field::Ext(rmps::ExtValue(125, vec![0; 16]))],
+               Value::from(vec![Value::from(1), Value::Ext(125, vec![0; 16])]));
+}
@@ -2,13 +2,23 @@ use std::error;
 use std::fmt::{self, Display, Formatter};
 use std::io::{self, ErrorKind};
 
-use rmp::decode::{MarkerReadError, ValueReadError};
+use rmp::decode::{LimitError, MarkerReadError, ValueReadError, ValidateError};
 
 pub mod value;
 pub mod value_ref;
+#[cfg(feature = "bytes")]
+pub mod value_bytes;
 
-pub use self::value::read_value;
-pub use self::value_ref::read_value_ref;
+pub use self::value::{read_value, read_value_with_limits, next_value};
+#[cfg(feature = "async")]
+pub use self::value::{read_value_async, read_value_async_with_limits};
+pub use self::value_ref::{read_value_ref, read_value_ref_with_limits};
+#[cfg(feature = "bytes")]
+pub use self::value_bytes::{read_value_bytes, read_value_bytes_with_limits};
+
+/// The maximum number of elements or bytes preallocated for a declared length, beyond which
+/// containers grow as the data actually arrives.
+const MAX_PREALLOC: usize = 4096;
 
 /// This type represents all possible errors that can occur when deserializing a value.
 #[derive(Debug)]
@@ -71,10 +81,25 @@ impl From<ValueReadError> for Error {
             ValueReadError::TypeMismatch(..) => {
                 Error::InvalidMarkerRead(io::Error::new(ErrorKind::Other, "type mismatch"))
             }
+            ValueReadError::NonCanonical(..) => {
+                Error::InvalidMarkerRead(io::Error::new(ErrorKind::Other, "non-canonical encoding"))
+            }
         }
     }
 }
 
+impl From<LimitError> for Error {
+    fn from(err: LimitError) -> Error {
+        Error::InvalidDataRead(io::Error::new(ErrorKind::InvalidData, err))
+    }
+}
+
+impl From<ValidateError> for Error {
+    fn from(err: ValidateError) -> Error {
+        Error::InvalidMarkerRead(io::Error::new(ErrorKind::InvalidData, err))
+    }
+}
+
 impl Into<io::Error> for Error {
     fn into(self) -> io::Error {
         match self {
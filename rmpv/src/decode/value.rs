@@ -1,32 +1,47 @@
-use std::io::Read;
+use std::cmp;
+use std::io::{self, ErrorKind, Read};
+#[cfg(feature = "async")]
+use std::future::Future;
 
-use rmp::Marker;
+#[cfg(feature = "async")]
+use futures_io::AsyncRead;
+#[cfg(feature = "async")]
+use futures_util::FutureExt;
+
+use rmp::{Family, Marker};
 use rmp::decode::{read_marker, read_data_u8, read_data_u16, read_data_u32, read_data_u64,
                   read_data_i8, read_data_i16, read_data_i32, read_data_i64, read_data_f32,
-                  read_data_f64};
+                  read_data_f64, DecodeLimits, Limiter, Unpacker};
+#[cfg(feature = "async")]
+use rmp::decode::asynchronous;
 
 use {Utf8String, Value};
 use super::Error;
 
-fn read_array_data<R: Read>(rd: &mut R, mut len: usize) -> Result<Vec<Value>, Error> {
-    let mut vec = Vec::with_capacity(len);
+fn read_array_data<R: Read>(rd: &mut R, lim: &mut Limiter, mut len: usize) -> Result<Vec<Value>, Error> {
+    lim.enter()?;
+    // The length comes from the wire, so the vector grows as the elements actually arrive.
+    let mut vec = Vec::with_capacity(cmp::min(len, super::MAX_PREALLOC));
 
     while len > 0 {
-        vec.push(read_value(rd)?);
+        vec.push(read_value_limited(rd, lim)?);
         len -= 1;
     }
 
+    lim.leave();
     Ok(vec)
 }
 
-fn read_map_data<R: Read>(rd: &mut R, mut len: usize) -> Result<Vec<(Value, Value)>, Error> {
-    let mut vec = Vec::with_capacity(len);
+fn read_map_data<R: Read>(rd: &mut R, lim: &mut Limiter, mut len: usize) -> Result<Vec<(Value, Value)>, Error> {
+    lim.enter()?;
+    let mut vec = Vec::with_capacity(cmp::min(len, super::MAX_PREALLOC));
 
     while len > 0 {
-        vec.push((read_value(rd)?, read_value(rd)?));
+        vec.push((read_value_limited(rd, lim)?, read_value_limited(rd, lim)?));
         len -= 1;
     }
 
+    lim.leave();
     Ok(vec)
 }
 
@@ -44,9 +59,13 @@ fn read_str_data<R: Read>(rd: &mut R, len: usize) -> Result<Utf8String, Error> {
 }
 
 fn read_bin_data<R: Read>(rd: &mut R, len: usize) -> Result<Vec<u8>, Error> {
-    let mut buf = Vec::with_capacity(len);
-    buf.resize(len as usize, 0u8);
-    rd.read_exact(&mut buf[..]).map_err(Error::InvalidDataRead)?;
+    // Grow the buffer as the data arrives instead of trusting the declared length.
+    let mut buf = Vec::with_capacity(cmp::min(len, super::MAX_PREALLOC));
+    let read = rd.take(len as u64).read_to_end(&mut buf).map_err(Error::InvalidDataRead)?;
+
+    if read < len {
+        return Err(Error::InvalidDataRead(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF")));
+    }
 
     Ok(buf)
 }
@@ -60,6 +79,10 @@ fn read_ext_body<R: Read>(rd: &mut R, len: usize) -> Result<(i8, Vec<u8>), Error
 
 /// Attempts to read bytes from the given reader and interpret them as a `Value`.
 ///
+/// The default `DecodeLimits` apply, so arrays and maps nested deeper than 1024 levels are
+/// rejected instead of overflowing the stack. Use `read_value_with_limits` to decode untrusted
+/// input.
+///
 /// # Errors
 ///
 /// This function will return `Error` on any I/O error while either reading or decoding a `Value`.
@@ -68,7 +91,72 @@ fn read_ext_body<R: Read>(rd: &mut R, len: usize) -> Result<(i8, Vec<u8>), Error
 pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
     where R: Read
 {
-    let val = match read_marker(rd)? {
+    read_value_with_limits(rd, &DecodeLimits::default())
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a `Value`, rejecting it as
+/// soon as it exceeds the given limits.
+///
+/// Declared lengths are checked before anything is allocated for them, and the memory allocated
+/// for the value grows with the data actually read, so a short message can't make this function
+/// allocate more than the limits allow.
+///
+/// # Errors
+///
+/// This function will return `Error` on any I/O error while either reading or decoding a `Value`,
+/// and an `ErrorKind::InvalidData` error wrapping `LimitError` if the value exceeds the limits.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rmp;
+/// extern crate rmpv;
+///
+/// use rmp::decode::DecodeLimits;
+/// use rmpv::decode::read_value_with_limits;
+///
+/// # fn main() {
+/// let limits = DecodeLimits { max_data_len: 1024, ..DecodeLimits::default() };
+///
+/// // A 5-byte message claiming a 4 GiB string.
+/// let buf = [0xdb, 0xff, 0xff, 0xff, 0xff];
+///
+/// assert!(read_value_with_limits(&mut &buf[..], &limits).is_err());
+/// # }
+/// ```
+pub fn read_value_with_limits<R>(rd: &mut R, limits: &DecodeLimits) -> Result<Value, Error>
+    where R: Read
+{
+    read_value_limited(rd, &mut Limiter::new(*limits))
+}
+
+fn read_value_limited<R>(rd: &mut R, lim: &mut Limiter) -> Result<Value, Error>
+    where R: Read
+{
+    let marker = read_marker(rd)?;
+    lim.check_marker(marker)?;
+
+    let len = match marker {
+        Marker::FixArray(len) | Marker::FixMap(len) => len as u32,
+        Marker::Array16 | Marker::Map16 => read_data_u16(rd)? as u32,
+        Marker::Array32 | Marker::Map32 => read_data_u32(rd)?,
+        marker => return read_flat_value(rd, lim, marker),
+    };
+    lim.check_len(marker, len)?;
+
+    if marker.family() == Family::Array {
+        Ok(Value::Array(read_array_data(rd, lim, len as usize)?))
+    } else {
+        Ok(Value::Map(read_map_data(rd, lim, len as usize)?))
+    }
+}
+
+// Reads values other than arrays and maps, which keeps the frame of the recursive function small
+// enough for the default depth limit.
+fn read_flat_value<R>(rd: &mut R, lim: &mut Limiter, marker: Marker) -> Result<Value, Error>
+    where R: Read
+{
+    let val = match marker {
         Marker::Null => Value::Nil,
         Marker::True => Value::Boolean(true),
         Marker::False => Value::Boolean(false),
@@ -90,59 +178,37 @@ pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
         }
         Marker::Str8 => {
             let len = read_data_u8(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_str_data(rd, len as usize)?;
             Value::String(res)
         }
         Marker::Str16 => {
             let len = read_data_u16(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_str_data(rd, len as usize)?;
             Value::String(res)
         }
         Marker::Str32 => {
             let len = read_data_u32(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_str_data(rd, len as usize)?;
             Value::String(res)
         }
-        Marker::FixArray(len) => {
-            let vec = read_array_data(rd, len as usize)?;
-            Value::Array(vec)
-        }
-        Marker::Array16 => {
-            let len = read_data_u16(rd)?;
-            let vec = read_array_data(rd, len as usize)?;
-            Value::Array(vec)
-        }
-        Marker::Array32 => {
-            let len = read_data_u32(rd)?;
-            let vec = read_array_data(rd, len as usize)?;
-            Value::Array(vec)
-        }
-        Marker::FixMap(len) => {
-            let map = read_map_data(rd, len as usize)?;
-            Value::Map(map)
-        }
-        Marker::Map16 => {
-            let len = read_data_u16(rd)?;
-            let map = read_map_data(rd, len as usize)?;
-            Value::Map(map)
-        }
-        Marker::Map32 => {
-            let len = read_data_u32(rd)?;
-            let map = read_map_data(rd, len as usize)?;
-            Value::Map(map)
-        }
         Marker::Bin8 => {
             let len = read_data_u8(rd)?;
+            lim.check_len(marker, len as u32)?;
             let vec = read_bin_data(rd, len as usize)?;
             Value::Binary(vec)
         }
         Marker::Bin16 => {
             let len = read_data_u16(rd)?;
+            lim.check_len(marker, len as u32)?;
             let vec = read_bin_data(rd, len as usize)?;
             Value::Binary(vec)
         }
         Marker::Bin32 => {
             let len = read_data_u32(rd)?;
+            lim.check_len(marker, len as u32)?;
             let vec = read_bin_data(rd, len as usize)?;
             Value::Binary(vec)
         }
@@ -173,21 +239,102 @@ pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
         }
         Marker::Ext8 => {
             let len = read_data_u8(rd)? as usize;
+            lim.check_len(marker, len as u32)?;
             let (ty, vec) = read_ext_body(rd, len)?;
             Value::Ext(ty, vec)
         }
         Marker::Ext16 => {
             let len = read_data_u16(rd)? as usize;
+            lim.check_len(marker, len as u32)?;
             let (ty, vec) = read_ext_body(rd, len)?;
             Value::Ext(ty, vec)
         }
         Marker::Ext32 => {
             let len = read_data_u32(rd)? as usize;
+            lim.check_len(marker, len as u32)?;
             let (ty, vec) = read_ext_body(rd, len)?;
             Value::Ext(ty, vec)
         }
         Marker::Reserved => Value::Nil,
+        Marker::FixArray(..) | Marker::Array16 | Marker::Array32 |
+        Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => unreachable!(),
     };
 
     Ok(val)
 }
+
+/// Attempts to extract the next complete message from the given unpacker and interpret it as a
+/// `Value`.
+///
+/// Returns `Ok(None)` if the unpacker doesn't have a complete message buffered yet, in which case
+/// more data should be fed into it.
+///
+/// # Errors
+///
+/// This function will return `Error` if the message is malformed or exceeds the unpacker limits.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rmp;
+/// extern crate rmpv;
+///
+/// use rmp::decode::Unpacker;
+/// use rmpv::Value;
+/// use rmpv::decode::next_value;
+///
+/// # fn main() {
+/// let mut unpacker = Unpacker::new();
+///
+/// unpacker.feed(&[0x92, 0x01]);
+/// assert_eq!(None, next_value(&mut unpacker).unwrap());
+///
+/// unpacker.feed(&[0xa1, 0x61]);
+/// assert_eq!(Some(Value::from(vec![Value::from(1), Value::from("a")])),
+///            next_value(&mut unpacker).unwrap());
+/// # }
+/// ```
+pub fn next_value(unpacker: &mut Unpacker) -> Result<Option<Value>, Error> {
+    let limits = *unpacker.limits();
+    match unpacker.next_message()? {
+        Some(mut msg) => read_value_with_limits(&mut msg, &limits).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Attempts to read bytes from the given asynchronous reader and interpret them as a `Value`.
+///
+/// The encoded value is first read entirely into a buffer, without blocking the executor thread,
+/// and then decoded as `read_value` does. The reader is left positioned right after the value.
+///
+/// # Errors
+///
+/// This function will return `Error` on any I/O error while either reading or decoding a `Value`.
+#[cfg(feature = "async")]
+pub fn read_value_async<'a, R>(rd: &'a mut R) -> impl Future<Output = Result<Value, Error>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    read_value_async_with_limits(rd, &DecodeLimits::default())
+}
+
+/// Attempts to read bytes from the given asynchronous reader and interpret them as a `Value`,
+/// unless the value exceeds the given limits.
+///
+/// The limits are checked while the value is being received, so no more than they allow is ever
+/// buffered.
+///
+/// # Errors
+///
+/// This function will return `Error` on any I/O error while either reading or decoding a `Value`,
+/// and an `ErrorKind::InvalidData` error wrapping `LimitError` if the value exceeds the limits.
+#[cfg(feature = "async")]
+pub fn read_value_async_with_limits<'a, R>(rd: &'a mut R, limits: &DecodeLimits) ->
+    impl Future<Output = Result<Value, Error>> + 'a
+    where R: AsyncRead + Unpin + ?Sized
+{
+    let limits = *limits;
+    asynchronous::read_value_bytes_with_limits(rd, &limits).map(move |res| {
+        let buf = res?;
+        read_value_with_limits(&mut &buf[..], &limits)
+    })
+}
@@ -0,0 +1,62 @@
+use bytes::Bytes;
+
+use rmp::decode::DecodeLimits;
+
+use ValueBytes;
+use super::Error;
+use super::value_ref::read_value_ref_with_limits;
+
+/// Attempts to read the first MessagePack value from the given buffer, advancing the buffer past
+/// it.
+///
+/// Strings, binaries and extensions of the value returned share the buffer instead of being
+/// copied, see `ValueBytes` for details. Nothing is consumed on errors, including a value
+/// truncated at the end of the buffer, so the buffer can be extended and the reading retried.
+///
+/// # Errors
+///
+/// Returns an `Error` value if unable to continue the decoding operation either because of read
+/// failure or any other circumstances. See `Error` documentation for more information.
+///
+/// # Examples
+///
+/// ```
+/// extern crate bytes;
+/// extern crate rmpv;
+///
+/// use bytes::Bytes;
+/// use rmpv::{Utf8Bytes, ValueBytes};
+/// use rmpv::decode::read_value_bytes;
+///
+/// # fn main() {
+/// let mut buf = Bytes::from_static(&[0xa2, 0x6c, 0x65, 0xc0]);
+///
+/// let val = read_value_bytes(&mut buf).unwrap();
+/// assert_eq!(ValueBytes::String(Utf8Bytes::from("le")), val);
+/// assert_eq!(ValueBytes::Nil, read_value_bytes(&mut buf).unwrap());
+/// assert!(buf.is_empty());
+/// # }
+/// ```
+pub fn read_value_bytes(buf: &mut Bytes) -> Result<ValueBytes, Error> {
+    read_value_bytes_with_limits(buf, &DecodeLimits::default())
+}
+
+/// Attempts to read the first MessagePack value from the given buffer, rejecting the value as
+/// soon as it exceeds the given limits.
+///
+/// See `read_value_bytes` for details.
+///
+/// # Errors
+///
+/// Returns an `Error` value if unable to continue the decoding operation, including an
+/// `ErrorKind::InvalidData` error wrapping `LimitError` if the value exceeds the limits.
+pub fn read_value_bytes_with_limits(buf: &mut Bytes, limits: &DecodeLimits) -> Result<ValueBytes, Error> {
+    let (val, len) = {
+        let mut rd = &buf[..];
+        let val = read_value_ref_with_limits(&mut rd, limits)?;
+        (ValueBytes::from_ref(buf, &val), buf.len() - rd.len())
+    };
+
+    let _ = buf.split_to(len);
+    Ok(val)
+}
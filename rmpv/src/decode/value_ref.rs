@@ -1,11 +1,12 @@
 use std;
+use std::cmp;
 use std::io::{self, Cursor, ErrorKind, Read};
 use std::str;
 
-use rmp::Marker;
+use rmp::{Family, Marker};
 use rmp::decode::{read_marker, read_data_u8, read_data_u16, read_data_u32, read_data_u64,
                   read_data_i8, read_data_i16, read_data_i32, read_data_i64, read_data_f32,
-                  read_data_f64};
+                  read_data_f64, DecodeLimits, Limiter};
 
 use {Utf8StringRef, ValueRef};
 use super::Error;
@@ -50,29 +51,33 @@ fn read_ext_body<'a, R>(rd: &mut R, len: usize) -> Result<(i8, &'a [u8]), Error>
     Ok((ty, buf))
 }
 
-fn read_array_data<'a, R>(rd: &mut R, mut len: usize) -> Result<Vec<ValueRef<'a>>, Error>
+fn read_array_data<'a, R>(rd: &mut R, lim: &mut Limiter, mut len: usize) -> Result<Vec<ValueRef<'a>>, Error>
     where R: BorrowRead<'a>
 {
-    let mut vec = Vec::with_capacity(len);
+    lim.enter()?;
+    let mut vec = Vec::with_capacity(cmp::min(len, super::MAX_PREALLOC));
 
     while len > 0 {
-        vec.push(read_value_ref(rd)?);
+        vec.push(read_value_ref_limited(rd, lim)?);
         len -= 1;
     }
 
+    lim.leave();
     Ok(vec)
 }
 
-fn read_map_data<'a, R>(rd: &mut R, mut len: usize) -> Result<Vec<(ValueRef<'a>, ValueRef<'a>)>, Error>
+fn read_map_data<'a, R>(rd: &mut R, lim: &mut Limiter, mut len: usize) -> Result<Vec<(ValueRef<'a>, ValueRef<'a>)>, Error>
     where R: BorrowRead<'a>
 {
-    let mut vec = Vec::with_capacity(len);
+    lim.enter()?;
+    let mut vec = Vec::with_capacity(cmp::min(len, super::MAX_PREALLOC));
 
     while len > 0 {
-        vec.push((read_value_ref(rd)?, read_value_ref(rd)?));
+        vec.push((read_value_ref_limited(rd, lim)?, read_value_ref_limited(rd, lim)?));
         len -= 1;
     }
 
+    lim.leave();
     Ok(vec)
 }
 
@@ -152,12 +157,56 @@ impl<'a> BorrowRead<'a> for Cursor<&'a [u8]> {
 /// ```
 pub fn read_value_ref<'a, R>(rd: &mut R) -> Result<ValueRef<'a>, Error>
     where R: BorrowRead<'a>
+{
+    read_value_ref_with_limits(rd, &DecodeLimits::default())
+}
+
+/// Attempts to read the data from the given reader until either a complete MessagePack value
+/// decoded or an error detected, rejecting the value as soon as it exceeds the given limits.
+///
+/// See `read_value_ref` for details.
+///
+/// # Errors
+///
+/// Returns an `Error` value if unable to continue the decoding operation, including an
+/// `ErrorKind::InvalidData` error wrapping `LimitError` if the value exceeds the limits.
+pub fn read_value_ref_with_limits<'a, R>(rd: &mut R, limits: &DecodeLimits) -> Result<ValueRef<'a>, Error>
+    where R: BorrowRead<'a>
+{
+    read_value_ref_limited(rd, &mut Limiter::new(*limits))
+}
+
+fn read_value_ref_limited<'a, R>(rd: &mut R, lim: &mut Limiter) -> Result<ValueRef<'a>, Error>
+    where R: BorrowRead<'a>
 {
     let mut rd = rd;
 
     // Reading the marker involves either 1 byte read or nothing. On success consumes strictly
     // 1 byte from the `rd`.
-    let val = match read_marker(rd)? {
+    let marker = read_marker(rd)?;
+    lim.check_marker(marker)?;
+
+    let len = match marker {
+        Marker::FixArray(len) | Marker::FixMap(len) => len as u32,
+        Marker::Array16 | Marker::Map16 => read_data_u16(rd)? as u32,
+        Marker::Array32 | Marker::Map32 => read_data_u32(rd)?,
+        marker => return read_flat_value(rd, lim, marker),
+    };
+    lim.check_len(marker, len)?;
+
+    if marker.family() == Family::Array {
+        Ok(ValueRef::Array(read_array_data(rd, lim, len as usize)?))
+    } else {
+        Ok(ValueRef::Map(read_map_data(rd, lim, len as usize)?))
+    }
+}
+
+// Reads values other than arrays and maps, which keeps the frame of the recursive function small
+// enough for the default depth limit.
+fn read_flat_value<'a, R>(rd: &mut R, lim: &mut Limiter, marker: Marker) -> Result<ValueRef<'a>, Error>
+    where R: BorrowRead<'a>
+{
+    let val = match marker {
         Marker::Null => ValueRef::Nil,
         Marker::True => ValueRef::Boolean(true),
         Marker::False => ValueRef::Boolean(false),
@@ -179,62 +228,40 @@ pub fn read_value_ref<'a, R>(rd: &mut R) -> Result<ValueRef<'a>, Error>
         }
         Marker::Str8 => {
             let len = read_data_u8(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_str_data(rd, len as usize)?;
             ValueRef::String(res)
         }
         Marker::Str16 => {
             let len = read_data_u16(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_str_data(rd, len as usize)?;
             ValueRef::String(res)
         }
         Marker::Str32 => {
             let len = read_data_u32(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_str_data(rd, len as usize)?;
             ValueRef::String(res)
         }
         Marker::Bin8 => {
             let len = read_data_u8(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_bin_data(rd, len as usize)?;
             ValueRef::Binary(res)
         }
         Marker::Bin16 => {
             let len = read_data_u16(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_bin_data(rd, len as usize)?;
             ValueRef::Binary(res)
         }
         Marker::Bin32 => {
             let len = read_data_u32(rd)?;
+            lim.check_len(marker, len as u32)?;
             let res = read_bin_data(rd, len as usize)?;
             ValueRef::Binary(res)
         }
-        Marker::FixArray(len) => {
-            let vec = read_array_data(rd, len as usize)?;
-            ValueRef::Array(vec)
-        }
-        Marker::Array16 => {
-            let len = read_data_u16(rd)?;
-            let vec = read_array_data(rd, len as usize)?;
-            ValueRef::Array(vec)
-        }
-        Marker::Array32 => {
-            let len = read_data_u32(rd)?;
-            let vec = read_array_data(rd, len as usize)?;
-            ValueRef::Array(vec)
-        }
-        Marker::FixMap(len) => {
-            let map = read_map_data(rd, len as usize)?;
-            ValueRef::Map(map)
-        }
-        Marker::Map16 => {
-            let len = read_data_u16(rd)?;
-            let map = read_map_data(rd, len as usize)?;
-            ValueRef::Map(map)
-        }
-        Marker::Map32 => {
-            let len = read_data_u32(rd)?;
-            let map = read_map_data(rd, len as usize)?;
-            ValueRef::Map(map)
-        }
         Marker::FixExt1 => {
             let len = 1;
             let (ty, vec) = read_ext_body(rd, len as usize)?;
@@ -262,20 +289,25 @@ pub fn read_value_ref<'a, R>(rd: &mut R) -> Result<ValueRef<'a>, Error>
         }
         Marker::Ext8 => {
             let len = read_data_u8(rd)?;
+            lim.check_len(marker, len as u32)?;
             let (ty, vec) = read_ext_body(rd, len as usize)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::Ext16 => {
             let len = read_data_u16(rd)?;
+            lim.check_len(marker, len as u32)?;
             let (ty, vec) = read_ext_body(rd, len as usize)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::Ext32 => {
             let len = read_data_u32(rd)?;
+            lim.check_len(marker, len as u32)?;
             let (ty, vec) = read_ext_body(rd, len as usize)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::Reserved => ValueRef::Nil,
+        Marker::FixArray(..) | Marker::Array16 | Marker::Array32 |
+        Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => unreachable!(),
     };
 
     Ok(val)
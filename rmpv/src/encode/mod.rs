@@ -2,6 +2,12 @@ pub use rmp::encode::ValueWriteError as Error;
 
 mod value;
 mod value_ref;
+#[cfg(feature = "bytes")]
+mod value_bytes;
 
 pub use self::value::write_value;
+#[cfg(feature = "async")]
+pub use self::value::write_value_async;
 pub use self::value_ref::write_value_ref;
+#[cfg(feature = "bytes")]
+pub use self::value_bytes::write_value_bytes;
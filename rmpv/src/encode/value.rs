@@ -1,7 +1,18 @@
 use std::io::Write;
+#[cfg(feature = "async")]
+use std::future::Future;
+
+#[cfg(feature = "async")]
+use futures_io::AsyncWrite;
+#[cfg(feature = "async")]
+use futures_util::FutureExt;
+#[cfg(feature = "async")]
+use futures_util::future::{ready, Either};
 
 use rmp::encode::{write_nil, write_bool, write_uint, write_sint, write_f32, write_f64, write_str,
                   write_bin, write_array_len, write_map_len, write_ext_meta};
+#[cfg(feature = "async")]
+use rmp::encode::asynchronous;
 
 use {Integer, IntPriv, Utf8String, Value};
 use super::Error;
@@ -68,3 +79,20 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
 
     Ok(())
 }
+
+/// Encodes the given Value and attempts to write it into the given asynchronous writer.
+///
+/// The value is encoded into a memory buffer first, which is then written without blocking the
+/// executor thread.
+#[cfg(feature = "async")]
+pub fn write_value_async<'a, W>(wr: &'a mut W, val: &Value) -> impl Future<Output = Result<(), Error>> + 'a
+    where W: AsyncWrite + Unpin + ?Sized
+{
+    let mut buf = Vec::new();
+    match write_value(&mut buf, val) {
+        Ok(()) => {
+            Either::Left(asynchronous::write_all(wr, buf).map(|res| res.map_err(Error::InvalidDataWrite)))
+        }
+        Err(err) => Either::Right(ready(Err(err))),
+    }
+}
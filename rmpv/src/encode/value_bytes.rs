@@ -0,0 +1,65 @@
+use std::io::Write;
+
+use rmp::encode::{write_ext_meta, write_array_len, write_map_len};
+
+use ValueBytes;
+use super::{write_value_ref, Error};
+
+/// Encodes and attempts to write the given `ValueBytes` into the Write.
+///
+/// To write directly into a `BytesMut` buffer, wrap it using `BufMut::writer`.
+///
+/// # Errors
+///
+/// This function returns Error with an underlying I/O error if unable to properly write entire
+/// value. Interruption errors are handled internally by silent operation restarting.
+///
+/// # Examples
+///
+/// ```
+/// extern crate bytes;
+/// extern crate rmpv;
+///
+/// use bytes::{BufMut, Bytes, BytesMut};
+/// use rmpv::ValueBytes;
+/// use rmpv::encode::write_value_bytes;
+///
+/// # fn main() {
+/// let val = ValueBytes::Array(vec![
+///     ValueBytes::from(42),
+///     ValueBytes::from(Bytes::from_static(&[0x00, 0x01])),
+/// ]);
+///
+/// let mut buf = BytesMut::new();
+/// write_value_bytes(&mut (&mut buf).writer(), &val).unwrap();
+///
+/// assert_eq!(&[0x92, 0x2a, 0xc4, 0x02, 0x00, 0x01][..], &buf[..]);
+/// # }
+/// ```
+pub fn write_value_bytes<W>(wr: &mut W, val: &ValueBytes) -> Result<(), Error>
+    where W: Write
+{
+    match *val {
+        ValueBytes::Array(ref vec) => {
+            write_array_len(wr, vec.len() as u32)?;
+            for v in vec {
+                write_value_bytes(wr, v)?;
+            }
+        }
+        ValueBytes::Map(ref map) => {
+            write_map_len(wr, map.len() as u32)?;
+            for &(ref key, ref val) in map {
+                write_value_bytes(wr, key)?;
+                write_value_bytes(wr, val)?;
+            }
+        }
+        ValueBytes::Ext(ty, ref data) => {
+            write_ext_meta(wr, data.len() as u32, ty)?;
+            wr.write_all(data).map_err(|err| Error::InvalidDataWrite(err))?;
+        }
+        // Scalars, strings and binaries have no nested buffers, so borrowing them is free.
+        ref val => write_value_ref(wr, &val.as_ref())?,
+    }
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ use std::vec::IntoIter;
 
 use serde::{self, Deserialize, Deserializer};
 use serde::de::{self, DeserializeSeed, IntoDeserializer, SeqAccess, Unexpected, Visitor};
+use serde_bytes::{ByteBuf, Bytes};
 
 use {Integer, IntPriv, Utf8String, Utf8StringRef, Value, ValueRef};
 
@@ -115,6 +116,15 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::Binary(v.to_owned()))
             }
 
+            #[inline]
+            fn visit_newtype_struct<D>(self, de: D) -> Result<Value, D::Error>
+                where D: de::Deserializer<'de>
+            {
+                // MessagePack extensions come as an `(i8, bytes)` tuple wrapped in a newtype.
+                let (ty, buf): (i8, ByteBuf) = Deserialize::deserialize(de)?;
+                Ok(Value::Ext(ty, buf.into()))
+            }
+
             #[inline]
             fn visit_map<V>(self, mut visitor: V) -> Result<Value, V::Error>
                 where V: de::MapAccess<'de>
@@ -221,6 +231,15 @@ impl<'de> Deserialize<'de> for ValueRef<'de> {
                 Ok(ValueRef::Binary(v))
             }
 
+            #[inline]
+            fn visit_newtype_struct<D>(self, de: D) -> Result<Self::Value, D::Error>
+                where D: Deserializer<'de>
+            {
+                // MessagePack extensions come as an `(i8, bytes)` tuple wrapped in a newtype.
+                let (ty, buf): (i8, Bytes<'de>) = Deserialize::deserialize(de)?;
+                Ok(ValueRef::Ext(ty, buf.into()))
+            }
+
             #[inline]
             fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
                 where V: de::MapAccess<'de>
@@ -285,13 +304,9 @@ impl<'de> Deserializer<'de> for Value {
                     Err(de::Error::invalid_length(len, &"fewer elements in map"))
                 }
             }
-            Value::Ext(..) => {
-                // TODO: [i8, [u8]] can be represented as:
-                //      - (0i8, Vec<u8>),
-                //      - struct F(i8, Vec<u8>),
-                //      - struct F {ty: i8, val: Vec<u8>}
-                //      - enum F{ A(Vec<u8>), B { name: Vec<u8> } }
-                unimplemented!();
+            Value::Ext(ty, buf) => {
+                // The same way `rmp-serde` passes extensions.
+                visitor.visit_newtype_struct(Value::Array(vec![Value::from(ty), Value::Binary(buf)]))
             }
         }
     }
@@ -376,8 +391,9 @@ impl<'de> Deserializer<'de> for ValueRef<'de> {
                     Err(de::Error::invalid_length(len, &"fewer elements in map"))
                 }
             }
-            ValueRef::Ext(..) => {
-                unimplemented!();
+            ValueRef::Ext(ty, buf) => {
+                // The same way `rmp-serde` passes extensions.
+                visitor.visit_newtype_struct(ValueRef::Array(vec![ValueRef::from(ty), ValueRef::Binary(buf)]))
             }
         }
     }
@@ -462,8 +478,9 @@ impl<'de> Deserializer<'de> for &'de ValueRef<'de> {
                     Err(de::Error::invalid_length(len, &"fewer elements in map"))
                 }
             }
-            ValueRef::Ext(..) => {
-                unimplemented!();
+            ValueRef::Ext(ty, buf) => {
+                // The same way `rmp-serde` passes extensions.
+                visitor.visit_newtype_struct(ValueRef::Array(vec![ValueRef::from(ty), ValueRef::Binary(buf)]))
             }
         }
     }
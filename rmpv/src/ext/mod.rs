@@ -11,6 +11,10 @@ pub use self::se::to_value;
 mod de;
 mod se;
 
+/// The name of the newtype struct `rmp-serde` maps to MessagePack extensions, which wraps an
+/// `(i8, bytes)` tuple of the type id and the data.
+const MSGPACK_EXT_STRUCT_NAME: &str = "_ExtStruct";
+
 #[derive(Debug)]
 pub enum Error {
     Syntax(String),
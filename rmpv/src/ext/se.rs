@@ -6,7 +6,7 @@ use serde_bytes::Bytes;
 
 use {Integer, IntPriv, Value};
 
-use super::Error;
+use super::{Error, MSGPACK_EXT_STRUCT_NAME};
 
 impl Serialize for Value {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
@@ -45,10 +45,7 @@ impl Serialize for Value {
                 state.end()
             }
             Value::Ext(ty, ref buf) => {
-                let mut state = s.serialize_seq(Some(2))?;
-                state.serialize_element(&ty)?;
-                state.serialize_element(buf)?;
-                state.end()
+                s.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &(ty, Bytes::from(&buf[..])))
             }
         }
     }
@@ -181,10 +178,24 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn serialize_newtype_struct<T: ?Sized>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_newtype_struct<T: ?Sized>(self, name: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
         where T: Serialize
     {
-        Ok(to_value(value)?)
+        let val = to_value(value)?;
+        if name != MSGPACK_EXT_STRUCT_NAME {
+            return Ok(val);
+        }
+
+        // The extension comes as an `(i8, bytes)` tuple.
+        if let Value::Array(ref vec) = val {
+            if let [Value::Integer(ty), Value::Binary(ref buf)] = vec[..] {
+                if let Some(ty) = ty.as_i64().filter(|&ty| ty as i8 as i64 == ty) {
+                    return Ok(Value::Ext(ty as i8, buf.clone()));
+                }
+            }
+        }
+
+        Err(Error::Syntax(format!("invalid extension: {}", val)))
     }
 
     fn serialize_newtype_variant<T: ?Sized>(self, _name: &'static str, idx: u32, _variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
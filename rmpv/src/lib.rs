@@ -12,6 +12,12 @@ extern crate serde;
 extern crate serde_bytes;
 extern crate rmp;
 extern crate num_traits;
+#[cfg(feature = "async")]
+extern crate futures_io;
+#[cfg(feature = "async")]
+extern crate futures_util;
+#[cfg(feature = "bytes")]
+extern crate bytes;
 
 use std::borrow::Cow;
 use std::fmt::{self, Debug, Display};
@@ -20,12 +26,20 @@ use std::str::Utf8Error;
 
 use num_traits::NumCast;
 
+pub use rmp::{Timestamp, TypedArray, ElemType, Endian};
+
 pub mod decode;
 pub mod encode;
 
 #[cfg(feature = "with-serde")]
 pub mod ext;
 
+#[cfg(feature = "bytes")]
+mod shared;
+
+#[cfg(feature = "bytes")]
+pub use shared::{Utf8Bytes, ValueBytes};
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 enum IntPriv {
     /// Always non-less than zero.
@@ -823,6 +837,158 @@ impl Value {
             None
         }
     }
+
+    /// If the `Value` is an Ext holding a valid MessagePack timestamp, returns the decoded
+    /// timestamp. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Timestamp, Value};
+    ///
+    /// let ts = Timestamp::new(1514862245, 678901234).unwrap();
+    ///
+    /// assert_eq!(Some(ts), Value::from(ts).as_timestamp());
+    ///
+    /// assert_eq!(None, Value::Ext(42, vec![0, 0, 0, 0]).as_timestamp());
+    /// ```
+    pub fn as_timestamp(&self) -> Option<Timestamp> {
+        self.as_ext().and_then(|(ty, buf)| ext_as_timestamp(ty, buf))
+    }
+
+    /// If the `Value` is an integer or an Ext of the given type holding a 128-bit integer, returns
+    /// or casts it to an i128. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rmp;
+    /// extern crate rmpv;
+    ///
+    /// use rmp::DEFAULT_I128_EXT_TYPE;
+    /// use rmpv::Value;
+    ///
+    /// # fn main() {
+    /// let val = Value::from_i128(i128::min_value(), DEFAULT_I128_EXT_TYPE);
+    ///
+    /// assert_eq!(Some(i128::min_value()), val.as_i128(DEFAULT_I128_EXT_TYPE));
+    /// assert_eq!(Some(-42), Value::from(-42).as_i128(DEFAULT_I128_EXT_TYPE));
+    ///
+    /// assert_eq!(None, val.as_i128(42));
+    /// # }
+    /// ```
+    pub fn as_i128(&self, ty: i8) -> Option<i128> {
+        match *self {
+            Value::Integer(ref n) => integer_as_i128(n),
+            Value::Ext(ext_ty, ref buf) => ext_as_wide(ext_ty, buf, ty).map(i128::from_be_bytes),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is an integer or an Ext of the given type holding a 128-bit integer, returns
+    /// or casts it to a u128. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rmp;
+    /// extern crate rmpv;
+    ///
+    /// use rmp::DEFAULT_U128_EXT_TYPE;
+    /// use rmpv::Value;
+    ///
+    /// # fn main() {
+    /// let val = Value::from_u128(u128::max_value(), DEFAULT_U128_EXT_TYPE);
+    ///
+    /// assert_eq!(Some(u128::max_value()), val.as_u128(DEFAULT_U128_EXT_TYPE));
+    /// assert_eq!(Some(42), Value::from(42).as_u128(DEFAULT_U128_EXT_TYPE));
+    ///
+    /// assert_eq!(None, Value::from(-1).as_u128(DEFAULT_U128_EXT_TYPE));
+    /// # }
+    /// ```
+    pub fn as_u128(&self, ty: i8) -> Option<u128> {
+        match *self {
+            Value::Integer(ref n) => integer_as_u128(n),
+            Value::Ext(ext_ty, ref buf) => ext_as_wide(ext_ty, buf, ty).map(u128::from_be_bytes),
+            _ => None,
+        }
+    }
+
+    /// If the `Value` is an Ext of the given type holding a valid typed array, returns the decoded
+    /// array. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Endian, TypedArray, Value};
+    ///
+    /// let array = TypedArray::from_f32(&[0.5, 1.5], Endian::Little);
+    /// let val = Value::from_typed_array(&array, TypedArray::DEFAULT_EXT_TYPE);
+    ///
+    /// assert_eq!(Some(array), val.as_typed_array(TypedArray::DEFAULT_EXT_TYPE));
+    ///
+    /// // Unknown element type.
+    /// assert_eq!(None, Value::Ext(42, vec![0x06]).as_typed_array(42));
+    /// ```
+    pub fn as_typed_array(&self, ty: i8) -> Option<TypedArray> {
+        self.as_ext().and_then(|(ext_ty, buf)| ext_as_typed_array(ext_ty, buf, ty))
+    }
+
+    /// Constructs an Ext of the given type holding a 128-bit signed integer, the same way
+    /// `rmp::encode::write_i128` writes it, even if the value fits in 64 bits.
+    pub fn from_i128(v: i128, ty: i8) -> Value {
+        Value::Ext(ty, v.to_be_bytes().to_vec())
+    }
+
+    /// Constructs an Ext of the given type holding a 128-bit unsigned integer, the same way
+    /// `rmp::encode::write_u128` writes it, even if the value fits in 64 bits.
+    pub fn from_u128(v: u128, ty: i8) -> Value {
+        Value::Ext(ty, v.to_be_bytes().to_vec())
+    }
+
+    /// Constructs an Ext of the given type holding a typed array, the same way
+    /// `rmp::encode::write_typed_array` writes it.
+    pub fn from_typed_array(v: &TypedArray, ty: i8) -> Value {
+        let mut buf = Vec::with_capacity(v.data_len() as usize);
+        rmp::encode::write_typed_array_data(&mut buf, v)
+            .expect("writing into a vector must never fail");
+        Value::Ext(ty, buf)
+    }
+}
+
+fn ext_as_timestamp(ty: i8, buf: &[u8]) -> Option<Timestamp> {
+    if ty == Timestamp::EXT_TYPE {
+        rmp::decode::read_timestamp_data(&mut &buf[..], buf.len() as u32).ok()
+    } else {
+        None
+    }
+}
+
+fn ext_as_typed_array(ty: i8, buf: &[u8], expected: i8) -> Option<TypedArray> {
+    if ty == expected {
+        rmp::decode::read_typed_array_data(&mut &buf[..], buf.len() as u32).ok()
+    } else {
+        None
+    }
+}
+
+fn integer_as_i128(n: &Integer) -> Option<i128> {
+    n.as_i64().map(|n| n as i128).or_else(|| n.as_u64().map(|n| n as i128))
+}
+
+fn integer_as_u128(n: &Integer) -> Option<u128> {
+    n.as_u64().map(|n| n as u128)
+}
+
+// Returns the payload of a 128-bit integer extension of the expected type.
+fn ext_as_wide(ty: i8, buf: &[u8], expected: i8) -> Option<[u8; 16]> {
+    if ty != expected || buf.len() != 16 {
+        return None;
+    }
+
+    let mut val = [0; 16];
+    val.copy_from_slice(buf);
+    Some(val)
 }
 
 static NIL: Value = Value::Nil;
@@ -950,6 +1116,15 @@ impl<'a> From<Cow<'a, [u8]>> for Value {
     }
 }
 
+impl From<Timestamp> for Value {
+    fn from(v: Timestamp) -> Self {
+        let mut buf = Vec::with_capacity(v.data_len() as usize);
+        rmp::encode::write_timestamp_data(&mut buf, v)
+            .expect("writing into a vector must never fail");
+        Value::Ext(Timestamp::EXT_TYPE, buf)
+    }
+}
+
 impl From<Vec<Value>> for Value {
     fn from(v: Vec<Value>) -> Self {
         Value::Array(v)
@@ -1133,6 +1308,66 @@ impl<'a> ValueRef<'a> {
             None
         }
     }
+
+    /// If the `ValueRef` is an Ext holding a valid MessagePack timestamp, returns the decoded
+    /// timestamp. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Timestamp, ValueRef};
+    ///
+    /// let val = ValueRef::Ext(-1, &[0x5a, 0x4a, 0xf6, 0xa5]);
+    ///
+    /// assert_eq!(Some(Timestamp::from_secs(1514862245)), val.as_timestamp());
+    /// assert_eq!(None, ValueRef::Nil.as_timestamp());
+    /// ```
+    pub fn as_timestamp(&self) -> Option<Timestamp> {
+        match *self {
+            ValueRef::Ext(ty, buf) => ext_as_timestamp(ty, buf),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is an integer or an Ext of the given type holding a 128-bit integer,
+    /// returns or casts it to an i128. Returns None otherwise.
+    pub fn as_i128(&self, ty: i8) -> Option<i128> {
+        match *self {
+            ValueRef::Integer(ref n) => integer_as_i128(n),
+            ValueRef::Ext(ext_ty, buf) => ext_as_wide(ext_ty, buf, ty).map(i128::from_be_bytes),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is an integer or an Ext of the given type holding a 128-bit integer,
+    /// returns or casts it to a u128. Returns None otherwise.
+    pub fn as_u128(&self, ty: i8) -> Option<u128> {
+        match *self {
+            ValueRef::Integer(ref n) => integer_as_u128(n),
+            ValueRef::Ext(ext_ty, buf) => ext_as_wide(ext_ty, buf, ty).map(u128::from_be_bytes),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is an Ext of the given type holding a valid typed array, returns the
+    /// decoded array. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{ElemType, ValueRef};
+    ///
+    /// let val = ValueRef::Ext(127, &[0x85, 0x2a, 0x00, 0x00, 0x00]);
+    ///
+    /// assert_eq!(ElemType::I32, val.as_typed_array(127).unwrap().elem_type());
+    /// assert_eq!(Some(vec![42]), val.as_typed_array(127).unwrap().to_i32_vec());
+    /// ```
+    pub fn as_typed_array(&self, ty: i8) -> Option<TypedArray> {
+        match *self {
+            ValueRef::Ext(ext_ty, buf) => ext_as_typed_array(ext_ty, buf, ty),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> From<u8> for ValueRef<'a> {
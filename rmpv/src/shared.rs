@@ -0,0 +1,332 @@
+use std::fmt::{self, Debug, Display};
+use std::ops::Index;
+use std::str::{self, Utf8Error};
+
+use bytes::Bytes;
+
+use {Integer, Utf8StringRef, Value, ValueRef};
+
+/// A string sharing the buffer it was decoded from, which may contain invalid UTF-8 sequence.
+///
+/// See `Utf8String` for the reasoning.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Utf8Bytes {
+    s: Result<Bytes, (Bytes, Utf8Error)>,
+}
+
+impl Utf8Bytes {
+    /// Constructs a string from the given buffer, checking whether it's valid UTF-8.
+    ///
+    /// The buffer is kept in either case, so no data is lost.
+    pub fn from_bytes(buf: Bytes) -> Self {
+        match str::from_utf8(&buf) {
+            Ok(..) => Utf8Bytes { s: Ok(buf) },
+            Err(err) => Utf8Bytes { s: Err((buf, err)) },
+        }
+    }
+
+    /// Returns `true` if the string is valid UTF-8.
+    pub fn is_str(&self) -> bool {
+        self.s.is_ok()
+    }
+
+    /// Returns `true` if the string contains invalid UTF-8 sequence.
+    pub fn is_err(&self) -> bool {
+        self.s.is_err()
+    }
+
+    /// Returns the string reference if the string is valid UTF-8, or else `None`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self.s {
+            // The buffer has been checked on construction.
+            Ok(ref buf) => Some(unsafe { str::from_utf8_unchecked(buf) }),
+            Err(..) => None,
+        }
+    }
+
+    /// Returns the underlying `Utf8Error` if the string contains invalud UTF-8 sequence, or
+    /// else `None`.
+    pub fn as_err(&self) -> Option<&Utf8Error> {
+        match self.s {
+            Ok(..) => None,
+            Err((_, ref err)) => Some(err),
+        }
+    }
+
+    /// Returns the buffer of this string no matter whether it's valid or not UTF-8.
+    pub fn as_bytes(&self) -> &Bytes {
+        match self.s {
+            Ok(ref buf) => buf,
+            Err((ref buf, ..)) => buf,
+        }
+    }
+
+    /// Converts a `Utf8Bytes` into the buffer it shares.
+    pub fn into_bytes(self) -> Bytes {
+        match self.s {
+            Ok(buf) => buf,
+            Err((buf, ..)) => buf,
+        }
+    }
+
+    /// Borrows the string, which is valid UTF-8 or not, as a `Utf8StringRef`.
+    pub fn as_ref(&self) -> Utf8StringRef {
+        match self.s {
+            Ok(..) => Utf8StringRef { s: Ok(self.as_str().expect("string must be valid")) },
+            Err((ref buf, err)) => Utf8StringRef { s: Err((&buf[..], err)) },
+        }
+    }
+}
+
+impl Display for Utf8Bytes {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self.as_str() {
+            Some(s) => write!(fmt, "\"{}\"", s),
+            None => Debug::fmt(&self.as_bytes()[..], fmt),
+        }
+    }
+}
+
+impl From<String> for Utf8Bytes {
+    fn from(val: String) -> Self {
+        Utf8Bytes {
+            s: Ok(Bytes::from(val)),
+        }
+    }
+}
+
+impl From<&'static str> for Utf8Bytes {
+    fn from(val: &'static str) -> Self {
+        Utf8Bytes {
+            s: Ok(Bytes::from_static(val.as_bytes())),
+        }
+    }
+}
+
+/// A MessagePack value, which shares the buffer it was decoded from.
+///
+/// Unlike `ValueRef`, which borrows the buffer for its lifetime, strings, binaries and extensions
+/// of a `ValueBytes` are reference-counted slices of a `Bytes` buffer. Decoding them costs no
+/// copy, while the value may outlive the buffer it was decoded from and be sent across threads.
+///
+/// Available with the `bytes` feature.
+///
+/// # Examples
+///
+/// ```
+/// extern crate bytes;
+/// extern crate rmpv;
+///
+/// use bytes::Bytes;
+/// use rmpv::ValueBytes;
+/// use rmpv::decode::read_value_bytes;
+///
+/// # fn main() {
+/// let mut buf = Bytes::from_static(&[0x92, 0xa2, 0x6c, 0x65, 0xc4, 0x02, 0x00, 0x01]);
+/// let val = read_value_bytes(&mut buf).unwrap();
+///
+/// assert_eq!(Some("le"), val[0].as_str());
+/// assert_eq!(Some(&Bytes::from_static(&[0x00, 0x01])), val[1].as_slice());
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueBytes {
+    /// Nil represents nil.
+    Nil,
+    /// Boolean represents true or false.
+    Boolean(bool),
+    /// Integer represents an integer.
+    ///
+    /// A value of an `Integer` object is limited from `-(2^63)` upto `(2^64)-1`.
+    Integer(Integer),
+    /// A 32-bit floating point number.
+    F32(f32),
+    /// A 64-bit floating point number.
+    F64(f64),
+    /// String extending Raw type represents a UTF-8 string.
+    String(Utf8Bytes),
+    /// Binary extending Raw type represents a byte array.
+    Binary(Bytes),
+    /// Array represents a sequence of objects.
+    Array(Vec<ValueBytes>),
+    /// Map represents key-value pairs of objects.
+    Map(Vec<(ValueBytes, ValueBytes)>),
+    /// Extended implements Extension interface: represents a tuple of type information and a byte
+    /// array where type information is an integer whose meaning is defined by applications.
+    Ext(i8, Bytes),
+}
+
+static NIL_BYTES: ValueBytes = ValueBytes::Nil;
+
+impl ValueBytes {
+    /// Converts a value decoded from the given buffer into a `ValueBytes`, sharing the buffer
+    /// instead of copying.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the strings, binaries or extensions the value refers to lies outside the
+    /// buffer.
+    pub fn from_ref(buf: &Bytes, val: &ValueRef) -> ValueBytes {
+        match *val {
+            ValueRef::Nil => ValueBytes::Nil,
+            ValueRef::Boolean(val) => ValueBytes::Boolean(val),
+            ValueRef::Integer(val) => ValueBytes::Integer(val),
+            ValueRef::F32(val) => ValueBytes::F32(val),
+            ValueRef::F64(val) => ValueBytes::F64(val),
+            ValueRef::String(Utf8StringRef { s: Ok(val) }) => {
+                ValueBytes::String(Utf8Bytes { s: Ok(buf.slice_ref(val.as_bytes())) })
+            }
+            ValueRef::String(Utf8StringRef { s: Err((val, err)) }) => {
+                ValueBytes::String(Utf8Bytes { s: Err((buf.slice_ref(val), err)) })
+            }
+            ValueRef::Binary(val) => ValueBytes::Binary(buf.slice_ref(val)),
+            ValueRef::Array(ref val) => {
+                ValueBytes::Array(val.iter().map(|v| ValueBytes::from_ref(buf, v)).collect())
+            }
+            ValueRef::Map(ref val) => {
+                ValueBytes::Map(val.iter().map(|&(ref k, ref v)| {
+                    (ValueBytes::from_ref(buf, k), ValueBytes::from_ref(buf, v))
+                }).collect())
+            }
+            ValueRef::Ext(ty, val) => ValueBytes::Ext(ty, buf.slice_ref(val)),
+        }
+    }
+
+    /// Returns a non-owning view of this value, borrowing the shared buffers.
+    pub fn as_ref(&self) -> ValueRef {
+        match *self {
+            ValueBytes::Nil => ValueRef::Nil,
+            ValueBytes::Boolean(val) => ValueRef::Boolean(val),
+            ValueBytes::Integer(val) => ValueRef::Integer(val),
+            ValueBytes::F32(val) => ValueRef::F32(val),
+            ValueBytes::F64(val) => ValueRef::F64(val),
+            ValueBytes::String(ref val) => ValueRef::String(val.as_ref()),
+            ValueBytes::Binary(ref val) => ValueRef::Binary(&val[..]),
+            ValueBytes::Array(ref val) => ValueRef::Array(val.iter().map(|v| v.as_ref()).collect()),
+            ValueBytes::Map(ref val) => {
+                ValueRef::Map(val.iter().map(|&(ref k, ref v)| (k.as_ref(), v.as_ref())).collect())
+            }
+            ValueBytes::Ext(ty, ref val) => ValueRef::Ext(ty, &val[..]),
+        }
+    }
+
+    /// Converts this value to an owned `Value`, copying the shared buffers.
+    pub fn to_owned(&self) -> Value {
+        match *self {
+            ValueBytes::Nil => Value::Nil,
+            ValueBytes::Boolean(val) => Value::Boolean(val),
+            ValueBytes::Integer(val) => Value::Integer(val),
+            ValueBytes::F32(val) => Value::F32(val),
+            ValueBytes::F64(val) => Value::F64(val),
+            ValueBytes::String(ref val) => Value::String(val.as_ref().into()),
+            ValueBytes::Binary(ref val) => Value::Binary(val.to_vec()),
+            ValueBytes::Array(ref val) => Value::Array(val.iter().map(|v| v.to_owned()).collect()),
+            ValueBytes::Map(ref val) => {
+                Value::Map(val.iter().map(|&(ref k, ref v)| (k.to_owned(), v.to_owned())).collect())
+            }
+            ValueBytes::Ext(ty, ref val) => Value::Ext(ty, val.to_vec()),
+        }
+    }
+
+    /// If the `ValueBytes` is a valid UTF-8 string, returns a `&str`. Returns `None` otherwise.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            ValueBytes::String(ref val) => val.as_str(),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueBytes` is a binary, returns the shared buffer. Returns `None` otherwise.
+    pub fn as_slice(&self) -> Option<&Bytes> {
+        match *self {
+            ValueBytes::Binary(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueBytes` is an array, returns the associated vector. Returns `None` otherwise.
+    pub fn as_array(&self) -> Option<&Vec<ValueBytes>> {
+        match *self {
+            ValueBytes::Array(ref array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueBytes` is a map, returns the associated vector of key-value tuples. Returns
+    /// `None` otherwise.
+    pub fn as_map(&self) -> Option<&Vec<(ValueBytes, ValueBytes)>> {
+        match *self {
+            ValueBytes::Map(ref map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueBytes` is an extension, returns the type and the shared buffer. Returns `None`
+    /// otherwise.
+    pub fn as_ext(&self) -> Option<(i8, &Bytes)> {
+        match *self {
+            ValueBytes::Ext(ty, ref buf) => Some((ty, buf)),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the element at the given index of an array, or `Nil` if there is no such element or
+/// the value is not an array.
+impl Index<usize> for ValueBytes {
+    type Output = ValueBytes;
+
+    fn index(&self, index: usize) -> &ValueBytes {
+        self.as_array().and_then(|v| v.get(index)).unwrap_or(&NIL_BYTES)
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($ty:ty)*) => {
+        $(
+            impl From<$ty> for ValueBytes {
+                fn from(v: $ty) -> Self {
+                    ValueBytes::Integer(From::from(v))
+                }
+            }
+        )*
+    }
+}
+
+impl_from_integer!(u8 u16 u32 u64 usize i8 i16 i32 i64 isize);
+
+impl From<bool> for ValueBytes {
+    fn from(v: bool) -> Self {
+        ValueBytes::Boolean(v)
+    }
+}
+
+impl From<f32> for ValueBytes {
+    fn from(v: f32) -> Self {
+        ValueBytes::F32(v)
+    }
+}
+
+impl From<f64> for ValueBytes {
+    fn from(v: f64) -> Self {
+        ValueBytes::F64(v)
+    }
+}
+
+impl From<Bytes> for ValueBytes {
+    fn from(val: Bytes) -> Self {
+        ValueBytes::Binary(val)
+    }
+}
+
+impl From<Utf8Bytes> for ValueBytes {
+    fn from(val: Utf8Bytes) -> Self {
+        ValueBytes::String(val)
+    }
+}
+
+impl Display for ValueBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        Display::fmt(&self.as_ref(), f)
+    }
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "async")]
+
+extern crate futures_executor;
+extern crate rmp;
+extern crate rmpv;
+
+use futures_executor::block_on;
+
+use rmp::decode::DecodeLimits;
+
+use rmpv::Value;
+use rmpv::decode::{read_value_async, read_value_async_with_limits};
+use rmpv::encode::write_value_async;
+
+#[test]
+fn pass_value_roundtrip() {
+    let val = Value::Array(vec![
+        Value::from(42),
+        Value::from("le message"),
+        Value::Map(vec![(Value::from(-1), Value::Binary(vec![1, 2, 3]))]),
+        Value::Ext(42, vec![0; 4]),
+    ]);
+
+    let mut buf = Vec::new();
+    block_on(write_value_async(&mut buf, &val)).unwrap();
+    block_on(write_value_async(&mut buf, &Value::Nil)).unwrap();
+
+    let mut rd = &buf[..];
+    assert_eq!(val, block_on(read_value_async(&mut rd)).unwrap());
+    assert_eq!(Value::Nil, block_on(read_value_async(&mut rd)).unwrap());
+    assert!(rd.is_empty());
+}
+
+#[test]
+fn fail_value_truncated() {
+    let buf = [0x92, 0x01];
+    let mut rd = &buf[..];
+
+    assert!(block_on(read_value_async(&mut rd)).is_err());
+}
+
+#[test]
+fn fail_value_over_limits() {
+    let limits = DecodeLimits { max_data_len: 4, ..DecodeLimits::default() };
+
+    let buf = [0x91, 0xa4, 0x61, 0x62, 0x63, 0x64];
+    let mut rd = &buf[..];
+    assert_eq!(Value::Array(vec![Value::from("abcd")]),
+               block_on(read_value_async_with_limits(&mut rd, &limits)).unwrap());
+
+    let buf = [0x91, 0xa5, 0x61, 0x62, 0x63, 0x64, 0x65];
+    let mut rd = &buf[..];
+    let err = block_on(read_value_async_with_limits(&mut rd, &limits)).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+    // The value is rejected by its header, so its data is left unread.
+    assert_eq!(5, rd.len());
+}
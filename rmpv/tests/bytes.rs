@@ -0,0 +1,117 @@
+#![cfg(feature = "bytes")]
+
+extern crate bytes;
+extern crate rmp;
+extern crate rmpv;
+
+use std::io::ErrorKind;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use rmp::decode::DecodeLimits;
+use rmpv::{Utf8Bytes, Value, ValueBytes};
+use rmpv::decode::{read_value_bytes, read_value_bytes_with_limits};
+use rmpv::encode::{write_value, write_value_bytes};
+
+#[test]
+fn from_bytes_shares_buffer() {
+    let buf = Bytes::from_static(&[0x93, 0xa2, 0x6c, 0x65, 0xc4, 0x02, 0x00, 0x01, 0xd5, 0x2a, 0x03, 0x04]);
+    let mut rd = buf.clone();
+
+    let val = read_value_bytes(&mut rd).unwrap();
+    assert!(rd.is_empty());
+
+    let expected = ValueBytes::Array(vec![
+        ValueBytes::String(Utf8Bytes::from("le")),
+        ValueBytes::Binary(Bytes::from_static(&[0x00, 0x01])),
+        ValueBytes::Ext(42, Bytes::from_static(&[0x03, 0x04])),
+    ]);
+    assert_eq!(expected, val);
+
+    // Payloads point into the original buffer.
+    let range = buf.as_ptr() as usize..buf.as_ptr() as usize + buf.len();
+    assert!(range.contains(&(val[0].as_str().unwrap().as_ptr() as usize)));
+    assert!(range.contains(&(val[1].as_slice().unwrap().as_ptr() as usize)));
+    assert!(range.contains(&(val[2].as_ext().unwrap().1.as_ptr() as usize)));
+}
+
+#[test]
+fn from_bytes_outlives_buffer() {
+    let val = {
+        let mut buf = Bytes::from(vec![0xc4, 0x03, 0x01, 0x02, 0x03]);
+        read_value_bytes(&mut buf).unwrap()
+    };
+
+    assert_eq!(Some(&Bytes::from_static(&[0x01, 0x02, 0x03])), val.as_slice());
+}
+
+#[test]
+fn from_bytes_invalid_utf8() {
+    let mut buf = Bytes::from_static(&[0xa2, 0xc3, 0x28]);
+
+    let val = read_value_bytes(&mut buf).unwrap();
+    match val {
+        ValueBytes::String(ref s) => {
+            assert!(s.is_err());
+            assert_eq!(&[0xc3, 0x28][..], &s.as_bytes()[..]);
+        }
+        ref val => panic!("unexpected value: {:?}", val),
+    }
+}
+
+#[test]
+fn from_bytes_several_values() {
+    let mut buf = Bytes::from_static(&[0x2a, 0xc0, 0xc3]);
+
+    assert_eq!(ValueBytes::from(42), read_value_bytes(&mut buf).unwrap());
+    assert_eq!(ValueBytes::Nil, read_value_bytes(&mut buf).unwrap());
+    assert_eq!(ValueBytes::from(true), read_value_bytes(&mut buf).unwrap());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn from_bytes_truncated_keeps_buffer() {
+    let mut buf = Bytes::from_static(&[0x92, 0x2a]);
+
+    let err = read_value_bytes(&mut buf).unwrap_err();
+    assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+    assert_eq!(&[0x92, 0x2a][..], &buf[..]);
+}
+
+#[test]
+fn from_bytes_with_limits() {
+    let mut buf = Bytes::from_static(&[0xc4, 0x03, 0x01, 0x02, 0x03]);
+    let limits = DecodeLimits { max_data_len: 2, ..DecodeLimits::default() };
+
+    let err = read_value_bytes_with_limits(&mut buf, &limits).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    assert_eq!(5, buf.len());
+}
+
+#[test]
+fn to_owned_matches_value() {
+    let val = Value::Map(vec![
+        (Value::from("key"), Value::Array(vec![Value::from(-1), Value::F64(0.5)])),
+        (Value::from(1), Value::Ext(1, vec![0x00])),
+    ]);
+
+    let mut buf = Vec::new();
+    write_value(&mut buf, &val).unwrap();
+
+    let mut rd = Bytes::from(buf);
+    assert_eq!(val, read_value_bytes(&mut rd).unwrap().to_owned());
+}
+
+#[test]
+fn write_into_bytes_mut() {
+    let val = ValueBytes::Map(vec![
+        (ValueBytes::String(Utf8Bytes::from("le")), ValueBytes::Array(vec![ValueBytes::Nil])),
+        (ValueBytes::from(1), ValueBytes::Ext(1, Bytes::from_static(&[0x00]))),
+    ]);
+
+    let mut buf = BytesMut::new();
+    write_value_bytes(&mut (&mut buf).writer(), &val).unwrap();
+
+    let mut rd = buf.freeze();
+    assert_eq!(val, read_value_bytes(&mut rd).unwrap());
+}
@@ -1,7 +1,12 @@
+extern crate rmp;
 extern crate rmpv;
 
+use std::io::ErrorKind;
+
+use rmp::decode::{DecodeLimits, LimitError, Unpacker};
+
 use rmpv::Value;
-use rmpv::decode::{read_value, Error};
+use rmpv::decode::{read_value, read_value_with_limits, next_value, Error};
 
 #[test]
 fn from_null_decode_value() {
@@ -363,3 +368,118 @@ fn from_array_of_two_integers() {
     let vec = vec![Value::from(4), Value::from(42)];
     assert_eq!(Value::Array(vec), read_value(&mut &buf[..]).unwrap());
 }
+
+#[test]
+fn from_unpacker_byte_by_byte() {
+    // {"a": [1, 2.5]}, nil
+    let buf = [0x81, 0xa1, 0x61, 0x92, 0x01, 0xcb, 0x40, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0];
+
+    let mut unpacker = Unpacker::new();
+    let mut values = Vec::new();
+    for byte in buf.iter() {
+        unpacker.feed(&[*byte]);
+        while let Some(val) = next_value(&mut unpacker).unwrap() {
+            values.push(val);
+        }
+    }
+
+    let map = Value::Map(vec![(Value::from("a"), Value::Array(vec![Value::from(1), Value::F64(2.5)]))]);
+    assert_eq!(vec![map, Value::Nil], values);
+}
+
+#[test]
+fn from_unpacker_with_limits() {
+    // 1500 nested arrays, deeper than the default limit.
+    let mut buf = vec![0x91; 1499];
+    buf.push(0x90);
+
+    let mut unpacker = Unpacker::with_limits(DecodeLimits { max_depth: 2000, ..DecodeLimits::default() });
+    unpacker.feed(&buf);
+
+    let mut val = next_value(&mut unpacker).unwrap().unwrap();
+    let mut depth = 1;
+    while let Value::Array(mut vec) = val {
+        match vec.pop() {
+            Some(v) => val = v,
+            None => break,
+        }
+        depth += 1;
+    }
+    assert_eq!(1500, depth);
+}
+
+fn limit_error(err: Error) -> LimitError {
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    let err: std::io::Error = err.into();
+    *err.get_ref().unwrap().downcast_ref::<LimitError>().unwrap()
+}
+
+#[test]
+fn from_array32_of_hostile_length_fails_without_allocation() {
+    // Claims 4 billion elements, but contains none.
+    let buf = [0xdd, 0xff, 0xff, 0xff, 0xff];
+
+    match read_value(&mut &buf[..]) {
+        Err(Error::InvalidMarkerRead(ref err)) if err.kind() == ErrorKind::UnexpectedEof => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_bin32_of_hostile_length_fails_without_allocation() {
+    let buf = [0xc6, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+    match read_value(&mut &buf[..]) {
+        Err(Error::InvalidDataRead(ref err)) if err.kind() == ErrorKind::UnexpectedEof => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn from_str_exceeding_data_limit_fails() {
+    let limits = DecodeLimits { max_data_len: 2, ..DecodeLimits::default() };
+
+    let buf = [0xa2, 0x61, 0x62];
+    assert_eq!(Value::from("ab"), read_value_with_limits(&mut &buf[..], &limits).unwrap());
+
+    let buf = [0xd9, 0x03, 0x61, 0x62, 0x63];
+    let err = read_value_with_limits(&mut &buf[..], &limits).unwrap_err();
+    assert_eq!(LimitError::LengthLimitExceeded(rmp::Marker::Str8), limit_error(err));
+}
+
+#[test]
+fn from_map_exceeding_container_limit_fails() {
+    let limits = DecodeLimits { max_container_len: 1, ..DecodeLimits::default() };
+
+    let buf = [0x91, 0x82, 0x01, 0x02, 0x03, 0x04];
+    let err = read_value_with_limits(&mut &buf[..], &limits).unwrap_err();
+    assert_eq!(LimitError::LengthLimitExceeded(rmp::Marker::FixMap(2)), limit_error(err));
+}
+
+#[test]
+fn from_array_exceeding_total_limit_fails() {
+    let limits = DecodeLimits { max_total_len: 8, ..DecodeLimits::default() };
+
+    let buf = [0x93, 0xcd, 0x01, 0x00, 0xcd, 0x02, 0x00, 0x01];
+    assert_eq!(8, rmp::decode::validate(&buf, &DecodeLimits::default()).unwrap());
+    read_value_with_limits(&mut &buf[..], &limits).unwrap();
+
+    let buf = [0x93, 0xcd, 0x01, 0x00, 0xcd, 0x02, 0x00, 0xcd, 0x03, 0x00];
+    let err = read_value_with_limits(&mut &buf[..], &limits).unwrap_err();
+    assert_eq!(LimitError::TotalLimitExceeded, limit_error(err));
+}
+
+#[test]
+fn from_deeply_nested_array_fails() {
+    let mut buf = vec![0x91; 100000];
+    buf.push(0xc0);
+
+    let err = read_value(&mut &buf[..]).unwrap_err();
+    assert_eq!(LimitError::DepthLimitExceeded, limit_error(err));
+
+    let limits = DecodeLimits { max_depth: 2, ..DecodeLimits::default() };
+    let buf = [0x91, 0x91, 0xc0];
+    read_value_with_limits(&mut &buf[..], &limits).unwrap();
+    let buf = [0x91, 0x91, 0x90];
+    assert!(read_value_with_limits(&mut &buf[..], &limits).is_err());
+}
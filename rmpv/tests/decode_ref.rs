@@ -1,7 +1,10 @@
+extern crate rmp;
 extern crate rmpv;
 
+use rmp::decode::DecodeLimits;
+
 use rmpv::ValueRef;
-use rmpv::decode::{read_value_ref, Error};
+use rmpv::decode::{read_value_ref, read_value_ref_with_limits, Error};
 
 #[test]
 fn from_nil() {
@@ -689,3 +692,21 @@ fn into_owned() {
     assert_eq!(expected, val.to_owned());
     assert_eq!(expected.as_ref(), val);
 }
+
+#[test]
+fn from_array32_of_hostile_length_fails() {
+    let buf = [0xdd, 0xff, 0xff, 0xff, 0xff];
+
+    assert!(read_value_ref(&mut &buf[..]).is_err());
+}
+
+#[test]
+fn from_bin_exceeding_limits_fails() {
+    let limits = DecodeLimits { max_data_len: 2, ..DecodeLimits::default() };
+
+    let buf = [0xc4, 0x02, 0x01, 0x02];
+    assert_eq!(ValueRef::Binary(&[1, 2]), read_value_ref_with_limits(&mut &buf[..], &limits).unwrap());
+
+    let buf = [0x91, 0xc4, 0x03, 0x01, 0x02, 0x03];
+    assert!(read_value_ref_with_limits(&mut &buf[..], &limits).is_err());
+}
@@ -131,3 +131,11 @@ fn pass_pack_ext() {
         &ValueRef::Ext(16, &[0x01, 0x02, 0x03])
     );
 }
+
+#[test]
+fn pass_pack_ext_with_reserved_type() {
+    check_packed_eq(
+        &vec![0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5],
+        &ValueRef::Ext(-1, &[0x5a, 0x4a, 0xf6, 0xa5])
+    );
+}
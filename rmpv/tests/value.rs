@@ -1,6 +1,6 @@
 extern crate rmpv;
 
-use rmpv::Value;
+use rmpv::{Endian, Timestamp, TypedArray, Value};
 
 #[test]
 fn display_nil() {
@@ -148,3 +148,51 @@ fn monadic_index() {
     assert!(val[2].is_nil());
     assert!(val[1][2][3][4][5].is_nil());
 }
+
+#[test]
+fn from_timestamp() {
+    let ts = Timestamp::new(1514862245, 678901234).unwrap();
+
+    assert_eq!(Value::Ext(-1, vec![0xa1, 0xdc, 0xd7, 0xc8, 0x5a, 0x4a, 0xf6, 0xa5]), Value::from(ts));
+}
+
+#[test]
+fn from_i128() {
+    let val = Value::from_i128(-2, 125);
+
+    assert_eq!(Some((125, &[0xff; 15][..])), val.as_ext().map(|(ty, buf)| (ty, &buf[..15])));
+    assert_eq!(Some(-2), val.as_i128(125));
+    assert_eq!(None, val.as_i128(126));
+    assert_eq!(None, val.as_u128(126));
+    assert_eq!(None, val.as_i64());
+}
+
+#[test]
+fn as_u128_from_ext_and_integer() {
+    let val = Value::from_u128(u64::max_value() as u128 + 1, 7);
+
+    assert_eq!(Some(u64::max_value() as u128 + 1), val.as_u128(7));
+    assert_eq!(Some(u64::max_value() as u128), Value::from(u64::max_value()).as_u128(7));
+    // Wrong payload length.
+    assert_eq!(None, Value::Ext(7, vec![0; 8]).as_u128(7));
+}
+
+#[test]
+fn from_typed_array() {
+    let array = TypedArray::from_i32(&[1, 2], Endian::Big);
+    let val = Value::from_typed_array(&array, 127);
+
+    assert_eq!(Value::Ext(127, vec![0x05, 0, 0, 0, 1, 0, 0, 0, 2]), val);
+    assert_eq!(Some(array), val.as_typed_array(127));
+    assert_eq!(None, val.as_typed_array(3));
+    // Truncated element.
+    assert_eq!(None, Value::Ext(127, vec![0x05, 0, 0, 0]).as_typed_array(127));
+}
+
+#[test]
+fn as_timestamp_from_invalid_ext() {
+    // Nanoseconds are out of range.
+    assert_eq!(None, Value::Ext(-1, vec![0xee, 0x6b, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00]).as_timestamp());
+    // Unsupported data length.
+    assert_eq!(None, Value::Ext(-1, vec![0x00, 0x00]).as_timestamp());
+}